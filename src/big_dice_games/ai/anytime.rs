@@ -0,0 +1,342 @@
+// big_dice_games/ai/anytime.rs
+//
+// Searches that trade optimality for speed, each one reporting the
+// suboptimality bound it can prove for the path it returns:
+//
+//   weighted A*  - f = g + epsilon * h
+//   ARA*         - Likhachev, Gordon & Thrun 2003, a chain of weighted
+//                  A* searches with decreasing epsilon that reuse
+//                  previous work
+//   focal search - Pearl & Kim's A*_epsilon, picks from the nodes
+//                  within epsilon of the best f, preferring low h
+//
+// The heuristic must be admissible for the bounds to mean anything.
+
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::big_dice_games::ai::astar::build_path;
+use crate::big_dice_games::ai::astar::SearchRecord;
+use crate::big_dice_games::ai::graph::NavGraph;
+
+// ARA* always lowers epsilon by at least this much per pass
+const MIN_EPSILON_STEP: f32 = 0.01;
+
+pub struct BoundedSolution
+{
+    // start .. end inclusive, empty if the end can't be reached
+    pub path: Vec<i32>,
+    pub cost: f32,
+
+    // the inflation the search was run with
+    pub epsilon: f32,
+    // cost is proven to be within bound * optimal
+    pub bound: f32,
+
+    pub expanded: Vec<i32>,
+}
+
+impl BoundedSolution {
+    pub fn found(&self) -> bool {
+	!self.path.is_empty()
+    }
+}
+
+// cost / lower bound, never more than epsilon claims and never below 1
+fn achieved_bound(cost: f32, lower_bound: f32, epsilon: f32) -> f32 {
+    if lower_bound <= 0.0 {
+	return if cost <= 0.0 { 1.0 } else { epsilon };
+    }
+
+    (cost / lower_bound).min(epsilon).max(1.0)
+}
+
+pub fn weighted_a_star<G, H>(graph: &G, start: i32, end: i32,
+			     heuristic: H, epsilon: f32) -> BoundedSolution
+where G: NavGraph + ?Sized, H: Fn(i32, i32) -> f32
+{
+    let mut open_nodes = BinaryHeap::<SearchRecord>::new();
+    let mut found_distances = HashMap::<i32, f32>::new();
+    let mut closed_set = HashSet::<i32>::new();
+    let mut prev_index = HashMap::<i32, i32>::new();
+    let mut expanded = Vec::<i32>::new();
+
+    open_nodes.push(SearchRecord {
+	key: epsilon * heuristic(start, end),
+	distance_travelled: 0.0,
+	index: start,
+    });
+    found_distances.insert(start, 0.0);
+    prev_index.insert(start, -1);
+
+    while let Some(n) = open_nodes.pop() {
+	if closed_set.contains(&n.index) ||
+	    n.distance_travelled > found_distances[&n.index] {
+	    continue;
+	}
+	closed_set.insert(n.index);
+	expanded.push(n.index);
+
+	if n.index == end {
+	    // whatever is still open bounds the optimal cost from below
+	    let mut lower_bound = n.distance_travelled;
+	    for r in open_nodes.iter() {
+		if closed_set.contains(&r.index) {
+		    continue;
+		}
+		lower_bound = lower_bound.min(
+		    r.distance_travelled + heuristic(r.index, end));
+	    }
+
+	    return BoundedSolution {
+		path: build_path(&prev_index, end),
+		cost: n.distance_travelled,
+		epsilon: epsilon,
+		bound: achieved_bound(n.distance_travelled, lower_bound, epsilon),
+		expanded: expanded,
+	    };
+	}
+
+	for (neighbor_index, step_cost) in graph.neighbors(n.index) {
+	    let new_elapsed_dist = n.distance_travelled + step_cost;
+
+	    if let Some(d) = found_distances.get(&neighbor_index) {
+		if *d <= new_elapsed_dist {
+		    continue;
+		}
+	    }
+
+	    open_nodes.push(SearchRecord {
+		key: new_elapsed_dist + epsilon * heuristic(neighbor_index, end),
+		distance_travelled: new_elapsed_dist,
+		index: neighbor_index,
+	    });
+	    found_distances.insert(neighbor_index, new_elapsed_dist);
+	    prev_index.insert(neighbor_index, n.index);
+
+	    // reopen, otherwise the open list isn't a lower bound
+	    closed_set.remove(&neighbor_index);
+	}
+    }
+
+    BoundedSolution {
+	path: Vec::new(),
+	cost: f32::INFINITY,
+	epsilon: epsilon,
+	bound: f32::INFINITY,
+	expanded: expanded,
+    }
+}
+
+// Anytime Repairing A*. Returns every solution published on the way
+// down from start_epsilon to 1, each one no worse than the last.
+pub fn ara_star<G, H>(graph: &G, start: i32, end: i32, heuristic: H,
+		      start_epsilon: f32, epsilon_step: f32) -> Vec<BoundedSolution>
+where G: NavGraph + ?Sized, H: Fn(i32, i32) -> f32
+{
+    let mut out_vec = Vec::<BoundedSolution>::new();
+
+    let mut found_distances = HashMap::<i32, f32>::new();
+    let mut prev_index = HashMap::<i32, i32>::new();
+    let mut open_set = HashSet::<i32>::new();
+    let mut closed_set = HashSet::<i32>::new();
+    // nodes improved after being closed in the current pass
+    let mut incons_set = HashSet::<i32>::new();
+
+    let g = |distances: &HashMap<i32, f32>, i: i32| -> f32 {
+	match distances.get(&i) {
+	    None => f32::INFINITY,
+	    Some(d) => *d,
+	}
+    };
+
+    found_distances.insert(start, 0.0);
+    prev_index.insert(start, -1);
+    open_set.insert(start);
+
+    let mut epsilon = start_epsilon.max(1.0);
+    let epsilon_step = epsilon_step.max(MIN_EPSILON_STEP);
+
+    loop {
+	// (re)key the open list for this epsilon
+	let mut open_nodes = BinaryHeap::<SearchRecord>::new();
+	for i in incons_set.drain() {
+	    open_set.insert(i);
+	}
+	for i in open_set.iter() {
+	    let d = g(&found_distances, *i);
+	    open_nodes.push(SearchRecord {
+		key: d + epsilon * heuristic(*i, end),
+		distance_travelled: d,
+		index: *i,
+	    });
+	}
+	closed_set.clear();
+
+	let mut expanded = Vec::<i32>::new();
+
+	// ImprovePath
+	loop {
+	    // drop records that have been superseded or closed
+	    while let Some(r) = open_nodes.peek() {
+		if open_set.contains(&r.index) &&
+		    r.distance_travelled <= g(&found_distances, r.index) {
+		    break;
+		}
+		open_nodes.pop();
+	    }
+
+	    let n = match open_nodes.peek() {
+		None => { break; }
+		Some(r) => r,
+	    };
+
+	    if g(&found_distances, end) <= n.key {
+		break;
+	    }
+
+	    let n = open_nodes.pop().unwrap();
+	    open_set.remove(&n.index);
+	    closed_set.insert(n.index);
+	    expanded.push(n.index);
+
+	    for (neighbor_index, step_cost) in graph.neighbors(n.index) {
+		let new_elapsed_dist = n.distance_travelled + step_cost;
+
+		if g(&found_distances, neighbor_index) <= new_elapsed_dist {
+		    continue;
+		}
+
+		found_distances.insert(neighbor_index, new_elapsed_dist);
+		prev_index.insert(neighbor_index, n.index);
+
+		if closed_set.contains(&neighbor_index) {
+		    incons_set.insert(neighbor_index);
+		} else {
+		    open_set.insert(neighbor_index);
+		    open_nodes.push(SearchRecord {
+			key: new_elapsed_dist + epsilon * heuristic(neighbor_index, end),
+			distance_travelled: new_elapsed_dist,
+			index: neighbor_index,
+		    });
+		}
+	    }
+	}
+
+	let cost = g(&found_distances, end);
+	if cost.is_infinite() {
+	    break;
+	}
+
+	let mut lower_bound = cost;
+	for i in open_set.iter().chain(incons_set.iter()) {
+	    lower_bound = lower_bound.min(
+		g(&found_distances, *i) + heuristic(*i, end));
+	}
+	let bound = achieved_bound(cost, lower_bound, epsilon);
+
+	out_vec.push(BoundedSolution {
+	    path: build_path(&prev_index, end),
+	    cost: cost,
+	    epsilon: epsilon,
+	    bound: bound,
+	    expanded: expanded,
+	});
+
+	if bound <= 1.0 || epsilon <= 1.0 {
+	    break;
+	}
+
+	epsilon = (epsilon - epsilon_step).max(1.0);
+    }
+
+    out_vec
+}
+
+// A*_epsilon: expand the node with the lowest h out of the focal list,
+// those open nodes with f <= epsilon * smallest f. The open list is
+// scanned linearly, which is fine at the size of our nav graphs.
+pub fn focal_search<G, H>(graph: &G, start: i32, end: i32,
+			  heuristic: H, epsilon: f32) -> BoundedSolution
+where G: NavGraph + ?Sized, H: Fn(i32, i32) -> f32
+{
+    let mut found_distances = HashMap::<i32, f32>::new();
+    let mut prev_index = HashMap::<i32, i32>::new();
+    let mut open_set = HashSet::<i32>::new();
+    let mut expanded = Vec::<i32>::new();
+
+    // below 1 the focal list could leave out even the best node
+    let epsilon = epsilon.max(1.0);
+
+    found_distances.insert(start, 0.0);
+    prev_index.insert(start, -1);
+    open_set.insert(start);
+
+    while !open_set.is_empty() {
+	let mut f_min = f32::INFINITY;
+	for i in open_set.iter() {
+	    f_min = f_min.min(found_distances[i] + heuristic(*i, end));
+	}
+
+	let mut best_index = -1;
+	let mut best_h = f32::INFINITY;
+	let mut best_g = 0.0;
+	for i in open_set.iter() {
+	    let d = found_distances[i];
+	    let h = heuristic(*i, end);
+	    if d + h > epsilon * f_min {
+		continue;
+	    }
+	    if (h < best_h) ||
+		(h == best_h && d > best_g) ||
+		(h == best_h && d == best_g && *i < best_index) {
+		best_index = *i;
+		best_h = h;
+		best_g = d;
+	    }
+	}
+
+	// only infinite or NaN estimates are left open, nothing to pick
+	if best_index < 0 {
+	    break;
+	}
+
+	open_set.remove(&best_index);
+	expanded.push(best_index);
+
+	if best_index == end {
+	    return BoundedSolution {
+		path: build_path(&prev_index, end),
+		cost: best_g,
+		epsilon: epsilon,
+		bound: achieved_bound(best_g, f_min, epsilon),
+		expanded: expanded,
+	    };
+	}
+
+	for (neighbor_index, step_cost) in graph.neighbors(best_index) {
+	    let new_elapsed_dist = best_g + step_cost;
+
+	    if let Some(d) = found_distances.get(&neighbor_index) {
+		if *d <= new_elapsed_dist {
+		    continue;
+		}
+	    }
+
+	    found_distances.insert(neighbor_index, new_elapsed_dist);
+	    prev_index.insert(neighbor_index, best_index);
+
+	    // this reopens closed nodes, which the bound relies on
+	    open_set.insert(neighbor_index);
+	}
+    }
+
+    BoundedSolution {
+	path: Vec::new(),
+	cost: f32::INFINITY,
+	epsilon: epsilon,
+	bound: f32::INFINITY,
+	expanded: expanded,
+    }
+}
@@ -1,2 +1,159 @@
 // A* Implementation
 
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::big_dice_games::ai::graph::NavGraph;
+
+#[derive(Debug)]
+pub struct SearchRecord
+{
+    pub key: f32,
+    pub distance_travelled: f32,
+    pub index: i32,
+}
+
+impl Ord for SearchRecord
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+	// Note, reverse order so we have a min-heap
+	if self.key > other.key {
+	    return Ordering::Less;
+	} else if self.key < other.key {
+	    return Ordering::Greater;
+	}
+
+	// prefer the record that has travelled further
+	if self.distance_travelled > other.distance_travelled {
+	    return Ordering::Greater;
+	} else if self.distance_travelled < other.distance_travelled {
+	    return Ordering::Less;
+	}
+
+	self.index.cmp(&other.index)
+    }
+}
+
+impl PartialOrd for SearchRecord
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+	Some(self.cmp(other))
+    }
+}
+
+impl Eq for SearchRecord
+{
+
+}
+
+impl PartialEq for SearchRecord
+{
+    fn eq(&self, other: &Self) -> bool {
+	self.index == other.index
+    }
+}
+
+pub struct SearchResult
+{
+    // start .. goal inclusive, empty if the goal can't be reached
+    pub path: Vec<i32>,
+    pub cost: f32,
+
+    // nodes in the order they were taken off the open list
+    pub expanded: Vec<i32>,
+
+    pub prev_index: HashMap<i32, i32>,
+}
+
+impl SearchResult {
+    pub fn found(&self) -> bool {
+	!self.path.is_empty()
+    }
+}
+
+// walk prev_index back from end until we hit the -1 the start was given
+pub fn build_path(prev_index: &HashMap<i32, i32>, end: i32) -> Vec<i32> {
+    let mut out_vec = Vec::<i32>::new();
+
+    if !prev_index.contains_key(&end) {
+	return out_vec;
+    }
+
+    let mut i = end;
+    while i != -1 {
+	out_vec.push(i);
+	i = prev_index[&i];
+    }
+
+    out_vec.reverse();
+    out_vec
+}
+
+// heuristic(a, b) estimates the cost of travelling from a to b
+pub fn a_star<G, H>(graph: &G, start: i32, end: i32, heuristic: H) -> SearchResult
+where G: NavGraph + ?Sized, H: Fn(i32, i32) -> f32
+{
+    let mut open_nodes = BinaryHeap::<SearchRecord>::new();
+    let mut found_distances = HashMap::<i32, f32>::new();
+    let mut closed_set = HashSet::<i32>::new();
+    let mut prev_index = HashMap::<i32, i32>::new();
+    let mut expanded = Vec::<i32>::new();
+
+    open_nodes.push(SearchRecord {
+	key: heuristic(start, end),
+	distance_travelled: 0.0,
+	index: start,
+    });
+    found_distances.insert(start, 0.0);
+    prev_index.insert(start, -1);
+
+    while let Some(n) = open_nodes.pop() {
+	if closed_set.contains(&n.index) {
+	    continue;
+	}
+	closed_set.insert(n.index);
+	expanded.push(n.index);
+
+	if n.index == end {
+	    return SearchResult {
+		path: build_path(&prev_index, end),
+		cost: n.distance_travelled,
+		expanded: expanded,
+		prev_index: prev_index,
+	    };
+	}
+
+	for (neighbor_index, step_cost) in graph.neighbors(n.index) {
+	    let new_elapsed_dist = n.distance_travelled + step_cost;
+
+	    if let Some(d) = found_distances.get(&neighbor_index) {
+		if *d <= new_elapsed_dist {
+		    continue;
+		}
+	    }
+
+	    open_nodes.push(SearchRecord {
+		key: new_elapsed_dist + heuristic(neighbor_index, end),
+		distance_travelled: new_elapsed_dist,
+		index: neighbor_index,
+	    });
+	    found_distances.insert(neighbor_index, new_elapsed_dist);
+	    prev_index.insert(neighbor_index, n.index);
+	}
+    }
+
+    SearchResult {
+	path: Vec::new(),
+	cost: f32::INFINITY,
+	expanded: expanded,
+	prev_index: prev_index,
+    }
+}
+
+pub fn dijkstra<G>(graph: &G, start: i32, end: i32) -> SearchResult
+where G: NavGraph + ?Sized
+{
+    a_star(graph, start, end, |_, _| 0.0)
+}
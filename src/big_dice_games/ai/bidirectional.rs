@@ -0,0 +1,188 @@
+// big_dice_games/ai/bidirectional.rs
+//
+// Bidirectional A* and Dijkstra. Both sides use the "average"
+// potential from Ikeda et al. / Goldberg & Harrelson:
+//
+//   p(v) = (h(v, end) - h(v, start)) / 2
+//
+// the forward side is keyed on g_f(v) + p(v) and the backward side on
+// g_b(v) - p(v). With a consistent heuristic both key sets are
+// monotone, and the search can stop as soon as
+//
+//   top_forward + top_backward >= best path seen so far
+//
+// Dijkstra is the same thing with p = 0.
+
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::big_dice_games::ai::astar::build_path;
+use crate::big_dice_games::ai::astar::SearchRecord;
+use crate::big_dice_games::ai::graph::NavGraph;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SearchDirection {
+    Forward,
+    Backward,
+}
+
+pub struct BidirectionalResult
+{
+    // start .. end inclusive, empty if the end can't be reached
+    pub path: Vec<i32>,
+    pub cost: f32,
+
+    // every expansion from both frontiers, in the order it happened
+    pub expanded: Vec<(i32, SearchDirection)>,
+
+    pub forward_prev: HashMap<i32, i32>,
+    pub backward_prev: HashMap<i32, i32>,
+
+    pub meeting_index: i32,
+}
+
+impl BidirectionalResult {
+    pub fn found(&self) -> bool {
+	!self.path.is_empty()
+    }
+
+    pub fn expanded_count(&self, dir: SearchDirection) -> usize {
+	self.expanded.iter().filter(|(_, d)| *d == dir).count()
+    }
+}
+
+struct Frontier
+{
+    open_nodes: BinaryHeap<SearchRecord>,
+    found_distances: HashMap<i32, f32>,
+    closed_set: HashSet<i32>,
+    prev_index: HashMap<i32, i32>,
+}
+
+impl Frontier {
+    fn new(origin: i32, key: f32) -> Frontier {
+	let mut frontier = Frontier {
+	    open_nodes: BinaryHeap::new(),
+	    found_distances: HashMap::new(),
+	    closed_set: HashSet::new(),
+	    prev_index: HashMap::new(),
+	};
+
+	frontier.open_nodes.push(SearchRecord {
+	    key: key,
+	    distance_travelled: 0.0,
+	    index: origin,
+	});
+	frontier.found_distances.insert(origin, 0.0);
+	frontier.prev_index.insert(origin, -1);
+
+	frontier
+    }
+
+    fn top_key(&self) -> f32 {
+	match self.open_nodes.peek() {
+	    None => f32::INFINITY,
+	    Some(r) => r.key,
+	}
+    }
+}
+
+pub fn bidirectional_a_star<G, H>(graph: &G, start: i32, end: i32, heuristic: H)
+				  -> BidirectionalResult
+where G: NavGraph + ?Sized, H: Fn(i32, i32) -> f32
+{
+    let potential = |v: i32| -> f32 {
+	(heuristic(v, end) - heuristic(v, start)) * 0.5
+    };
+
+    let mut forward = Frontier::new(start, potential(start));
+    let mut backward = Frontier::new(end, -potential(end));
+
+    let mut expanded = Vec::<(i32, SearchDirection)>::new();
+
+    let mut best_cost = f32::INFINITY;
+    let mut meeting_index = -1;
+
+    if start == end {
+	best_cost = 0.0;
+	meeting_index = start;
+    }
+
+    while !forward.open_nodes.is_empty() && !backward.open_nodes.is_empty() {
+	if forward.top_key() + backward.top_key() >= best_cost {
+	    break;
+	}
+
+	// grow whichever frontier is currently smaller
+	let dir = if forward.open_nodes.len() <= backward.open_nodes.len() {
+	    SearchDirection::Forward
+	} else {
+	    SearchDirection::Backward
+	};
+
+	let (this_side, other_side, sign) = match dir {
+	    SearchDirection::Forward => (&mut forward, &backward, 1.0),
+	    SearchDirection::Backward => (&mut backward, &forward, -1.0),
+	};
+
+	let n = this_side.open_nodes.pop().unwrap();
+	if this_side.closed_set.contains(&n.index) {
+	    continue;
+	}
+	this_side.closed_set.insert(n.index);
+	expanded.push((n.index, dir));
+
+	for (neighbor_index, step_cost) in graph.neighbors(n.index) {
+	    let new_elapsed_dist = n.distance_travelled + step_cost;
+
+	    if let Some(d) = this_side.found_distances.get(&neighbor_index) {
+		if *d <= new_elapsed_dist {
+		    continue;
+		}
+	    }
+
+	    this_side.open_nodes.push(SearchRecord {
+		key: new_elapsed_dist + sign * potential(neighbor_index),
+		distance_travelled: new_elapsed_dist,
+		index: neighbor_index,
+	    });
+	    this_side.found_distances.insert(neighbor_index, new_elapsed_dist);
+	    this_side.prev_index.insert(neighbor_index, n.index);
+
+	    if let Some(d) = other_side.found_distances.get(&neighbor_index) {
+		if new_elapsed_dist + *d < best_cost {
+		    best_cost = new_elapsed_dist + *d;
+		    meeting_index = neighbor_index;
+		}
+	    }
+	}
+    }
+
+    let mut path = Vec::<i32>::new();
+    if meeting_index >= 0 {
+	path = build_path(&forward.prev_index, meeting_index);
+
+	// the backward tree points towards the end
+	let mut i = backward.prev_index[&meeting_index];
+	while i != -1 {
+	    path.push(i);
+	    i = backward.prev_index[&i];
+	}
+    }
+
+    BidirectionalResult {
+	path: path,
+	cost: best_cost,
+	expanded: expanded,
+	forward_prev: forward.prev_index,
+	backward_prev: backward.prev_index,
+	meeting_index: meeting_index,
+    }
+}
+
+pub fn bidirectional_dijkstra<G>(graph: &G, start: i32, end: i32) -> BidirectionalResult
+where G: NavGraph + ?Sized
+{
+    bidirectional_a_star(graph, start, end, |_, _| 0.0)
+}
@@ -0,0 +1,356 @@
+// big_dice_games/ai/cbs.rs
+//
+// Multi-agent path finding with Conflict-Based Search (Sharon, Stern,
+// Felner & Sturtevant 2015). Agents move in lock-step: every time step
+// each agent either moves to a neighbor or waits. The high level
+// searches a tree of constraint sets, the low level is A* in
+// (node, time) space that respects the constraints for one agent.
+//
+// Cost is sum of arrival times. Two agents conflict if they are on the
+// same node at the same time (vertex conflict) or swap nodes across
+// one step (edge conflict).
+
+use std::cmp::Ordering;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use crate::big_dice_games::ai::graph::NavGraph;
+
+// give up on the high level after this many constraint tree nodes
+const MAX_CT_EXPANSIONS: usize = 5000;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Constraint {
+    // agent may not be at index at time
+    Vertex { agent: usize, index: i32, time: i32 },
+    // agent may not step from -> to leaving at time
+    Edge { agent: usize, from: i32, to: i32, time: i32 },
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Conflict {
+    Vertex { a: usize, b: usize, index: i32, time: i32 },
+    Edge { a: usize, b: usize, time: i32 },
+}
+
+pub struct MapfSolution
+{
+    // one path per agent, one node per time step, ending at the goal
+    pub paths: Vec<Vec<i32>>,
+    pub cost: i32,
+    pub ct_expanded: usize,
+}
+
+// where an agent is at time t; they wait at the goal once they arrive
+pub fn position_at(path: &[i32], t: i32) -> i32 {
+    let last = path.len() as i32 - 1;
+    path[t.clamp(0, last) as usize]
+}
+
+// steps from goal to every node, ignoring other agents; -1 if unreachable
+pub fn step_distances<G>(graph: &G, goal: i32) -> Vec<i32>
+where G: NavGraph + ?Sized
+{
+    let mut distances = vec![-1; graph.node_count()];
+    let mut queue = VecDeque::<i32>::new();
+
+    if !graph.is_open(goal) {
+	return distances;
+    }
+
+    distances[goal as usize] = 0;
+    queue.push_back(goal);
+
+    while let Some(i) = queue.pop_front() {
+	for (neighbor_index, _) in graph.neighbors(i) {
+	    if distances[neighbor_index as usize] < 0 {
+		distances[neighbor_index as usize] = distances[i as usize] + 1;
+		queue.push_back(neighbor_index);
+	    }
+	}
+    }
+
+    distances
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct TimedRecord
+{
+    f: i32,
+    time: i32,
+    index: i32,
+}
+
+impl Ord for TimedRecord
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+	// Note, reverse order so we have a min-heap, then prefer later
+	// times, which are closer to the goal
+	other.f.cmp(&self.f)
+	    .then(self.time.cmp(&other.time))
+	    .then(self.index.cmp(&other.index))
+    }
+}
+
+impl PartialOrd for TimedRecord
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+	Some(self.cmp(other))
+    }
+}
+
+// A* in (node, time) space with wait actions, guided by the step
+// distances to the goal. blocked(from, to, time) rules out stepping
+// from -> to leaving at time (from == to is waiting), and the search
+// ends at the first state finished(index, time) accepts. Nothing is
+// blocked differently after settled_time, so from then on each node
+// only needs searching at the earliest time it's reached.
+pub fn space_time_search<G, B, F>(graph: &G, start: i32, start_time: i32,
+				  distances: &[i32], max_time: i32, settled_time: i32,
+				  blocked: B, finished: F) -> Option<Vec<i32>>
+where G: NavGraph + ?Sized,
+      B: Fn(i32, i32, i32) -> bool,
+      F: Fn(i32, i32) -> bool
+{
+    if distances[start as usize] < 0 {
+	return None;
+    }
+
+    let closed_key = |index: i32, time: i32| (index, time.min(settled_time + 1));
+
+    let mut open_nodes = BinaryHeap::<TimedRecord>::new();
+    let mut closed_set = HashSet::<(i32, i32)>::new();
+    let mut prev_state = HashMap::<(i32, i32), (i32, i32)>::new();
+
+    open_nodes.push(TimedRecord {
+	f: start_time + distances[start as usize],
+	time: start_time,
+	index: start,
+    });
+
+    while let Some(n) = open_nodes.pop() {
+	let state = (n.index, n.time);
+	if closed_set.contains(&closed_key(n.index, n.time)) {
+	    continue;
+	}
+	closed_set.insert(closed_key(n.index, n.time));
+
+	if finished(n.index, n.time) {
+	    let mut out_vec = vec![n.index];
+	    let mut s = state;
+	    while let Some(p) = prev_state.get(&s) {
+		out_vec.push(p.0);
+		s = *p;
+	    }
+	    out_vec.reverse();
+	    return Some(out_vec);
+	}
+
+	if n.time >= max_time {
+	    continue;
+	}
+
+	let mut moves: Vec<i32> = graph.neighbors(n.index)
+	    .into_iter()
+	    .map(|(i, _)| i)
+	    .collect();
+	// waiting in place
+	moves.push(n.index);
+
+	let t = n.time + 1;
+	for next in moves {
+	    let d = distances[next as usize];
+	    if d < 0 ||
+		blocked(n.index, next, n.time) ||
+		closed_set.contains(&closed_key(next, t)) {
+		continue;
+	    }
+
+	    if !prev_state.contains_key(&(next, t)) {
+		prev_state.insert((next, t), state);
+	    }
+	    open_nodes.push(TimedRecord {
+		f: t + d,
+		time: t,
+		index: next,
+	    });
+	}
+    }
+
+    None
+}
+
+// single agent A* in (node, time) space
+pub fn space_time_a_star<G>(graph: &G, agent: usize, start: i32, goal: i32,
+			    distances: &[i32], constraints: &[Constraint])
+			    -> Option<Vec<i32>>
+where G: NavGraph + ?Sized
+{
+    let mut vertex_blocks = HashSet::<(i32, i32)>::new();
+    let mut edge_blocks = HashSet::<(i32, i32, i32)>::new();
+    // can't settle at the goal before it's been left alone for good
+    let mut goal_free_time = 0;
+    let mut settled_time = 0;
+
+    for c in constraints {
+	match *c {
+	    Constraint::Vertex { agent: a, index, time } if a == agent => {
+		vertex_blocks.insert((index, time));
+		if index == goal {
+		    goal_free_time = goal_free_time.max(time + 1);
+		}
+		settled_time = settled_time.max(time);
+	    }
+	    Constraint::Edge { agent: a, from, to, time } if a == agent => {
+		edge_blocks.insert((from, to, time));
+		settled_time = settled_time.max(time);
+	    }
+	    _ => {}
+	}
+    }
+
+    if vertex_blocks.contains(&(start, 0)) {
+	return None;
+    }
+
+    space_time_search(graph, start, 0, distances,
+		      goal_free_time + graph.node_count() as i32, settled_time,
+		      |from, to, time| vertex_blocks.contains(&(to, time + 1)) ||
+		      edge_blocks.contains(&(from, to, time)),
+		      |index, time| index == goal && time >= goal_free_time)
+}
+
+pub fn first_conflict(paths: &[Vec<i32>]) -> Option<Conflict> {
+    let horizon = paths.iter().map(|p| p.len()).max().unwrap_or(0) as i32;
+
+    for t in 0 .. horizon {
+	for a in 0 .. paths.len() {
+	    for b in (a + 1) .. paths.len() {
+		let a_here = position_at(&paths[a], t);
+		let b_here = position_at(&paths[b], t);
+
+		if a_here == b_here {
+		    return Some(Conflict::Vertex { a: a, b: b, index: a_here, time: t });
+		}
+
+		let a_next = position_at(&paths[a], t + 1);
+		let b_next = position_at(&paths[b], t + 1);
+
+		if a_here == b_next && b_here == a_next && a_here != a_next {
+		    return Some(Conflict::Edge { a: a, b: b, time: t });
+		}
+	    }
+	}
+    }
+
+    None
+}
+
+fn sum_of_costs(paths: &[Vec<i32>]) -> i32 {
+    paths.iter().map(|p| p.len() as i32 - 1).sum()
+}
+
+struct ConstraintTreeNode
+{
+    constraints: Vec<Constraint>,
+    paths: Vec<Vec<i32>>,
+    cost: i32,
+}
+
+// plan agents (start, goal) jointly; None if any goal is unreachable or
+// the constraint tree grew too large
+pub fn conflict_based_search<G>(graph: &G, agents: &[(i32, i32)]) -> Option<MapfSolution>
+where G: NavGraph + ?Sized
+{
+    let distances: Vec<Vec<i32>> = agents.iter()
+	.map(|(_, goal)| step_distances(graph, *goal))
+	.collect();
+
+    let mut root_paths = Vec::<Vec<i32>>::new();
+    for (agent, (start, goal)) in agents.iter().enumerate() {
+	root_paths.push(space_time_a_star(graph, agent, *start, *goal,
+					  &distances[agent], &[])?);
+    }
+
+    let mut tree = Vec::<ConstraintTreeNode>::new();
+    let mut open_nodes = BinaryHeap::<Reverse<(i32, usize)>>::new();
+
+    tree.push(ConstraintTreeNode {
+	constraints: Vec::new(),
+	cost: sum_of_costs(&root_paths),
+	paths: root_paths,
+    });
+    open_nodes.push(Reverse((tree[0].cost, 0)));
+
+    let mut ct_expanded = 0;
+
+    while let Some(Reverse((_, node_index))) = open_nodes.pop() {
+	ct_expanded += 1;
+	if ct_expanded > MAX_CT_EXPANSIONS {
+	    break;
+	}
+
+	let conflict = match first_conflict(&tree[node_index].paths) {
+	    None => {
+		let node = &tree[node_index];
+		return Some(MapfSolution {
+		    paths: node.paths.clone(),
+		    cost: node.cost,
+		    ct_expanded: ct_expanded,
+		});
+	    }
+	    Some(c) => c,
+	};
+
+	let new_constraints = match conflict {
+	    Conflict::Vertex { a, b, index, time } => {
+		vec![Constraint::Vertex { agent: a, index: index, time: time },
+		     Constraint::Vertex { agent: b, index: index, time: time }]
+	    }
+	    Conflict::Edge { a, b, time } => {
+		let paths = &tree[node_index].paths;
+		vec![Constraint::Edge { agent: a,
+					from: position_at(&paths[a], time),
+					to: position_at(&paths[a], time + 1),
+					time: time },
+		     Constraint::Edge { agent: b,
+					from: position_at(&paths[b], time),
+					to: position_at(&paths[b], time + 1),
+					time: time }]
+	    }
+	};
+
+	for c in new_constraints {
+	    let agent = match c {
+		Constraint::Vertex { agent, .. } => agent,
+		Constraint::Edge { agent, .. } => agent,
+	    };
+
+	    let mut constraints = tree[node_index].constraints.clone();
+	    constraints.push(c);
+
+	    let (start, goal) = agents[agent];
+	    let new_path = match space_time_a_star(graph, agent, start, goal,
+						   &distances[agent], &constraints) {
+		None => { continue; }
+		Some(p) => p,
+	    };
+
+	    let mut paths = tree[node_index].paths.clone();
+	    paths[agent] = new_path;
+
+	    let cost = sum_of_costs(&paths);
+	    tree.push(ConstraintTreeNode {
+		constraints: constraints,
+		paths: paths,
+		cost: cost,
+	    });
+	    open_nodes.push(Reverse((cost, tree.len() - 1)));
+	}
+    }
+
+    None
+}
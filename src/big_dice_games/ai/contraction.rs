@@ -0,0 +1,317 @@
+// big_dice_games/ai/contraction.rs
+//
+// Contraction hierarchies (Geisberger et al. 2008). Nodes are
+// contracted one at a time, least important first, and whenever the
+// only shortest path between two of a node's neighbors ran through it
+// a shortcut edge stands in. A query is then a bidirectional Dijkstra
+// that only ever climbs to more important nodes, which settles a tiny
+// part of the graph. Worth it when the map is static and queried often.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+
+use priority_queue::PriorityQueue;
+
+use crate::big_dice_games::ai::astar::SearchRecord;
+use crate::big_dice_games::ai::graph::NavGraph;
+
+// witness searches give up after settling this many nodes; a missed
+// witness only costs an unneeded shortcut
+const WITNESS_SETTLE_LIMIT: usize = 200;
+
+pub struct ChResult
+{
+    // start .. goal inclusive with the shortcuts unpacked, empty if
+    // the goal can't be reached
+    pub path: Vec<i32>,
+    pub cost: f32,
+    // nodes settled by the forward and backward searches
+    pub settled: Vec<i32>,
+    // where the two searches met, at the top of the path
+    pub meeting: i32,
+}
+
+impl ChResult {
+    pub fn found(&self) -> bool {
+	!self.path.is_empty()
+    }
+}
+
+pub struct ContractionHierarchy
+{
+    // contraction order, -1 for closed nodes
+    pub rank: Vec<i32>,
+    // edges to more important nodes, original and shortcut
+    up: Vec<Vec<(i32, f32)>>,
+    // (lower index, higher index) -> the node a shortcut skips over
+    shortcut_middle: HashMap<(i32, i32), i32>,
+}
+
+fn pair_key(a: i32, b: i32) -> (i32, i32) {
+    (a.min(b), a.max(b))
+}
+
+// remaining graph during contraction: cheapest edge between each pair
+struct Overlay
+{
+    edges: Vec<HashMap<i32, f32>>,
+}
+
+impl Overlay {
+    // false if there was already an edge at least as cheap
+    fn add_edge(&mut self, a: i32, b: i32, cost: f32) -> bool {
+	let e = self.edges[a as usize].entry(b).or_insert(f32::INFINITY);
+	if cost >= *e {
+	    return false;
+	}
+	*e = cost;
+	self.edges[b as usize].insert(a, cost);
+	true
+    }
+
+    // shortest distances from source to each target without passing
+    // through skip, giving up past max_cost
+    fn witness(&self, source: i32, skip: i32, targets: &[i32], max_cost: f32) -> HashMap<i32, f32> {
+	let mut found = HashMap::<i32, f32>::new();
+	let mut distances = HashMap::<i32, f32>::new();
+	let mut open_nodes = BinaryHeap::<SearchRecord>::new();
+	let mut settled = 0;
+
+	distances.insert(source, 0.0);
+	open_nodes.push(SearchRecord { key: 0.0, distance_travelled: 0.0, index: source });
+
+	while let Some(n) = open_nodes.pop() {
+	    if n.distance_travelled > distances[&n.index] {
+		continue;
+	    }
+	    if n.distance_travelled > max_cost || settled >= WITNESS_SETTLE_LIMIT {
+		break;
+	    }
+	    settled += 1;
+
+	    if targets.contains(&n.index) {
+		found.insert(n.index, n.distance_travelled);
+		if found.len() == targets.len() {
+		    break;
+		}
+	    }
+
+	    for (m, cost) in &self.edges[n.index as usize] {
+		if *m == skip {
+		    continue;
+		}
+		let d = n.distance_travelled + cost;
+		if distances.get(m).is_none_or(|old| d < *old) {
+		    distances.insert(*m, d);
+		    open_nodes.push(SearchRecord { key: d, distance_travelled: d, index: *m });
+		}
+	    }
+	}
+
+	found
+    }
+
+    // the shortcuts contracting v would need, as (u, w, cost)
+    fn shortcuts(&self, v: i32) -> Vec<(i32, i32, f32)> {
+	let neighbors: Vec<(i32, f32)> = self.edges[v as usize].iter().map(|(n, c)| (*n, *c)).collect();
+	let mut out = Vec::new();
+
+	for (i, (u, cu)) in neighbors.iter().enumerate() {
+	    let targets: Vec<i32> = neighbors[i + 1 ..].iter().map(|(w, _)| *w).collect();
+	    if targets.is_empty() {
+		continue;
+	    }
+
+	    let max_via = neighbors[i + 1 ..].iter().map(|(_, cw)| cu + cw).fold(0.0, f32::max);
+	    let witnesses = self.witness(*u, v, &targets, max_via);
+
+	    for (w, cw) in &neighbors[i + 1 ..] {
+		let via = cu + cw;
+		if witnesses.get(w).is_none_or(|d| *d > via) {
+		    out.push((*u, *w, via));
+		}
+	    }
+	}
+
+	out
+    }
+
+    fn edge_difference(&self, v: i32) -> i32 {
+	self.shortcuts(v).len() as i32 - self.edges[v as usize].len() as i32
+    }
+}
+
+impl ContractionHierarchy {
+    pub fn new<G>(graph: &G) -> ContractionHierarchy
+    where G: NavGraph + ?Sized
+    {
+	let n = graph.node_count();
+	let mut overlay = Overlay { edges: vec![HashMap::new(); n] };
+
+	for i in 0 .. n as i32 {
+	    if !graph.is_open(i) {
+		continue;
+	    }
+	    for (j, cost) in graph.neighbors(i) {
+		overlay.add_edge(i, j, cost);
+	    }
+	}
+
+	let mut ch = ContractionHierarchy {
+	    rank: vec![-1; n],
+	    up: vec![Vec::new(); n],
+	    shortcut_middle: HashMap::new(),
+	};
+
+	// least important first: the fewest shortcuts for the edges removed
+	let mut queue = PriorityQueue::<i32, Reverse<i32>>::new();
+	for i in 0 .. n as i32 {
+	    if graph.is_open(i) {
+		queue.push(i, Reverse(overlay.edge_difference(i)));
+	    }
+	}
+
+	let mut next_rank = 0;
+	while let Some((v, Reverse(priority))) = queue.pop() {
+	    // priorities go stale as neighbors get contracted; lazily
+	    // recheck, and put v back if it's no longer the least
+	    let current = overlay.edge_difference(v);
+	    if current > priority {
+		if let Some((_, Reverse(next))) = queue.peek() {
+		    if current > *next {
+			queue.push(v, Reverse(current));
+			continue;
+		    }
+		}
+	    }
+
+	    for (u, w, cost) in overlay.shortcuts(v) {
+		if overlay.add_edge(u, w, cost) {
+		    ch.shortcut_middle.insert(pair_key(u, w), v);
+		}
+	    }
+
+	    // every remaining neighbor will be contracted later, so ranks higher
+	    let neighbors: Vec<(i32, f32)> = overlay.edges[v as usize].drain().collect();
+	    for (u, cost) in neighbors {
+		overlay.edges[u as usize].remove(&v);
+		ch.up[v as usize].push((u, cost));
+	    }
+
+	    ch.rank[v as usize] = next_rank;
+	    next_rank += 1;
+	}
+
+	ch
+    }
+
+    pub fn shortcut_count(&self) -> usize {
+	self.shortcut_middle.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+	self.up.iter().map(|e| e.len()).sum()
+    }
+
+    // the original nodes a, possibly shortcut, edge stands for
+    fn unpack(&self, a: i32, b: i32, out: &mut Vec<i32>) {
+	match self.shortcut_middle.get(&pair_key(a, b)) {
+	    None => out.push(b),
+	    Some(m) => {
+		self.unpack(a, *m, out);
+		self.unpack(*m, b, out);
+	    },
+	}
+    }
+
+    pub fn find_path(&self, start: i32, goal: i32) -> ChResult {
+	let mut out = ChResult {
+	    path: Vec::new(),
+	    cost: f32::INFINITY,
+	    settled: Vec::new(),
+	    meeting: -1,
+	};
+
+	if start < 0 || goal < 0 || self.rank[start as usize] < 0 || self.rank[goal as usize] < 0 {
+	    return out;
+	}
+
+	// forward from the start and backward from the goal, both upward
+	let mut distances = [HashMap::<i32, f32>::new(), HashMap::<i32, f32>::new()];
+	let mut prev = [HashMap::<i32, i32>::new(), HashMap::<i32, i32>::new()];
+	let mut open_nodes = [BinaryHeap::<SearchRecord>::new(), BinaryHeap::<SearchRecord>::new()];
+
+	for (side, end) in [(0, start), (1, goal)] {
+	    distances[side].insert(end, 0.0);
+	    prev[side].insert(end, -1);
+	    open_nodes[side].push(SearchRecord { key: 0.0, distance_travelled: 0.0, index: end });
+	}
+
+	let mut side = 0;
+	loop {
+	    // alternate, but a side whose next node can't beat the best
+	    // meeting found so far is done
+	    let live: Vec<usize> = (0 .. 2)
+		.filter(|s| open_nodes[*s].peek().is_some_and(|n| n.distance_travelled < out.cost))
+		.collect();
+	    if live.is_empty() {
+		break;
+	    }
+	    if !live.contains(&side) {
+		side = live[0];
+	    }
+
+	    let n = open_nodes[side].pop().unwrap();
+	    if n.distance_travelled > distances[side][&n.index] {
+		continue;
+	    }
+	    out.settled.push(n.index);
+
+	    if let Some(d) = distances[1 - side].get(&n.index) {
+		if n.distance_travelled + d < out.cost {
+		    out.cost = n.distance_travelled + d;
+		    out.meeting = n.index;
+		}
+	    }
+
+	    for (m, cost) in &self.up[n.index as usize] {
+		let d = n.distance_travelled + cost;
+		if distances[side].get(m).is_none_or(|old| d < *old) {
+		    distances[side].insert(*m, d);
+		    prev[side].insert(*m, n.index);
+		    open_nodes[side].push(SearchRecord { key: d, distance_travelled: d, index: *m });
+		}
+	    }
+
+	    side = 1 - side;
+	}
+
+	if out.meeting < 0 {
+	    return out;
+	}
+
+	// up from the start to the meeting, then down to the goal
+	let mut climb = Vec::new();
+	for s in 0 .. 2 {
+	    let mut chain = Vec::new();
+	    let mut i = out.meeting;
+	    while i != -1 {
+		chain.push(i);
+		i = prev[s][&i];
+	    }
+	    if s == 0 {
+		chain.reverse();
+		climb = chain;
+	    } else {
+		climb.extend(chain.into_iter().skip(1));
+	    }
+	}
+
+	out.path.push(climb[0]);
+	for w in climb.windows(2) {
+	    self.unpack(w[0], w[1], &mut out.path);
+	}
+	out
+    }
+}
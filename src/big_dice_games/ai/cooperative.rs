@@ -0,0 +1,304 @@
+// big_dice_games/ai/cooperative.rs
+//
+// Cooperative A* and Windowed Hierarchical Cooperative A* (Silver,
+// "Cooperative Pathfinding", AIIDE 2005). Agents plan one at a time in
+// (node, time) space; each finished plan is written into a shared
+// reservation table that later agents have to route around. Much
+// cheaper than optimal MAPF, and not complete, but it scales to lots
+// of agents.
+//
+// The windowed version only reserves the next `window` steps, then
+// replans everybody from where they got to, with the true distance to
+// goal (the "hierarchical" part) guiding each search past the window.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::big_dice_games::ai::cbs::position_at;
+use crate::big_dice_games::ai::cbs::space_time_search;
+use crate::big_dice_games::ai::cbs::step_distances;
+use crate::big_dice_games::ai::graph::NavGraph;
+
+// give up on an agent this many steps past its unobstructed step
+// count, spent waiting for or going round other agents
+const MAX_DELAY: i32 = 64;
+
+// who holds which node at which time, and which edges are being crossed
+pub struct ReservationTable
+{
+    vertices: HashMap<(i32, i32), usize>,
+    edges: HashSet<(i32, i32, i32)>,
+    // agents parked at their goal from this time onwards
+    parked: HashMap<i32, i32>,
+    // latest time each node is reserved
+    last_reserved: HashMap<i32, i32>,
+    // latest time anything is reserved
+    last_time: i32,
+}
+
+impl ReservationTable {
+    pub fn new() -> ReservationTable {
+	ReservationTable {
+	    vertices: HashMap::new(),
+	    edges: HashSet::new(),
+	    parked: HashMap::new(),
+	    last_reserved: HashMap::new(),
+	    last_time: 0,
+	}
+    }
+
+    pub fn clear(&mut self) {
+	self.vertices.clear();
+	self.edges.clear();
+	self.parked.clear();
+	self.last_reserved.clear();
+	self.last_time = 0;
+    }
+
+    pub fn release(&mut self, index: i32, time: i32) {
+	self.vertices.remove(&(index, time));
+    }
+
+    pub fn is_vertex_free(&self, index: i32, time: i32) -> bool {
+	if self.vertices.contains_key(&(index, time)) {
+	    return false;
+	}
+
+	match self.parked.get(&index) {
+	    None => true,
+	    Some(t) => time < *t,
+	}
+    }
+
+    // nobody needs index at any time after this one
+    pub fn is_free_after(&self, index: i32, time: i32) -> bool {
+	if self.parked.contains_key(&index) {
+	    return false;
+	}
+
+	match self.last_reserved.get(&index) {
+	    None => true,
+	    Some(t) => *t <= time,
+	}
+    }
+
+    // somebody stays on index for good
+    pub fn is_parked(&self, index: i32) -> bool {
+	self.parked.contains_key(&index)
+    }
+
+    // after this time nothing is reserved that wasn't reserved before it
+    pub fn settled_time(&self) -> i32 {
+	self.last_time
+    }
+
+    // moving from -> to leaving at time, blocked if someone goes to -> from
+    pub fn is_edge_free(&self, from: i32, to: i32, time: i32) -> bool {
+	!self.edges.contains(&(to, from, time))
+    }
+
+    // path[t] is where the agent is at start_time + t; if park is set
+    // the final node stays reserved forever
+    pub fn reserve(&mut self, agent: usize, path: &[i32], start_time: i32, park: bool) {
+	for (t, index) in path.iter().enumerate() {
+	    let time = start_time + t as i32;
+	    self.vertices.insert((*index, time), agent);
+
+	    let last = self.last_reserved.entry(*index).or_insert(time);
+	    *last = (*last).max(time);
+	    self.last_time = self.last_time.max(time);
+
+	    if t + 1 < path.len() {
+		self.edges.insert((*index, path[t + 1], time));
+	    }
+	}
+
+	if park {
+	    if let Some(last) = path.last() {
+		self.parked.insert(*last, start_time + path.len() as i32 - 1);
+	    }
+	}
+    }
+}
+
+// A* in (node, time) with wait actions, avoiding reservations. With a
+// window, the search stops once it is `window` steps out and lets the
+// distance heuristic vouch for the rest; without one it gives up
+// MAX_DELAY steps past the unobstructed step count.
+pub fn reserved_a_star<G>(graph: &G, start: i32, goal: i32, start_time: i32,
+			  distances: &[i32], table: &ReservationTable,
+			  window: Option<i32>) -> Option<Vec<i32>>
+where G: NavGraph + ?Sized
+{
+    if distances[start as usize] < 0 || table.is_parked(goal) {
+	return None;
+    }
+
+    let (max_time, settled_time) = match window {
+	None => (start_time + distances[start as usize] + MAX_DELAY, table.settled_time()),
+	// the window closing is a change too
+	Some(w) => (start_time + w, start_time + w),
+    };
+
+    space_time_search(graph, start, start_time, distances, max_time, settled_time,
+		      |from, to, time| !table.is_vertex_free(to, time + 1) ||
+		      !table.is_edge_free(from, to, time),
+		      // a plan has to be able to stay on the goal once it gets there
+		      |index, time| (index == goal && table.is_free_after(goal, time)) ||
+		      (window.is_some() && time >= max_time))
+}
+
+// plan every agent once, in order, each against everyone before it.
+// Agents that can't find a plan get None.
+pub fn cooperative_a_star<G>(graph: &G, agents: &[(i32, i32)]) -> Vec<Option<Vec<i32>>>
+where G: NavGraph + ?Sized
+{
+    let mut table = ReservationTable::new();
+    let mut out_vec = Vec::<Option<Vec<i32>>>::new();
+
+    for (agent, (start, goal)) in agents.iter().enumerate() {
+	let distances = step_distances(graph, *goal);
+	let path = reserved_a_star(graph, *start, *goal, 0, &distances, &table, None);
+
+	match &path {
+	    Some(p) => table.reserve(agent, p, 0, true),
+	    // hold the start so nobody walks through a stuck agent
+	    None => table.reserve(agent, &[*start], 0, true),
+	}
+	out_vec.push(path);
+    }
+
+    out_vec
+}
+
+// Windowed HCA*. Agents advance together one step at a time, and every
+// replan_interval steps all of them replan a window of steps ahead,
+// in a rotating order so nobody always goes last.
+pub struct WindowedPlanner
+{
+    pub agents: Vec<(i32, i32)>,
+    pub positions: Vec<i32>,
+    // planned steps ahead of each agent, positions[a] first
+    pub plans: Vec<Vec<i32>>,
+    pub time: i32,
+
+    window: i32,
+    replan_interval: i32,
+    distances: Vec<Vec<i32>>,
+    table: ReservationTable,
+}
+
+impl WindowedPlanner {
+    pub fn new<G>(graph: &G, agents: &[(i32, i32)], window: i32, replan_interval: i32)
+		  -> WindowedPlanner
+    where G: NavGraph + ?Sized
+    {
+	let distances = agents.iter()
+	    .map(|(_, goal)| step_distances(graph, *goal))
+	    .collect();
+
+	WindowedPlanner {
+	    agents: agents.to_vec(),
+	    positions: agents.iter().map(|(start, _)| *start).collect(),
+	    plans: vec![Vec::new(); agents.len()],
+	    time: 0,
+	    window: window.max(1),
+	    replan_interval: replan_interval.clamp(1, window.max(1)),
+	    distances: distances,
+	    table: ReservationTable::new(),
+	}
+    }
+
+    pub fn is_finished(&self) -> bool {
+	self.positions.iter()
+	    .zip(self.agents.iter())
+	    .all(|(p, (_, goal))| *p == *goal)
+    }
+
+    fn replan<G>(&mut self, graph: &G)
+    where G: NavGraph + ?Sized
+    {
+	let count = self.agents.len();
+	if count == 0 {
+	    return;
+	}
+
+	let first = (self.time / self.replan_interval) as usize % count;
+	let mut order: Vec<usize> = (0 .. count).map(|k| (first + k) % count).collect();
+
+	// if somebody gets boxed in, give them first pick and start over
+	for _ in 0 .. count {
+	    match self.plan_in_order(graph, &order) {
+		None => { return; }
+		Some(stuck) => {
+		    order.retain(|a| *a != stuck);
+		    order.insert(0, stuck);
+		}
+	    }
+	}
+    }
+
+    // plan each agent in turn; returns the first one that couldn't find
+    // a plan, who is left waiting where it is
+    fn plan_in_order<G>(&mut self, graph: &G, order: &[usize]) -> Option<usize>
+    where G: NavGraph + ?Sized
+    {
+	self.table.clear();
+
+	// until an agent has planned, assume it waits where it is, so
+	// nobody plans to step into it
+	for a in 0 .. self.agents.len() {
+	    self.table.reserve(a, &[self.positions[a]], self.time + 1, false);
+	}
+
+	let mut stuck = None;
+
+	for a in order {
+	    let a = *a;
+	    let here = self.positions[a];
+	    let goal = self.agents[a].1;
+
+	    self.table.release(here, self.time + 1);
+
+	    let mut plan = match reserved_a_star(graph, here, goal, self.time,
+						 &self.distances[a], &self.table,
+						 Some(self.window)) {
+		None => {
+		    if stuck.is_none() {
+			stuck = Some(a);
+		    }
+		    vec![here]
+		}
+		Some(p) => p,
+	    };
+
+	    // short plans end by waiting, which has to be reserved too
+	    let reserved_len = self.window as usize + 1;
+	    while plan.len() < reserved_len {
+		plan.push(*plan.last().unwrap());
+	    }
+
+	    // only the window is binding, the rest is left open
+	    self.table.reserve(a, &plan[.. reserved_len], self.time, false);
+	    self.plans[a] = plan;
+	}
+
+	stuck
+    }
+
+    // move everybody one time step, replanning when due
+    pub fn step<G>(&mut self, graph: &G)
+    where G: NavGraph + ?Sized
+    {
+	if self.time % self.replan_interval == 0 {
+	    self.replan(graph);
+	}
+
+	for a in 0 .. self.agents.len() {
+	    let offset = self.time % self.replan_interval + 1;
+	    self.positions[a] = position_at(&self.plans[a], offset);
+	}
+
+	self.time += 1;
+    }
+}
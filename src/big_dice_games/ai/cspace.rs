@@ -0,0 +1,239 @@
+// big_dice_games/ai/cspace.rs
+//
+// Configuration space obstacles. A robot with a footprint fits at a
+// spot exactly when its reference point is outside every obstacle grown
+// by the footprint (the Minkowski sum of the obstacle with the
+// footprint reflected through the reference point), so once obstacles
+// are inflated the planners can keep treating the robot as a point.
+//
+// Footprints don't rotate here; a robot that turns needs the footprint
+// for each heading, or a circle around all of them.
+
+use std::collections::HashSet;
+
+use crate::big_dice_games::ai::distance::DistanceField;
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::math::vector::Vector;
+
+pub enum Footprint {
+    Circle(f32),
+    // convex, around the reference point
+    Polygon(Vec<Vec2f>),
+}
+
+impl Footprint {
+    // a width by height box centered on the reference point
+    pub fn rectangle(width: f32, height: f32) -> Footprint {
+	let hw = width * 0.5;
+	let hh = height * 0.5;
+	Footprint::Polygon(vec![Vec2f::new(-hw, -hh),
+				Vec2f::new(hw, -hh),
+				Vec2f::new(hw, hh),
+				Vec2f::new(-hw, hh)])
+    }
+
+    // furthest the footprint reaches from the reference point
+    pub fn bounding_radius(&self) -> f32 {
+	match self {
+	    Footprint::Circle(r) => *r,
+	    Footprint::Polygon(corners) => corners.iter()
+		.map(|c| c.mag())
+		.fold(0.0, f32::max),
+	}
+    }
+}
+
+// distance from p to the axis aligned square of half size half
+// centered on c, 0 inside
+fn square_distance(p: Vec2f, c: Vec2f, half: f32) -> f32 {
+    let dx = ((p.x - c.x).abs() - half).max(0.0);
+    let dy = ((p.y - c.y).abs() - half).max(0.0);
+    (dx * dx + dy * dy).sqrt()
+}
+
+// does a convex polygon overlap the square of half size half centered
+// on c? Separating axis test; touching doesn't count.
+fn polygon_overlaps_square(polygon: &[Vec2f], c: Vec2f, half: f32) -> bool {
+    let square = [Vec2f::new(c.x - half, c.y - half),
+		  Vec2f::new(c.x + half, c.y - half),
+		  Vec2f::new(c.x + half, c.y + half),
+		  Vec2f::new(c.x - half, c.y + half)];
+
+    let mut axes = vec![Vec2f::new(1.0, 0.0), Vec2f::new(0.0, 1.0)];
+    for i in 0 .. polygon.len() {
+	let edge = polygon[(i + 1) % polygon.len()] - polygon[i];
+	axes.push(edge.perp());
+    }
+
+    for axis in axes {
+	let project = |points: &[Vec2f]| points.iter()
+	    .map(|p| p.dot(&axis))
+	    .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), d| (lo.min(d), hi.max(d)));
+
+	let (a_lo, a_hi) = project(polygon);
+	let (b_lo, b_hi) = project(&square);
+	if a_hi <= b_lo || b_hi <= a_lo {
+	    return false;
+	}
+    }
+
+    true
+}
+
+// cell offsets (dx, dy) such that a robot centered on a cell overlaps
+// a wall that far away
+pub fn footprint_stamp(cell_size: f32, footprint: &Footprint) -> Vec<(i32, i32)> {
+    let reach = (footprint.bounding_radius() / cell_size).ceil() as i32 + 1;
+    let half = cell_size * 0.5;
+
+    let mut out_vec = Vec::<(i32, i32)>::new();
+
+    for dy in -reach ..= reach {
+	for dx in -reach ..= reach {
+	    let c = Vec2f::new(dx as f32 * cell_size, dy as f32 * cell_size);
+	    let hit = match footprint {
+		Footprint::Circle(r) => square_distance(Vec2f::zero(), c, half) < *r,
+		Footprint::Polygon(corners) => polygon_overlaps_square(corners, c, half),
+	    };
+	    if hit {
+		out_vec.push((dx, dy));
+	    }
+	}
+    }
+
+    out_vec
+}
+
+// Cells where a robot with this footprint would overlap a wall,
+// walls included. Circles read straight off the distance field, taking
+// each wall as the circle round its square, which is a little cautious
+// beside straight walls; polygons stamp their reflection around every
+// wall.
+pub fn inflate_grid(num_x: i32, num_y: i32, cell_size: f32,
+		    walls: &HashSet<i32>, footprint: &Footprint) -> HashSet<i32> {
+    let mut out_set = HashSet::<i32>::new();
+
+    match footprint {
+	Footprint::Circle(r) => {
+	    let field = DistanceField::from_grid(num_x, num_y, cell_size, walls);
+	    let reach = *r + cell_size * 0.5 * std::f32::consts::SQRT_2;
+	    for (i, d) in field.distances.iter().enumerate() {
+		if *d < reach {
+		    out_set.insert(i as i32);
+		}
+	    }
+	}
+	Footprint::Polygon(_) => {
+	    let stamp = footprint_stamp(cell_size, footprint);
+	    for w in walls {
+		let wx = w % num_x;
+		let wy = w / num_x;
+		for (dx, dy) in &stamp {
+		    // the robot sits at wall - offset
+		    let x = wx - dx;
+		    let y = wy - dy;
+		    if x >= 0 && y >= 0 && x < num_x && y < num_y {
+			out_set.insert(y * num_x + x);
+		    }
+		}
+	    }
+	}
+    }
+
+    out_set
+}
+
+// convex hull in order round the outside, by Andrew's monotone
+// chain; collinear points dropped
+pub fn convex_hull(points: &[Vec2f]) -> Vec<Vec2f> {
+    let mut sorted: Vec<Vec2f> = points.to_vec();
+    sorted.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap()
+		   .then(a.y.partial_cmp(&b.y).unwrap()));
+
+    if sorted.len() < 3 {
+	return sorted;
+    }
+
+    let mut hull = Vec::<Vec2f>::new();
+
+    // lower, then upper
+    for pass in 0 .. 2 {
+	let start = hull.len();
+	for p in &sorted {
+	    while hull.len() >= start + 2 {
+		let a = hull[hull.len() - 2];
+		let b = hull[hull.len() - 1];
+		if (b - a).cross(&(*p - a)) > 0.0 {
+		    break;
+		}
+		hull.pop();
+	    }
+	    hull.push(*p);
+	}
+	// the last point starts the other chain
+	hull.pop();
+	if pass == 0 {
+	    sorted.reverse();
+	}
+    }
+
+    hull
+}
+
+// Minkowski sum of two convex polygons
+pub fn minkowski_sum(a: &[Vec2f], b: &[Vec2f]) -> Vec<Vec2f> {
+    let mut sums = Vec::<Vec2f>::new();
+    for p in a {
+	for q in b {
+	    sums.push(*p + *q);
+	}
+    }
+    convex_hull(&sums)
+}
+
+// a polygon around the circle, so the approximation only ever errs
+// on the side of more clearance
+pub fn circle_polygon(center: Vec2f, radius: f32, sides: usize) -> Vec<Vec2f> {
+    let step = std::f32::consts::PI * 2.0 / sides as f32;
+    let outer = radius / (step * 0.5).cos();
+
+    (0 .. sides)
+	.map(|i| {
+	    let a = i as f32 * step;
+	    center + Vec2f::new(a.cos(), a.sin()).scale(outer)
+	})
+	.collect()
+}
+
+// is p inside a convex polygon? on the boundary counts
+pub fn convex_contains(polygon: &[Vec2f], p: Vec2f) -> bool {
+    let mut sign = 0.0;
+
+    for i in 0 .. polygon.len() {
+	let a = polygon[i];
+	let b = polygon[(i + 1) % polygon.len()];
+	let cross = (b - a).cross(&(p - a));
+	if cross == 0.0 {
+	    continue;
+	}
+	if sign == 0.0 {
+	    sign = cross.signum();
+	} else if cross.signum() != sign {
+	    return false;
+	}
+    }
+
+    !polygon.is_empty()
+}
+
+// the configuration space obstacle for a convex obstacle polygon
+pub fn inflate_polygon(obstacle: &[Vec2f], footprint: &Footprint) -> Vec<Vec2f> {
+    let reflected = match footprint {
+	Footprint::Circle(r) => circle_polygon(Vec2f::zero(), *r, 16),
+	Footprint::Polygon(corners) => corners.iter()
+	    .map(|c| c.scale(-1.0))
+	    .collect(),
+    };
+
+    minkowski_sum(obstacle, &reflected)
+}
@@ -0,0 +1,144 @@
+// big_dice_games/ai/delaunay.rs
+//
+// The Delaunay triangulation of a point set as a nav graph: points are
+// linked along triangle edges. It's the dual of the Voronoi diagram, so
+// the links are (bar a few along the border) the same as Voronoi cell
+// adjacency, but with the triangles, edge lengths and circumcenters to
+// hand rather than hidden inside a Voronoi builder. Edges can be cut
+// where they cross obstacles without touching the triangles.
+
+use std::collections::HashSet;
+
+use crate::big_dice_games::ai::graph::NavGraph;
+use crate::big_dice_games::ai::navmesh::point_in_polygon;
+use crate::big_dice_games::math::triangulation::segments_cross;
+use crate::big_dice_games::math::triangulation::Triangulation;
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::math::vector::Vector;
+
+pub struct DelaunayGraph
+{
+    pub triangulation: Triangulation,
+
+    // neighbors by point, whether or not the edge has been cut
+    adjacency: Vec<Vec<i32>>,
+    // cut edges, lower index first
+    removed: HashSet<(i32, i32)>,
+    open: Vec<bool>,
+}
+
+fn edge_key(a: i32, b: i32) -> (i32, i32) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+impl DelaunayGraph {
+    pub fn new(points: &[Vec2f]) -> DelaunayGraph {
+	let triangulation = Triangulation::delaunay(points);
+
+	let mut adjacency = vec![Vec::<i32>::new(); points.len()];
+	for (a, b) in triangulation.undirected_edges() {
+	    adjacency[a].push(b as i32);
+	    adjacency[b].push(a as i32);
+	}
+
+	DelaunayGraph {
+	    triangulation: triangulation,
+	    adjacency: adjacency,
+	    removed: HashSet::new(),
+	    open: vec![true; points.len()],
+	}
+    }
+
+    pub fn triangles(&self) -> &[[usize; 3]] {
+	&self.triangulation.triangles
+    }
+
+    pub fn triangle_corners(&self, t: usize) -> [Vec2f; 3] {
+	let tri = self.triangulation.triangles[t];
+	let p = &self.triangulation.points;
+	[p[tri[0]], p[tri[1]], p[tri[2]]]
+    }
+
+    pub fn circumcenter(&self, t: usize) -> Vec2f {
+	self.triangulation.circumcenter(t)
+    }
+
+    pub fn edge_length(&self, a: i32, b: i32) -> f32 {
+	(self.node_point(a) - self.node_point(b)).mag()
+    }
+
+    // every edge once with its length, cut ones included
+    pub fn edges(&self) -> Vec<(i32, i32, f32)> {
+	let mut out_vec = Vec::<(i32, i32, f32)>::new();
+	for (a, neighbors) in self.adjacency.iter().enumerate() {
+	    for b in neighbors {
+		if *b > a as i32 {
+		    out_vec.push((a as i32, *b, self.edge_length(a as i32, *b)));
+		}
+	    }
+	}
+	out_vec
+    }
+
+    pub fn is_removed(&self, a: i32, b: i32) -> bool {
+	self.removed.contains(&edge_key(a, b))
+    }
+
+    pub fn removed_count(&self) -> usize {
+	self.removed.len()
+    }
+
+    pub fn set_open(&mut self, i: i32, open: bool) {
+	self.open[i as usize] = open;
+    }
+
+    // put back every cut edge
+    pub fn restore_edges(&mut self) {
+	self.removed.clear();
+    }
+
+    // cut the edges blocked() says no to; returns how many went
+    pub fn remove_edges<F>(&mut self, blocked: F) -> usize
+    where F: Fn(Vec2f, Vec2f) -> bool
+    {
+	let before = self.removed.len();
+	for (a, b, _) in self.edges() {
+	    if blocked(self.node_point(a), self.node_point(b)) {
+		self.removed.insert(edge_key(a, b));
+	    }
+	}
+	self.removed.len() - before
+    }
+
+    // cut edges passing through any of the polygons
+    pub fn remove_edges_crossing(&mut self, obstacles: &[Vec<Vec2f>]) -> usize {
+	self.remove_edges(|a, b| {
+	    let mid = Vec2f::new((a.x + b.x) * 0.5, (a.y + b.y) * 0.5);
+	    obstacles.iter().any(|o| {
+		point_in_polygon(o, mid) ||
+		    (0 .. o.len()).any(|i| segments_cross(a, b, o[i], o[(i + 1) % o.len()]))
+	    })
+	})
+    }
+}
+
+impl NavGraph for DelaunayGraph {
+    fn node_count(&self) -> usize {
+	self.triangulation.points.len()
+    }
+
+    fn node_point(&self, i: i32) -> Vec2f {
+	self.triangulation.points[i as usize]
+    }
+
+    fn neighbors(&self, i: i32) -> Vec<(i32, f32)> {
+	self.adjacency[i as usize].iter()
+	    .filter(|n| self.open[**n as usize] && !self.is_removed(i, **n))
+	    .map(|n| (*n, self.edge_length(i, *n)))
+	    .collect()
+    }
+
+    fn is_open(&self, i: i32) -> bool {
+	self.open[i as usize]
+    }
+}
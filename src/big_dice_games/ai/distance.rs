@@ -0,0 +1,185 @@
+// big_dice_games/ai/distance.rs
+//
+// Exact Euclidean distance transform of a grid, after Felzenszwalb and
+// Huttenlocher, "Distance Transforms of Sampled Functions" (2012): a 1D
+// pass down every column then along every row, each finding the lower
+// envelope of parabolas rooted at the occupied cells. Linear in the
+// number of cells.
+//
+// Also a graph adapter that makes edges near walls cost more, so
+// searches prefer to keep some clearance.
+
+use std::collections::HashSet;
+
+use crate::big_dice_games::ai::graph::NavGraph;
+use crate::big_dice_games::math::vector::Vec2f;
+
+// stands in for infinity inside the transform, squared distances of
+// real grids never get near it
+const FAR: f32 = 1.0e20;
+
+pub struct DistanceField
+{
+    // center to nearest occupied center, in pixels
+    pub distances: Vec<f32>,
+}
+
+impl DistanceField {
+    // num_x by num_y cells indexed y * num_x + x
+    pub fn from_grid(num_x: i32, num_y: i32, cell_size: f32,
+		     occupied: &HashSet<i32>) -> DistanceField {
+	let count = (num_x * num_y) as usize;
+
+	if occupied.is_empty() {
+	    return DistanceField {
+		distances: vec![f32::INFINITY; count],
+	    };
+	}
+
+	let mut squared = vec![FAR; count];
+	for i in occupied {
+	    if *i >= 0 && (*i as usize) < count {
+		squared[*i as usize] = 0.0;
+	    }
+	}
+
+	// columns
+	let mut column = vec![0.0; num_y as usize];
+	for x in 0 .. num_x {
+	    for y in 0 .. num_y {
+		column[y as usize] = squared[(y * num_x + x) as usize];
+	    }
+	    let out = transform_1d(&column);
+	    for y in 0 .. num_y {
+		squared[(y * num_x + x) as usize] = out[y as usize];
+	    }
+	}
+
+	// then rows, over the column results
+	for y in 0 .. num_y {
+	    let start = (y * num_x) as usize;
+	    let end = start + num_x as usize;
+	    let out = transform_1d(&squared[start .. end]);
+	    squared[start .. end].copy_from_slice(&out);
+	}
+
+	DistanceField {
+	    distances: squared.iter().map(|d| d.sqrt() * cell_size).collect(),
+	}
+    }
+
+    pub fn get(&self, i: i32) -> f32 {
+	self.distances[i as usize]
+    }
+}
+
+// squared distance to the nearest sample, where f holds 0 at samples
+// (or any other squared offset) and FAR elsewhere
+fn transform_1d(f: &[f32]) -> Vec<f32> {
+    let n = f.len();
+    let mut out = vec![FAR; n];
+    if n == 0 {
+	return out;
+    }
+
+    // roots of the parabolas on the lower envelope, and where each
+    // takes over from the last
+    let mut roots = vec![0usize; n];
+    let mut bounds = vec![0.0f32; n + 1];
+    let mut k = 0;
+
+    bounds[0] = f32::NEG_INFINITY;
+    bounds[1] = f32::INFINITY;
+
+    let intersect = |q: usize, r: usize| -> f32 {
+	let qf = q as f32;
+	let rf = r as f32;
+	((f[q] + qf * qf) - (f[r] + rf * rf)) / (2.0 * qf - 2.0 * rf)
+    };
+
+    for q in 1 .. n {
+	if f[q] >= FAR {
+	    continue;
+	}
+	if f[roots[k]] >= FAR {
+	    // nothing real on the envelope yet
+	    roots[k] = q;
+	    continue;
+	}
+
+	let mut s = intersect(q, roots[k]);
+	while s <= bounds[k] {
+	    k -= 1;
+	    s = intersect(q, roots[k]);
+	}
+
+	k += 1;
+	roots[k] = q;
+	bounds[k] = s;
+	bounds[k + 1] = f32::INFINITY;
+    }
+
+    if f[roots[0]] >= FAR {
+	return out;
+    }
+
+    k = 0;
+    for q in 0 .. n {
+	let qf = q as f32;
+	while bounds[k + 1] < qf {
+	    k += 1;
+	}
+	let d = qf - roots[k] as f32;
+	out[q] = d * d + f[roots[k]];
+    }
+
+    out
+}
+
+// Wraps a graph so edges cost more near walls: an edge whose ends are
+// closer than safe_distance is scaled up by as much as 1 + weight at
+// the wall. Costs only ever go up, so distance heuristics stay
+// admissible.
+pub struct ClearanceCost<'a, G: NavGraph + ?Sized>
+{
+    pub graph: &'a G,
+    pub clearance: &'a [f32],
+    pub safe_distance: f32,
+    pub weight: f32,
+}
+
+impl<'a, G: NavGraph + ?Sized> ClearanceCost<'a, G> {
+    fn penalty(&self, i: i32) -> f32 {
+	let c = self.clearance[i as usize];
+	if c >= self.safe_distance {
+	    return 0.0;
+	}
+	(self.safe_distance - c) / self.safe_distance
+    }
+}
+
+impl<'a, G: NavGraph + ?Sized> NavGraph for ClearanceCost<'a, G> {
+    fn node_count(&self) -> usize {
+	self.graph.node_count()
+    }
+
+    fn node_point(&self, i: i32) -> Vec2f {
+	self.graph.node_point(i)
+    }
+
+    fn neighbors(&self, i: i32) -> Vec<(i32, f32)> {
+	let here = self.penalty(i);
+
+	self.graph.neighbors(i)
+	    .into_iter()
+	    .map(|(n, cost)| {
+		let scale = 1.0 + self.weight * 0.5 * (here + self.penalty(n));
+		(n, cost * scale)
+	    })
+	    .collect()
+    }
+
+    fn is_open(&self, i: i32) -> bool {
+	self.graph.is_open(i)
+    }
+}
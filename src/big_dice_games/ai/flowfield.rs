@@ -0,0 +1,102 @@
+// big_dice_games/ai/flowfield.rs
+//
+// Flow fields, as used for RTS crowds: one Dijkstra pass out from the
+// goal gives every node its distance to go (the integration field),
+// and each node then points at its cheapest neighbor (the flow field).
+// Any number of agents can then steer by lookup.
+
+use std::collections::BinaryHeap;
+
+use crate::big_dice_games::ai::astar::SearchRecord;
+use crate::big_dice_games::ai::graph::NavGraph;
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::math::vector::Vector;
+
+// distance from every node to goal, INFINITY where it can't be reached
+pub fn integration_field<G>(graph: &G, goal: i32) -> Vec<f32>
+where G: NavGraph + ?Sized
+{
+    let mut distances = vec![f32::INFINITY; graph.node_count()];
+    let mut open_nodes = BinaryHeap::<SearchRecord>::new();
+
+    if goal < 0 || !graph.is_open(goal) {
+	return distances;
+    }
+
+    distances[goal as usize] = 0.0;
+    open_nodes.push(SearchRecord {
+	key: 0.0,
+	distance_travelled: 0.0,
+	index: goal,
+    });
+
+    while let Some(n) = open_nodes.pop() {
+	if n.distance_travelled > distances[n.index as usize] {
+	    continue;
+	}
+
+	for (neighbor_index, step_cost) in graph.neighbors(n.index) {
+	    let new_elapsed_dist = n.distance_travelled + step_cost;
+
+	    if distances[neighbor_index as usize] <= new_elapsed_dist {
+		continue;
+	    }
+
+	    distances[neighbor_index as usize] = new_elapsed_dist;
+	    open_nodes.push(SearchRecord {
+		key: new_elapsed_dist,
+		distance_travelled: new_elapsed_dist,
+		index: neighbor_index,
+	    });
+	}
+    }
+
+    distances
+}
+
+// for each node, the neighbor to step to next; -1 at the goal and
+// wherever the goal can't be reached
+pub fn flow_field<G>(graph: &G, integration: &[f32]) -> Vec<i32>
+where G: NavGraph + ?Sized
+{
+    let mut next_index = vec![-1; graph.node_count()];
+
+    for i in 0 .. graph.node_count() {
+	let here = integration[i];
+	if here.is_infinite() || here <= 0.0 {
+	    continue;
+	}
+
+	let mut best = here;
+	for (neighbor_index, step_cost) in graph.neighbors(i as i32) {
+	    let d = integration[neighbor_index as usize];
+
+	    // going via the neighbor has to be consistent with here,
+	    // so break ties towards the one on a shortest path
+	    if d < best && d + step_cost <= here + 0.001 {
+		best = d;
+		next_index[i] = neighbor_index;
+	    }
+	}
+    }
+
+    next_index
+}
+
+// unit vector along the flow at node i, zero if there's nowhere to go
+pub fn flow_direction<G>(graph: &G, flow: &[i32], i: i32) -> Vec2f
+where G: NavGraph + ?Sized
+{
+    let next = flow[i as usize];
+    if next < 0 {
+	return Vec2f::new(0.0, 0.0);
+    }
+
+    let delta = graph.node_point(next) - graph.node_point(i);
+    let len = delta.mag();
+    if len <= 0.0 {
+	return Vec2f::new(0.0, 0.0);
+    }
+
+    delta.scale(1.0 / len)
+}
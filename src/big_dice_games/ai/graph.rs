@@ -0,0 +1,20 @@
+// big_dice_games/ai/graph.rs
+
+use crate::big_dice_games::math::vector::Vec2f;
+
+// A navigable graph, addressed by the same i32 indices the nav screens
+// use for their points. Edges are assumed to be symmetric, so a search
+// running backwards from the goal can use neighbors() as predecessors.
+pub trait NavGraph {
+    fn node_count(&self) -> usize;
+
+    fn node_point(&self, i: i32) -> Vec2f;
+
+    // (neighbor index, cost of the step) for every open neighbor
+    fn neighbors(&self, i: i32) -> Vec<(i32, f32)>;
+
+    // false for walls; a closed node may still list its neighbors
+    fn is_open(&self, _i: i32) -> bool {
+	true
+    }
+}
@@ -0,0 +1,106 @@
+// big_dice_games/ai/heuristic_check.rs
+//
+// Checks a heuristic against the true distances to a goal, from one
+// Dijkstra pass out of it. A heuristic is admissible if it never
+// overestimates (h <= h*), which keeps A* optimal, and consistent if
+// it never drops by more than the cost of an edge (h(a) <= c + h(b)),
+// which means nodes never need reopening. h/h* per node shows how much
+// of the true distance the heuristic sees: 1 is perfect, 0 is Dijkstra.
+
+use crate::big_dice_games::ai::flowfield::integration_field;
+use crate::big_dice_games::ai::graph::NavGraph;
+
+// float error allowed before a value counts as a violation
+const RELATIVE_TOLERANCE: f32 = 0.00001;
+const ABSOLUTE_TOLERANCE: f32 = 0.0001;
+
+pub struct HeuristicReport
+{
+    // h / h* for every node, NaN at the goal and where it can't be reached
+    pub ratios: Vec<f32>,
+    pub mean_ratio: f32,
+
+    // nodes where h > h*, and by how much at worst
+    pub overestimates: Vec<i32>,
+    pub worst_overestimate: f32,
+
+    // edges a -> b where h(a) > c + h(b), and by how much at worst
+    pub inconsistent_edges: Vec<(i32, i32)>,
+    pub worst_inconsistency: f32,
+}
+
+impl HeuristicReport {
+    pub fn admissible(&self) -> bool {
+	self.overestimates.is_empty()
+    }
+
+    pub fn consistent(&self) -> bool {
+	self.inconsistent_edges.is_empty()
+    }
+
+    pub fn max_ratio(&self) -> f32 {
+	self.ratios.iter().cloned().filter(|r| !r.is_nan()).fold(0.0, f32::max)
+    }
+}
+
+fn exceeds(value: f32, limit: f32) -> bool {
+    value > limit * (1.0 + RELATIVE_TOLERANCE) + ABSOLUTE_TOLERANCE
+}
+
+// heuristic(a, goal) estimates the cost of travelling from a to goal
+pub fn check_heuristic<G, H>(graph: &G, goal: i32, heuristic: H) -> HeuristicReport
+where G: NavGraph + ?Sized, H: Fn(i32, i32) -> f32
+{
+    let truth = integration_field(graph, goal);
+    let n = truth.len();
+
+    let mut report = HeuristicReport {
+	ratios: vec![f32::NAN; n],
+	mean_ratio: 0.0,
+	overestimates: Vec::new(),
+	worst_overestimate: 0.0,
+	inconsistent_edges: Vec::new(),
+	worst_inconsistency: 0.0,
+    };
+
+    let h: Vec<f32> = (0 .. n as i32)
+	.map(|i| if truth[i as usize].is_finite() { heuristic(i, goal) } else { 0.0 })
+	.collect();
+
+    let mut ratio_sum = 0.0;
+    let mut ratio_count = 0;
+
+    for i in 0 .. n {
+	if !truth[i].is_finite() {
+	    continue;
+	}
+
+	if exceeds(h[i], truth[i]) {
+	    report.overestimates.push(i as i32);
+	    report.worst_overestimate = report.worst_overestimate.max(h[i] - truth[i]);
+	}
+
+	if truth[i] > 0.0 {
+	    report.ratios[i] = h[i] / truth[i];
+	    ratio_sum += report.ratios[i];
+	    ratio_count += 1;
+	}
+
+	for (j, cost) in graph.neighbors(i as i32) {
+	    if !truth[j as usize].is_finite() {
+		continue;
+	    }
+
+	    if exceeds(h[i], cost + h[j as usize]) {
+		report.inconsistent_edges.push((i as i32, j));
+		report.worst_inconsistency = report.worst_inconsistency.max(h[i] - cost - h[j as usize]);
+	    }
+	}
+    }
+
+    if ratio_count > 0 {
+	report.mean_ratio = ratio_sum / ratio_count as f32;
+    }
+
+    report
+}
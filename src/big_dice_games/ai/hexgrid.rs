@@ -0,0 +1,95 @@
+// big_dice_games/ai/hexgrid.rs
+//
+// A rectangle of hexes as a nav graph. Every step is to one of six
+// equally far neighbors, so costs are in steps and hex distance is an
+// exact heuristic until walls get in the way.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::big_dice_games::ai::graph::NavGraph;
+use crate::big_dice_games::math::hex::Hex;
+use crate::big_dice_games::math::hex::HexLayout;
+use crate::big_dice_games::math::vector::Vec2f;
+
+pub struct HexGrid
+{
+    pub layout: HexLayout,
+    pub hexes: Vec<Hex>,
+    indices: HashMap<Hex, i32>,
+    pub walls: HashSet<i32>,
+}
+
+impl HexGrid {
+    // every hex whose centre is inside 0,0 - width,height
+    pub fn new(layout: HexLayout, width: f32, height: f32) -> HexGrid {
+	let mut hexes = Vec::<Hex>::new();
+
+	// generous bounds in axial terms, trimmed by pixel position
+	let reach = (width.max(height) / layout.spacing()) as i32 * 2 + 2;
+	let corner = layout.pixel_to_hex(Vec2f::zero());
+	for r in corner.r - reach .. corner.r + reach {
+	    for q in corner.q - reach .. corner.q + reach {
+		let h = Hex::new(q, r);
+		let p = layout.hex_to_pixel(h);
+		if p.x >= 0.0 && p.x < width && p.y >= 0.0 && p.y < height {
+		    hexes.push(h);
+		}
+	    }
+	}
+
+	// reading order, so indices run across the screen
+	hexes.sort_by(|a, b| {
+	    let pa = layout.hex_to_pixel(*a);
+	    let pb = layout.hex_to_pixel(*b);
+	    (pa.y, pa.x).partial_cmp(&(pb.y, pb.x)).unwrap()
+	});
+
+	let indices = hexes.iter().enumerate().map(|(i, h)| (*h, i as i32)).collect();
+
+	HexGrid {
+	    layout: layout,
+	    hexes: hexes,
+	    indices: indices,
+	    walls: HashSet::new(),
+	}
+    }
+
+    pub fn index_of(&self, h: Hex) -> i32 {
+	match self.indices.get(&h) {
+	    None => -1,
+	    Some(i) => *i,
+	}
+    }
+
+    // the hex under a pixel, -1 off the grid
+    pub fn index_at(&self, p: Vec2f) -> i32 {
+	self.index_of(self.layout.pixel_to_hex(p))
+    }
+
+    pub fn hex_distance(&self, a: i32, b: i32) -> f32 {
+	self.hexes[a as usize].distance(&self.hexes[b as usize]) as f32
+    }
+}
+
+impl NavGraph for HexGrid {
+    fn node_count(&self) -> usize {
+	self.hexes.len()
+    }
+
+    fn node_point(&self, i: i32) -> Vec2f {
+	self.layout.hex_to_pixel(self.hexes[i as usize])
+    }
+
+    fn neighbors(&self, i: i32) -> Vec<(i32, f32)> {
+	self.hexes[i as usize].neighbors().iter()
+	    .map(|h| self.index_of(*h))
+	    .filter(|n| *n >= 0 && !self.walls.contains(n))
+	    .map(|n| (n, 1.0))
+	    .collect()
+    }
+
+    fn is_open(&self, i: i32) -> bool {
+	!self.walls.contains(&i)
+    }
+}
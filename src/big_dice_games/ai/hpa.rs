@@ -0,0 +1,472 @@
+// big_dice_games/ai/hpa.rs
+//
+// Hierarchical path-finding A* (Botea, Müller & Schaeffer 2004) over a
+// square grid. The grid is cut into clusters, the open stretches along
+// each border between clusters become entrances, and the transitions
+// across them are joined by precomputed paths through each cluster. A
+// search then only has to cross that small abstract graph, and the
+// cached paths turn its answer back into grid spaces.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use crate::big_dice_games::ai::astar::a_star;
+use crate::big_dice_games::ai::astar::SearchResult;
+use crate::big_dice_games::ai::graph::NavGraph;
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::math::vector::Vector;
+
+// entrances at least this wide get a transition at each end, narrower
+// ones a single one in the middle
+const ENTRANCE_SPLIT: i32 = 6;
+
+pub struct IntraEdge
+{
+    pub from: i32,
+    pub to: i32,
+    pub cost: f32,
+    // from .. to inclusive
+    pub path: Vec<i32>,
+}
+
+pub struct HpaResult
+{
+    // start, the transitions crossed, goal
+    pub abstract_path: Vec<i32>,
+    // the refined path through grid spaces
+    pub path: Vec<i32>,
+    pub cost: f32,
+
+    // abstract nodes expanded, and grid spaces expanded connecting
+    // the start and goal to their clusters
+    pub abstract_expanded: usize,
+    pub local_expanded: usize,
+}
+
+impl HpaResult {
+    pub fn found(&self) -> bool {
+	!self.path.is_empty()
+    }
+}
+
+pub struct Hpa
+{
+    pub num_x: i32,
+    pub num_y: i32,
+    pub cluster_size: i32,
+    pub clusters_x: i32,
+    pub clusters_y: i32,
+
+    // (lower cluster, higher cluster) -> the pairs of spaces either
+    // side of the border that are transitions, with the step cost
+    pub transitions: HashMap<(i32, i32), Vec<(i32, i32, f32)>>,
+
+    // paths between every pair of transitions in each cluster
+    pub intra_edges: HashMap<i32, Vec<IntraEdge>>,
+
+    // clusters rebuilt by the most recent update
+    pub rebuilt: Vec<i32>,
+}
+
+// searches that stay inside one cluster
+struct ClusterView<'a, G: NavGraph + ?Sized>
+{
+    graph: &'a G,
+    hpa: &'a Hpa,
+    cluster: i32,
+}
+
+impl<'a, G: NavGraph + ?Sized> NavGraph for ClusterView<'a, G> {
+    fn node_count(&self) -> usize {
+	self.graph.node_count()
+    }
+
+    fn node_point(&self, i: i32) -> Vec2f {
+	self.graph.node_point(i)
+    }
+
+    fn neighbors(&self, i: i32) -> Vec<(i32, f32)> {
+	self.graph.neighbors(i)
+	    .into_iter()
+	    .filter(|(n, _)| self.hpa.cluster_of(*n) == self.cluster)
+	    .collect()
+    }
+
+    fn is_open(&self, i: i32) -> bool {
+	self.graph.is_open(i)
+    }
+}
+
+// the abstract graph, plus the start and goal's edges into it
+struct AbstractView<'a, G: NavGraph + ?Sized>
+{
+    graph: &'a G,
+    hpa: &'a Hpa,
+    extra: &'a HashMap<i32, Vec<(i32, f32)>>,
+}
+
+impl<'a, G: NavGraph + ?Sized> NavGraph for AbstractView<'a, G> {
+    fn node_count(&self) -> usize {
+	self.graph.node_count()
+    }
+
+    fn node_point(&self, i: i32) -> Vec2f {
+	self.graph.node_point(i)
+    }
+
+    fn neighbors(&self, i: i32) -> Vec<(i32, f32)> {
+	let mut out = self.hpa.abstract_neighbors(i);
+	if let Some(edges) = self.extra.get(&i) {
+	    out.extend(edges.iter().cloned());
+	}
+	out
+    }
+}
+
+fn straight_line<G: NavGraph + ?Sized>(graph: &G, a: i32, b: i32) -> f32 {
+    (graph.node_point(a) - graph.node_point(b)).mag()
+}
+
+impl Hpa {
+    pub fn new<G>(graph: &G, num_x: i32, num_y: i32, cluster_size: i32) -> Hpa
+    where G: NavGraph + ?Sized
+    {
+	let mut hpa = Hpa {
+	    num_x: num_x,
+	    num_y: num_y,
+	    cluster_size: cluster_size,
+	    clusters_x: (num_x + cluster_size - 1) / cluster_size,
+	    clusters_y: (num_y + cluster_size - 1) / cluster_size,
+	    transitions: HashMap::new(),
+	    intra_edges: HashMap::new(),
+	    rebuilt: Vec::new(),
+	};
+
+	let all: Vec<i32> = (0 .. hpa.cluster_count()).collect();
+	hpa.rebuild(graph, &all);
+	hpa
+    }
+
+    pub fn cluster_count(&self) -> i32 {
+	self.clusters_x * self.clusters_y
+    }
+
+    pub fn cluster_of(&self, i: i32) -> i32 {
+	let x = i % self.num_x;
+	let y = i / self.num_x;
+	x / self.cluster_size + (y / self.cluster_size) * self.clusters_x
+    }
+
+    // x0, y0, x1, y1 in spaces, ends exclusive
+    pub fn cluster_rect(&self, c: i32) -> (i32, i32, i32, i32) {
+	let x0 = (c % self.clusters_x) * self.cluster_size;
+	let y0 = (c / self.clusters_x) * self.cluster_size;
+	(x0, y0,
+	 (x0 + self.cluster_size).min(self.num_x),
+	 (y0 + self.cluster_size).min(self.num_y))
+    }
+
+    fn neighbor_clusters(&self, c: i32) -> Vec<i32> {
+	let cx = c % self.clusters_x;
+	let cy = c / self.clusters_x;
+	let mut out = Vec::new();
+
+	for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+	    let x = cx + dx;
+	    let y = cy + dy;
+	    if x >= 0 && x < self.clusters_x && y >= 0 && y < self.clusters_y {
+		out.push(x + y * self.clusters_x);
+	    }
+	}
+	out
+    }
+
+    // the abstract nodes in a cluster: its side of every transition
+    pub fn cluster_nodes(&self, c: i32) -> Vec<i32> {
+	let mut out = Vec::new();
+
+	for n in self.neighbor_clusters(c) {
+	    let key = (c.min(n), c.max(n));
+	    if let Some(list) = self.transitions.get(&key) {
+		for (a, b, _) in list {
+		    out.push(if c == key.0 { *a } else { *b });
+		}
+	    }
+	}
+
+	out.sort();
+	out.dedup();
+	out
+    }
+
+    pub fn abstract_nodes(&self) -> Vec<i32> {
+	(0 .. self.cluster_count())
+	    .flat_map(|c| self.cluster_nodes(c))
+	    .collect()
+    }
+
+    pub fn edge_count(&self) -> usize {
+	self.transitions.values().map(|t| t.len()).sum::<usize>() +
+	    self.intra_edges.values().map(|e| e.len()).sum::<usize>()
+    }
+
+    pub fn abstract_neighbors(&self, i: i32) -> Vec<(i32, f32)> {
+	let c = self.cluster_of(i);
+	let mut out = Vec::new();
+
+	for n in self.neighbor_clusters(c) {
+	    let key = (c.min(n), c.max(n));
+	    if let Some(list) = self.transitions.get(&key) {
+		for (a, b, cost) in list {
+		    if *a == i {
+			out.push((*b, *cost));
+		    } else if *b == i {
+			out.push((*a, *cost));
+		    }
+		}
+	    }
+	}
+
+	if let Some(edges) = self.intra_edges.get(&c) {
+	    for e in edges {
+		if e.from == i {
+		    out.push((e.to, e.cost));
+		} else if e.to == i {
+		    out.push((e.from, e.cost));
+		}
+	    }
+	}
+	out
+    }
+
+    // rebuild the transitions along one border, a being left of or above b
+    fn build_border<G>(&mut self, graph: &G, a: i32, b: i32)
+    where G: NavGraph + ?Sized
+    {
+	let (ax0, ay0, ax1, ay1) = self.cluster_rect(a);
+	let horizontal = a / self.clusters_x == b / self.clusters_x;
+
+	// pairs of spaces facing each other across the border
+	let pairs: Vec<(i32, i32)> = if horizontal {
+	    (ay0 .. ay1).map(|y| (ax1 - 1 + y * self.num_x, ax1 + y * self.num_x)).collect()
+	} else {
+	    (ax0 .. ax1).map(|x| (x + (ay1 - 1) * self.num_x, x + ay1 * self.num_x)).collect()
+	};
+
+	// step cost across each pair, if it can be crossed at all
+	let crossing: Vec<Option<f32>> = pairs.iter()
+	    .map(|(p, q)| {
+		if !graph.is_open(*p) || !graph.is_open(*q) {
+		    return None;
+		}
+		graph.neighbors(*p).into_iter().find(|(n, _)| n == q).map(|(_, cost)| cost)
+	    })
+	    .collect();
+
+	let mut list = Vec::new();
+	let mut i = 0;
+	while i < pairs.len() {
+	    if crossing[i].is_none() {
+		i += 1;
+		continue;
+	    }
+
+	    let begin = i;
+	    while i < pairs.len() && crossing[i].is_some() {
+		i += 1;
+	    }
+	    let end = i - 1;
+
+	    let picks = if (end - begin + 1) as i32 >= ENTRANCE_SPLIT {
+		vec![begin, end]
+	    } else {
+		vec![(begin + end) / 2]
+	    };
+
+	    for k in picks {
+		list.push((pairs[k].0, pairs[k].1, crossing[k].unwrap()));
+	    }
+	}
+
+	if list.is_empty() {
+	    self.transitions.remove(&(a, b));
+	} else {
+	    self.transitions.insert((a, b), list);
+	}
+    }
+
+    // path every pair of transitions through the cluster
+    fn build_cluster<G>(&mut self, graph: &G, c: i32)
+    where G: NavGraph + ?Sized
+    {
+	let nodes = self.cluster_nodes(c);
+	let view = ClusterView { graph: graph, hpa: self, cluster: c };
+	let h = |a: i32, b: i32| straight_line(graph, a, b);
+
+	let mut edges = Vec::new();
+	for i in 0 .. nodes.len() {
+	    for j in i + 1 .. nodes.len() {
+		let result = a_star(&view, nodes[i], nodes[j], &h);
+		if result.found() {
+		    edges.push(IntraEdge {
+			from: nodes[i],
+			to: nodes[j],
+			cost: result.cost,
+			path: result.path,
+		    });
+		}
+	    }
+	}
+
+	self.intra_edges.insert(c, edges);
+    }
+
+    // rebuild the borders around the given clusters, then every
+    // cluster whose transitions might have moved
+    fn rebuild<G>(&mut self, graph: &G, dirty: &[i32])
+    where G: NavGraph + ?Sized
+    {
+	let mut borders = HashSet::<(i32, i32)>::new();
+	let mut clusters = HashSet::<i32>::new();
+
+	for c in dirty {
+	    clusters.insert(*c);
+	    for n in self.neighbor_clusters(*c) {
+		borders.insert(((*c).min(n), (*c).max(n)));
+		clusters.insert(n);
+	    }
+	}
+
+	for (a, b) in &borders {
+	    self.build_border(graph, *a, *b);
+	}
+
+	let mut rebuilt: Vec<i32> = clusters.into_iter().collect();
+	rebuilt.sort();
+	for c in &rebuilt {
+	    self.build_cluster(graph, *c);
+	}
+	self.rebuilt = rebuilt;
+    }
+
+    // spaces changed, so repair the clusters they touch
+    pub fn update<G>(&mut self, graph: &G, changed: &[i32])
+    where G: NavGraph + ?Sized
+    {
+	let mut dirty = HashSet::<i32>::new();
+
+	for i in changed {
+	    let x = i % self.num_x;
+	    let y = i / self.num_x;
+	    dirty.insert(self.cluster_of(*i));
+
+	    // a space on a border also changes the cluster across it
+	    for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+		let nx = x + dx;
+		let ny = y + dy;
+		if nx >= 0 && nx < self.num_x && ny >= 0 && ny < self.num_y {
+		    dirty.insert(self.cluster_of(nx + ny * self.num_x));
+		}
+	    }
+	}
+
+	let dirty: Vec<i32> = dirty.into_iter().collect();
+	self.rebuild(graph, &dirty);
+    }
+
+    fn intra_path(&self, a: i32, b: i32) -> Option<Vec<i32>> {
+	let edges = self.intra_edges.get(&self.cluster_of(a))?;
+	for e in edges {
+	    if e.from == a && e.to == b {
+		return Some(e.path.clone());
+	    }
+	    if e.from == b && e.to == a {
+		return Some(e.path.iter().rev().cloned().collect());
+	    }
+	}
+	None
+    }
+
+    pub fn find_path<G>(&self, graph: &G, start: i32, goal: i32) -> HpaResult
+    where G: NavGraph + ?Sized
+    {
+	let mut out = HpaResult {
+	    abstract_path: Vec::new(),
+	    path: Vec::new(),
+	    cost: f32::INFINITY,
+	    abstract_expanded: 0,
+	    local_expanded: 0,
+	};
+
+	if !graph.is_open(start) || !graph.is_open(goal) {
+	    return out;
+	}
+
+	let h = |a: i32, b: i32| straight_line(graph, a, b);
+
+	// connect the start and goal to the transitions of their clusters
+	let mut extra = HashMap::<i32, Vec<(i32, f32)>>::new();
+	let mut extra_paths = HashMap::<(i32, i32), Vec<i32>>::new();
+
+	let mut connect = |from: i32, to: i32, result: &SearchResult| {
+	    extra.entry(from).or_default().push((to, result.cost));
+	    extra.entry(to).or_default().push((from, result.cost));
+	    extra_paths.insert((from, to), result.path.clone());
+	};
+
+	for end in [start, goal] {
+	    let c = self.cluster_of(end);
+	    let view = ClusterView { graph: graph, hpa: self, cluster: c };
+
+	    for n in self.cluster_nodes(c) {
+		if n == end {
+		    continue;
+		}
+		let result = a_star(&view, end, n, &h);
+		out.local_expanded += result.expanded.len();
+		if result.found() {
+		    connect(end, n, &result);
+		}
+	    }
+	}
+
+	// close together, the best path may never leave the cluster
+	if self.cluster_of(start) == self.cluster_of(goal) && start != goal {
+	    let view = ClusterView { graph: graph, hpa: self, cluster: self.cluster_of(start) };
+	    let result = a_star(&view, start, goal, &h);
+	    out.local_expanded += result.expanded.len();
+	    if result.found() {
+		connect(start, goal, &result);
+	    }
+	}
+
+	let view = AbstractView { graph: graph, hpa: self, extra: &extra };
+	let result = a_star(&view, start, goal, &h);
+	out.abstract_expanded = result.expanded.len();
+
+	if !result.found() {
+	    return out;
+	}
+
+	// refine: cached paths within clusters, single steps across borders
+	let mut path = vec![start];
+	for w in result.path.windows(2) {
+	    let (a, b) = (w[0], w[1]);
+	    let step = if let Some(p) = extra_paths.get(&(a, b)) {
+		p.clone()
+	    } else if let Some(p) = extra_paths.get(&(b, a)) {
+		p.iter().rev().cloned().collect()
+	    } else if let Some(p) = self.intra_path(a, b) {
+		p
+	    } else {
+		vec![a, b]
+	    };
+	    path.extend(step.into_iter().skip(1));
+	}
+
+	out.abstract_path = result.path;
+	out.path = path;
+	out.cost = result.cost;
+	out
+    }
+}
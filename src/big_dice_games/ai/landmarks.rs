@@ -0,0 +1,204 @@
+// big_dice_games/ai/landmarks.rs
+//
+// Landmark (ALT) heuristics, after Goldberg & Harrelson 2005. Exact
+// distances from a few landmarks are stored for every node, and the
+// triangle inequality then bounds the distance between any two nodes:
+// d(a, b) >= |d(L, a) - d(L, b)|. With symmetric edges this is the same
+// bound the differential heuristics of Sturtevant et al. use on game
+// maps. Unlike a straight line it knows about the walls.
+
+use crate::big_dice_games::ai::flowfield::integration_field;
+use crate::big_dice_games::ai::graph::NavGraph;
+use crate::big_dice_games::util::rng::Rng;
+
+const AVOID_ATTEMPTS: usize = 4;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum LandmarkSelection {
+    // each landmark as far as possible from the ones before it
+    Farthest,
+    // grow a shortest path tree from a random root and descend into
+    // the subtree the current landmarks estimate worst
+    Avoid,
+}
+
+impl LandmarkSelection {
+    pub fn name(&self) -> &str {
+	match self {
+	    LandmarkSelection::Farthest => "farthest",
+	    LandmarkSelection::Avoid => "avoid",
+	}
+    }
+}
+
+pub struct Landmarks
+{
+    pub selection: LandmarkSelection,
+    pub nodes: Vec<i32>,
+    // distances[k][i]: from landmark k to node i, INFINITY if unreachable
+    pub distances: Vec<Vec<f32>>,
+}
+
+impl Landmarks {
+    // first is any open node; landmarks are only placed in its component
+    pub fn new<G>(graph: &G, count: usize, selection: LandmarkSelection,
+		  first: i32, seed: u64) -> Landmarks
+    where G: NavGraph + ?Sized
+    {
+	let mut landmarks = Landmarks {
+	    selection: selection,
+	    nodes: Vec::new(),
+	    distances: Vec::new(),
+	};
+
+	if first < 0 || !graph.is_open(first) {
+	    return landmarks;
+	}
+
+	let from_first = integration_field(graph, first);
+	let mut rng = Rng::new(seed);
+
+	// a root can land where every branch already has a landmark, so
+	// avoid gets a few tries per landmark
+	let mut attempts = 0;
+	while landmarks.nodes.len() < count && attempts < count * AVOID_ATTEMPTS {
+	    attempts += 1;
+
+	    let next = match selection {
+		LandmarkSelection::Farthest => landmarks.farthest(&from_first),
+		LandmarkSelection::Avoid => {
+		    let reachable: Vec<i32> = (0 .. from_first.len() as i32)
+			.filter(|i| from_first[*i as usize].is_finite())
+			.collect();
+		    let root = reachable[rng.range_i32(0, reachable.len() as i32) as usize];
+		    landmarks.avoid(graph, root)
+		},
+	    };
+
+	    match next {
+		Some(n) if !landmarks.nodes.contains(&n) => {
+		    landmarks.nodes.push(n);
+		    landmarks.distances.push(integration_field(graph, n));
+		},
+		_ if selection == LandmarkSelection::Avoid => continue,
+		_ => break,
+	    }
+	}
+
+	landmarks
+    }
+
+    // lower bound on the cost from a to b
+    pub fn estimate(&self, a: i32, b: i32) -> f32 {
+	(0 .. self.nodes.len())
+	    .map(|k| self.estimate_with(k, a, b))
+	    .fold(0.0, f32::max)
+    }
+
+    pub fn estimate_with(&self, k: usize, a: i32, b: i32) -> f32 {
+	let da = self.distances[k][a as usize];
+	let db = self.distances[k][b as usize];
+
+	// a node cut off from this landmark tells us nothing
+	if da.is_finite() && db.is_finite() {
+	    (da - db).abs()
+	} else {
+	    0.0
+	}
+    }
+
+    // the landmark giving the tightest bound from a to b
+    pub fn best_landmark(&self, a: i32, b: i32) -> Option<usize> {
+	(0 .. self.nodes.len())
+	    .max_by(|x, y| self.estimate_with(*x, a, b)
+		    .partial_cmp(&self.estimate_with(*y, a, b))
+		    .unwrap())
+    }
+
+    // the reachable node whose nearest landmark is farthest away; with
+    // no landmarks yet, the node farthest from the first one
+    fn farthest(&self, from_first: &[f32]) -> Option<i32> {
+	let mut best = None;
+	let mut best_dist = -1.0;
+
+	for i in 0 .. from_first.len() {
+	    if !from_first[i].is_finite() {
+		continue;
+	    }
+
+	    let dist = if self.distances.is_empty() {
+		from_first[i]
+	    } else {
+		self.distances.iter().map(|d| d[i]).fold(f32::INFINITY, f32::min)
+	    };
+
+	    if dist > best_dist {
+		best = Some(i as i32);
+		best_dist = dist;
+	    }
+	}
+
+	best
+    }
+
+    fn avoid<G>(&self, graph: &G, root: i32) -> Option<i32>
+    where G: NavGraph + ?Sized
+    {
+	let dist = integration_field(graph, root);
+	let n = dist.len();
+
+	// shortest path tree: each node hangs off the neighbor it was reached from
+	let mut children = vec![Vec::<usize>::new(); n];
+	let mut order: Vec<usize> = (0 .. n).filter(|i| dist[*i].is_finite()).collect();
+	for i in &order {
+	    if *i as i32 == root {
+		continue;
+	    }
+	    let parent = graph.neighbors(*i as i32)
+		.into_iter()
+		.filter(|(p, _)| dist[*p as usize].is_finite())
+		.min_by(|x, y| (dist[x.0 as usize] + x.1).partial_cmp(&(dist[y.0 as usize] + y.1)).unwrap());
+	    if let Some((p, _)) = parent {
+		children[p as usize].push(*i);
+	    }
+	}
+
+	// weight: how much the current landmarks underestimate the
+	// distance from the root; size sums it over each subtree, and any
+	// subtree that already holds a landmark is left alone
+	let mut size = vec![0.0; n];
+	let mut has_landmark = vec![false; n];
+	order.sort_by(|a, b| dist[*b].partial_cmp(&dist[*a]).unwrap());
+
+	for i in &order {
+	    let mut s = dist[*i] - self.estimate(root, *i as i32);
+	    let mut marked = self.nodes.contains(&(*i as i32));
+	    for c in &children[*i] {
+		s += size[*c];
+		marked = marked || has_landmark[*c];
+	    }
+	    has_landmark[*i] = marked;
+	    size[*i] = if marked { 0.0 } else { s };
+	}
+
+	// walk down the heaviest branch to a leaf
+	let mut node = root as usize;
+	loop {
+	    let next = children[node]
+		.iter()
+		.filter(|c| size[**c] > 0.0)
+		.max_by(|a, b| size[**a].partial_cmp(&size[**b]).unwrap());
+
+	    match next {
+		None => break,
+		Some(c) => node = *c,
+	    }
+	}
+
+	if size[node] > 0.0 {
+	    Some(node as i32)
+	} else {
+	    None
+	}
+    }
+}
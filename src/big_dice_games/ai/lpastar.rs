@@ -0,0 +1,241 @@
+// big_dice_games/ai/lpastar.rs
+//
+// Lifelong Planning A* (Koenig, Likhachev & Furcy 2004). Keeps g and
+// rhs values between searches so that when the graph changes only the
+// affected part of the search tree gets repaired.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use priority_queue::PriorityQueue;
+
+use crate::big_dice_games::ai::graph::NavGraph;
+
+// keys this close to the goal's count as ties and get expanded, so
+// float noise can't leave a stale node on the final path
+const KEY_TOLERANCE: f32 = 0.001;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LpaKey
+{
+    k1: f32,
+    k2: f32,
+}
+
+impl LpaKey {
+    fn less_than(&self, other: &LpaKey) -> bool {
+	self.k1 < other.k1 || (self.k1 == other.k1 && self.k2 < other.k2)
+    }
+
+    fn close_to_or_less_than(&self, other: &LpaKey) -> bool {
+	self.k1 <= other.k1 + KEY_TOLERANCE
+    }
+}
+
+impl Ord for LpaKey
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+	// Note, reverse order so the queue pops the smallest key
+	if self.less_than(other) {
+	    Ordering::Greater
+	} else if other.less_than(self) {
+	    Ordering::Less
+	} else {
+	    Ordering::Equal
+	}
+    }
+}
+
+impl PartialOrd for LpaKey
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+	Some(self.cmp(other))
+    }
+}
+
+impl Eq for LpaKey
+{
+
+}
+
+pub struct LpaStar
+{
+    pub start: i32,
+    pub end: i32,
+
+    g: HashMap<i32, f32>,
+    rhs: HashMap<i32, f32>,
+
+    open_nodes: PriorityQueue<i32, LpaKey>,
+
+    // nodes expanded by the most recent compute_shortest_path
+    pub expanded: Vec<i32>,
+}
+
+impl LpaStar {
+    pub fn new(start: i32, end: i32) -> LpaStar {
+	let mut lpa = LpaStar {
+	    start: start,
+	    end: end,
+	    g: HashMap::new(),
+	    rhs: HashMap::new(),
+	    open_nodes: PriorityQueue::new(),
+	    expanded: Vec::new(),
+	};
+
+	lpa.rhs.insert(start, 0.0);
+	lpa.open_nodes.push(start, LpaKey {
+	    k1: 0.0,
+	    k2: 0.0,
+	});
+
+	lpa
+    }
+
+    pub fn g(&self, i: i32) -> f32 {
+	match self.g.get(&i) {
+	    None => f32::INFINITY,
+	    Some(d) => *d,
+	}
+    }
+
+    pub fn rhs(&self, i: i32) -> f32 {
+	match self.rhs.get(&i) {
+	    None => f32::INFINITY,
+	    Some(d) => *d,
+	}
+    }
+
+    fn calculate_key<H>(&self, i: i32, heuristic: &H) -> LpaKey
+    where H: Fn(i32, i32) -> f32
+    {
+	let m = self.g(i).min(self.rhs(i));
+
+	LpaKey {
+	    k1: m + heuristic(i, self.end),
+	    k2: m,
+	}
+    }
+
+    fn update_vertex<G, H>(&mut self, graph: &G, heuristic: &H, i: i32)
+    where G: NavGraph + ?Sized, H: Fn(i32, i32) -> f32
+    {
+	if i != self.start {
+	    let mut best = f32::INFINITY;
+
+	    if graph.is_open(i) {
+		for (neighbor_index, step_cost) in graph.neighbors(i) {
+		    best = best.min(self.g(neighbor_index) + step_cost);
+		}
+	    }
+	    self.rhs.insert(i, best);
+	}
+
+	self.open_nodes.remove(&i);
+
+	if self.g(i) != self.rhs(i) {
+	    let key = self.calculate_key(i, heuristic);
+	    self.open_nodes.push(i, key);
+	}
+    }
+
+    pub fn compute_shortest_path<G, H>(&mut self, graph: &G, heuristic: &H)
+    where G: NavGraph + ?Sized, H: Fn(i32, i32) -> f32
+    {
+	self.expanded.clear();
+
+	loop {
+	    let top_key = match self.open_nodes.peek() {
+		None => { break; }
+		Some((_, key)) => *key,
+	    };
+
+	    let end_key = self.calculate_key(self.end, heuristic);
+	    if !top_key.close_to_or_less_than(&end_key) &&
+		self.rhs(self.end) == self.g(self.end) {
+		break;
+	    }
+
+	    let (u, _) = self.open_nodes.pop().unwrap();
+	    self.expanded.push(u);
+
+	    if self.g(u) > self.rhs(u) {
+		// locally overconsistent, settle it
+		self.g.insert(u, self.rhs(u));
+	    } else {
+		// underconsistent, something it relied on got worse
+		self.g.insert(u, f32::INFINITY);
+		self.update_vertex(graph, heuristic, u);
+	    }
+
+	    for (neighbor_index, _) in graph.neighbors(u) {
+		self.update_vertex(graph, heuristic, neighbor_index);
+	    }
+	}
+    }
+
+    // call when i has opened or closed; its edges are the ones that changed
+    pub fn node_changed<G, H>(&mut self, graph: &G, heuristic: &H, i: i32)
+    where G: NavGraph + ?Sized, H: Fn(i32, i32) -> f32
+    {
+	self.update_vertex(graph, heuristic, i);
+
+	for (neighbor_index, _) in graph.neighbors(i) {
+	    self.update_vertex(graph, heuristic, neighbor_index);
+	}
+    }
+
+    // call when the heuristic itself changed; the queued keys were
+    // worked out with the old one
+    pub fn update_keys<H>(&mut self, heuristic: &H)
+    where H: Fn(i32, i32) -> f32
+    {
+	let queued: Vec<i32> = self.open_nodes.iter().map(|(i, _)| *i).collect();
+	for i in queued {
+	    let key = self.calculate_key(i, heuristic);
+	    self.open_nodes.change_priority(&i, key);
+	}
+    }
+
+    // follow the cheapest predecessors back from the end
+    pub fn path<G>(&self, graph: &G) -> Vec<i32>
+    where G: NavGraph + ?Sized
+    {
+	let mut out_vec = Vec::<i32>::new();
+
+	if self.g(self.end).is_infinite() {
+	    return out_vec;
+	}
+
+	let mut i = self.end;
+	out_vec.push(i);
+
+	while i != self.start && out_vec.len() <= graph.node_count() {
+	    let mut best_index = -1;
+	    let mut best = f32::INFINITY;
+
+	    for (neighbor_index, step_cost) in graph.neighbors(i) {
+		// only ever step downhill, so we can't go round in circles
+		if self.g(neighbor_index) >= self.g(i) {
+		    continue;
+		}
+
+		let d = self.g(neighbor_index) + step_cost;
+		if d < best {
+		    best = d;
+		    best_index = neighbor_index;
+		}
+	    }
+
+	    if best_index < 0 {
+		return Vec::new();
+	    }
+
+	    i = best_index;
+	    out_vec.push(i);
+	}
+
+	out_vec.reverse();
+	out_vec
+    }
+}
@@ -0,0 +1,430 @@
+// big_dice_games/ai/maze.rs
+//
+// Test maps for the square grid: walls as a set of space indices
+// (x + y * num_x), the same as the nav screens keep. The same seed
+// always gives the same map.
+//
+// The perfect mazes carve passages between cells on the odd
+// coordinates, so every wall is one space thick and there's exactly
+// one route between any two open spaces. Caves and rooms leave plenty
+// of open floor and loops.
+
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use crate::big_dice_games::util::rng::Rng;
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MazeKind {
+    Backtracker,
+    Prim,
+    Kruskal,
+    Wilson,
+    Caves,
+    Rooms,
+}
+
+pub const MAZE_KINDS: [MazeKind; 6] = [MazeKind::Backtracker, MazeKind::Prim, MazeKind::Kruskal,
+				       MazeKind::Wilson, MazeKind::Caves, MazeKind::Rooms];
+
+// chance a space starts as wall before the cave is smoothed
+const CAVE_FILL: f32 = 0.45;
+const CAVE_SMOOTHING: usize = 5;
+
+const ROOM_ATTEMPTS: usize = 60;
+const ROOM_MIN: i32 = 4;
+const ROOM_MAX: i32 = 11;
+
+impl MazeKind {
+    pub fn name(&self) -> &str {
+	match self {
+	    MazeKind::Backtracker => "backtracker",
+	    MazeKind::Prim => "prim",
+	    MazeKind::Kruskal => "kruskal",
+	    MazeKind::Wilson => "wilson",
+	    MazeKind::Caves => "caves",
+	    MazeKind::Rooms => "rooms",
+	}
+    }
+}
+
+pub fn generate_maze(kind: MazeKind, num_x: i32, num_y: i32, seed: u64) -> HashSet<i32> {
+    let mut rng = Rng::new(seed);
+    let mut grid = Grid::new(num_x, num_y, true);
+
+    match kind {
+	MazeKind::Backtracker => backtracker(&mut grid, &mut rng),
+	MazeKind::Prim => prim(&mut grid, &mut rng),
+	MazeKind::Kruskal => kruskal(&mut grid, &mut rng),
+	MazeKind::Wilson => wilson(&mut grid, &mut rng),
+	MazeKind::Caves => caves(&mut grid, &mut rng),
+	MazeKind::Rooms => rooms(&mut grid, &mut rng),
+    }
+
+    grid.walls()
+}
+
+// the map as text, # for walls, for printing from tools
+pub fn maze_to_string(walls: &HashSet<i32>, num_x: i32, num_y: i32) -> String {
+    let mut out = String::new();
+    for y in 0 .. num_y {
+	for x in 0 .. num_x {
+	    out.push(if walls.contains(&(x + y * num_x)) { '#' } else { '.' });
+	}
+	out.push('\n');
+    }
+    out
+}
+
+struct Grid
+{
+    num_x: i32,
+    num_y: i32,
+    wall: Vec<bool>,
+}
+
+impl Grid {
+    fn new(num_x: i32, num_y: i32, filled: bool) -> Grid {
+	Grid {
+	    num_x: num_x,
+	    num_y: num_y,
+	    wall: vec![filled; (num_x * num_y).max(0) as usize],
+	}
+    }
+
+    fn in_bounds(&self, x: i32, y: i32) -> bool {
+	x >= 0 && y >= 0 && x < self.num_x && y < self.num_y
+    }
+
+    fn is_wall(&self, x: i32, y: i32) -> bool {
+	!self.in_bounds(x, y) || self.wall[(x + y * self.num_x) as usize]
+    }
+
+    fn set(&mut self, x: i32, y: i32, wall: bool) {
+	if self.in_bounds(x, y) {
+	    self.wall[(x + y * self.num_x) as usize] = wall;
+	}
+    }
+
+    // maze cells sit on odd coordinates with walls between
+    fn cells_x(&self) -> i32 {
+	(self.num_x - 1) / 2
+    }
+
+    fn cells_y(&self) -> i32 {
+	(self.num_y - 1) / 2
+    }
+
+    fn open_cell(&mut self, c: (i32, i32)) {
+	self.set(c.0 * 2 + 1, c.1 * 2 + 1, false);
+    }
+
+    // knock through between two neighboring cells
+    fn join_cells(&mut self, a: (i32, i32), b: (i32, i32)) {
+	self.open_cell(a);
+	self.open_cell(b);
+	self.set(a.0 + b.0 + 1, a.1 + b.1 + 1, false);
+    }
+
+    fn cell_neighbors(&self, c: (i32, i32)) -> Vec<(i32, i32)> {
+	[(1, 0), (-1, 0), (0, 1), (0, -1)].iter()
+	    .map(|(dx, dy)| (c.0 + dx, c.1 + dy))
+	    .filter(|(x, y)| *x >= 0 && *y >= 0 && *x < self.cells_x() && *y < self.cells_y())
+	    .collect()
+    }
+
+    fn walls(&self) -> HashSet<i32> {
+	(0 .. self.wall.len())
+	    .filter(|i| self.wall[*i])
+	    .map(|i| i as i32)
+	    .collect()
+    }
+}
+
+// depth first, backing up at dead ends: long twisty corridors
+fn backtracker(grid: &mut Grid, rng: &mut Rng) {
+    if grid.cells_x() < 1 || grid.cells_y() < 1 {
+	return;
+    }
+
+    let mut visited = HashSet::<(i32, i32)>::new();
+    let start = (rng.range_i32(0, grid.cells_x()), rng.range_i32(0, grid.cells_y()));
+    let mut stack = vec![start];
+    visited.insert(start);
+    grid.open_cell(start);
+
+    while let Some(c) = stack.last().cloned() {
+	let options: Vec<(i32, i32)> = grid.cell_neighbors(c).into_iter()
+	    .filter(|n| !visited.contains(n))
+	    .collect();
+
+	if options.is_empty() {
+	    stack.pop();
+	    continue;
+	}
+
+	let n = options[rng.range_i32(0, options.len() as i32) as usize];
+	grid.join_cells(c, n);
+	visited.insert(n);
+	stack.push(n);
+    }
+}
+
+// grow from a random frontier cell each time: short branchy dead ends
+fn prim(grid: &mut Grid, rng: &mut Rng) {
+    if grid.cells_x() < 1 || grid.cells_y() < 1 {
+	return;
+    }
+
+    let mut in_maze = HashSet::<(i32, i32)>::new();
+    let start = (rng.range_i32(0, grid.cells_x()), rng.range_i32(0, grid.cells_y()));
+    in_maze.insert(start);
+    grid.open_cell(start);
+
+    // (outside cell, the maze cell it would join to)
+    let mut frontier: Vec<((i32, i32), (i32, i32))> = grid.cell_neighbors(start).into_iter()
+	.map(|n| (n, start))
+	.collect();
+
+    while !frontier.is_empty() {
+	let pick = rng.range_i32(0, frontier.len() as i32) as usize;
+	let (c, from) = frontier.swap_remove(pick);
+	if !in_maze.insert(c) {
+	    continue;
+	}
+
+	grid.join_cells(from, c);
+	for n in grid.cell_neighbors(c) {
+	    if !in_maze.contains(&n) {
+		frontier.push((n, c));
+	    }
+	}
+    }
+}
+
+fn find_root(parents: &mut Vec<usize>, i: usize) -> usize {
+    let mut root = i;
+    while parents[root] != root {
+	root = parents[root];
+    }
+
+    // point everything on the way straight at the root
+    let mut j = i;
+    while parents[j] != root {
+	let next = parents[j];
+	parents[j] = root;
+	j = next;
+    }
+    root
+}
+
+// join cells along shuffled walls unless they're already connected
+fn kruskal(grid: &mut Grid, rng: &mut Rng) {
+    let cx = grid.cells_x();
+    let cy = grid.cells_y();
+    if cx < 1 || cy < 1 {
+	return;
+    }
+
+    let mut edges = Vec::<((i32, i32), (i32, i32))>::new();
+    for y in 0 .. cy {
+	for x in 0 .. cx {
+	    grid.open_cell((x, y));
+	    if x + 1 < cx {
+		edges.push(((x, y), (x + 1, y)));
+	    }
+	    if y + 1 < cy {
+		edges.push(((x, y), (x, y + 1)));
+	    }
+	}
+    }
+    rng.shuffle(&mut edges);
+
+    let mut parents: Vec<usize> = (0 .. (cx * cy) as usize).collect();
+    for (a, b) in edges {
+	let ra = find_root(&mut parents, (a.0 + a.1 * cx) as usize);
+	let rb = find_root(&mut parents, (b.0 + b.1 * cx) as usize);
+	if ra != rb {
+	    parents[ra] = rb;
+	    grid.join_cells(a, b);
+	}
+    }
+}
+
+// loop erased random walks from unvisited cells until they hit the
+// maze; slow to start, but an unbiased pick from every possible maze
+fn wilson(grid: &mut Grid, rng: &mut Rng) {
+    let cx = grid.cells_x();
+    let cy = grid.cells_y();
+    if cx < 1 || cy < 1 {
+	return;
+    }
+
+    let mut in_maze = vec![false; (cx * cy) as usize];
+    let first = rng.range_i32(0, cx * cy);
+    in_maze[first as usize] = true;
+    grid.open_cell((first % cx, first / cx));
+
+    let mut remaining: Vec<i32> = (0 .. cx * cy).filter(|c| *c != first).collect();
+    rng.shuffle(&mut remaining);
+
+    // the way each cell on the walk was last left
+    let mut exit_to = vec![-1; (cx * cy) as usize];
+
+    for start in remaining {
+	if in_maze[start as usize] {
+	    continue;
+	}
+
+	let mut c = start;
+	while !in_maze[c as usize] {
+	    let options = grid.cell_neighbors((c % cx, c / cx));
+	    let n = options[rng.range_i32(0, options.len() as i32) as usize];
+	    exit_to[c as usize] = n.0 + n.1 * cx;
+	    c = exit_to[c as usize];
+	}
+
+	// following the last exits skips any loops the walk made
+	let mut c = start;
+	while !in_maze[c as usize] {
+	    let n = exit_to[c as usize];
+	    grid.join_cells((c % cx, c / cx), (n % cx, n / cx));
+	    in_maze[c as usize] = true;
+	    c = n;
+	}
+    }
+}
+
+// random fill, smoothed: a space becomes wall with five or more walls
+// among it and its eight neighbors. Pockets cut off from the biggest
+// cave are filled in so everything open is reachable.
+fn caves(grid: &mut Grid, rng: &mut Rng) {
+    for y in 0 .. grid.num_y {
+	for x in 0 .. grid.num_x {
+	    let border = x == 0 || y == 0 || x == grid.num_x - 1 || y == grid.num_y - 1;
+	    grid.set(x, y, border || rng.next_f32() < CAVE_FILL);
+	}
+    }
+
+    for _ in 0 .. CAVE_SMOOTHING {
+	let mut next = Grid::new(grid.num_x, grid.num_y, true);
+	for y in 0 .. grid.num_y {
+	    for x in 0 .. grid.num_x {
+		let mut count = 0;
+		for dy in -1 .. 2 {
+		    for dx in -1 .. 2 {
+			if grid.is_wall(x + dx, y + dy) {
+			    count += 1;
+			}
+		    }
+		}
+		next.set(x, y, count >= 5);
+	    }
+	}
+	grid.wall = next.wall;
+    }
+
+    keep_largest_region(grid);
+}
+
+fn keep_largest_region(grid: &mut Grid) {
+    let count = grid.wall.len();
+    let mut region = vec![-1; count];
+    let mut sizes = Vec::<usize>::new();
+
+    for s in 0 .. count {
+	if grid.wall[s] || region[s] >= 0 {
+	    continue;
+	}
+
+	let id = sizes.len() as i32;
+	let mut size = 0;
+	let mut queue = VecDeque::<i32>::new();
+	queue.push_back(s as i32);
+	region[s] = id;
+
+	while let Some(i) = queue.pop_front() {
+	    size += 1;
+	    let (x, y) = (i % grid.num_x, i / grid.num_x);
+	    for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
+		let (nx, ny) = (x + dx, y + dy);
+		if grid.is_wall(nx, ny) {
+		    continue;
+		}
+		let n = (nx + ny * grid.num_x) as usize;
+		if region[n] < 0 {
+		    region[n] = id;
+		    queue.push_back(n as i32);
+		}
+	    }
+	}
+	sizes.push(size);
+    }
+
+    let biggest = match (0 .. sizes.len()).max_by_key(|i| sizes[*i]) {
+	None => { return; }
+	Some(b) => b as i32,
+    };
+
+    for s in 0 .. count {
+	if region[s] != biggest {
+	    grid.wall[s] = true;
+	}
+    }
+}
+
+// rectangular rooms that don't touch, each joined to the one before by
+// an L shaped corridor
+fn rooms(grid: &mut Grid, rng: &mut Rng) {
+    // (x, y, w, h)
+    let mut placed = Vec::<(i32, i32, i32, i32)>::new();
+
+    for _ in 0 .. ROOM_ATTEMPTS {
+	let w = rng.range_i32(ROOM_MIN, ROOM_MAX + 1);
+	let h = rng.range_i32(ROOM_MIN, ROOM_MAX + 1);
+	if w + 2 > grid.num_x || h + 2 > grid.num_y {
+	    continue;
+	}
+	let x = rng.range_i32(1, grid.num_x - w);
+	let y = rng.range_i32(1, grid.num_y - h);
+
+	// keep a wall's width between rooms
+	let overlaps = placed.iter().any(|&(px, py, pw, ph)| {
+	    x <= px + pw && px <= x + w && y <= py + ph && py <= y + h
+	});
+	if overlaps {
+	    continue;
+	}
+
+	for ry in y .. y + h {
+	    for rx in x .. x + w {
+		grid.set(rx, ry, false);
+	    }
+	}
+
+	if let Some((px, py, pw, ph)) = placed.last().cloned() {
+	    let (ax, ay) = (px + pw / 2, py + ph / 2);
+	    let (bx, by) = (x + w / 2, y + h / 2);
+
+	    // which leg goes first
+	    let corner = if rng.next_f32() < 0.5 { (bx, ay) } else { (ax, by) };
+	    carve_line(grid, (ax, ay), corner);
+	    carve_line(grid, corner, (bx, by));
+	}
+
+	placed.push((x, y, w, h));
+    }
+}
+
+fn carve_line(grid: &mut Grid, a: (i32, i32), b: (i32, i32)) {
+    let (mut x, mut y) = a;
+    grid.set(x, y, false);
+    while (x, y) != b {
+	if x != b.0 {
+	    x += (b.0 - x).signum();
+	} else {
+	    y += (b.1 - y).signum();
+	}
+	grid.set(x, y, false);
+    }
+}
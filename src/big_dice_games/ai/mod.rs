@@ -1 +1,25 @@
 // BigDiceGames/AI/mod.rs
+
+pub mod graph;
+pub mod astar;
+pub mod bidirectional;
+pub mod anytime;
+pub mod lpastar;
+pub mod flowfield;
+pub mod cbs;
+pub mod cooperative;
+pub mod orca;
+pub mod steering;
+pub mod cspace;
+pub mod distance;
+pub mod navmesh;
+pub mod visibility;
+pub mod prm;
+pub mod delaunay;
+pub mod hexgrid;
+pub mod maze;
+pub mod hpa;
+pub mod landmarks;
+pub mod contraction;
+pub mod heuristic_check;
+pub mod neighborhood;
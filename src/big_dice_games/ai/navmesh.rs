@@ -0,0 +1,392 @@
+// big_dice_games/ai/navmesh.rs
+//
+// A navigation mesh: free space round polygonal obstacles cut into
+// triangles by a constrained Delaunay triangulation. A* runs over the
+// edges between triangles, midpoint to midpoint, which gives a
+// corridor, and the simple stupid funnel algorithm (Mononen, 2010)
+// pulls the shortest path through it. Searching edges rather than
+// triangle centroids keeps long thin triangles from skewing the choice
+// of corridor.
+
+use crate::big_dice_games::ai::astar::a_star;
+use crate::big_dice_games::ai::graph::NavGraph;
+use crate::big_dice_games::math::triangulation::orient;
+use crate::big_dice_games::math::triangulation::Triangulation;
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::math::vector::Vector;
+
+pub struct NavMesh
+{
+    pub triangulation: Triangulation,
+    // obstacle edges that couldn't be forced in, usually from
+    // overlapping obstacles
+    pub failed_constraints: usize,
+
+    // edges between two walkable triangles, as vertex indices wound
+    // the way the first of edge_triangles has them
+    pub portal_edges: Vec<(usize, usize)>,
+    pub edge_triangles: Vec<[usize; 2]>,
+    // portal edge indices round each triangle
+    pub triangle_portals: Vec<Vec<usize>>,
+}
+
+pub struct NavMeshPath
+{
+    // triangle indices, start's to goal's; empty if unreachable
+    pub corridor: Vec<i32>,
+    // portal edges, in the order the search took them
+    pub expanded: Vec<usize>,
+    // the edges crossed along the corridor, (right, left)
+    pub portals: Vec<(Vec2f, Vec2f)>,
+    // the pulled string, start to goal
+    pub path: Vec<Vec2f>,
+}
+
+impl NavMeshPath {
+    pub fn found(&self) -> bool {
+	!self.path.is_empty()
+    }
+
+    pub fn length(&self) -> f32 {
+	self.path.windows(2)
+	    .map(|w| (w[1] - w[0]).mag())
+	    .sum()
+    }
+}
+
+// even-odd test, works for any simple polygon
+pub fn point_in_polygon(polygon: &[Vec2f], p: Vec2f) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len().wrapping_sub(1);
+
+    for i in 0 .. polygon.len() {
+	let a = polygon[i];
+	let b = polygon[j];
+	if (a.y > p.y) != (b.y > p.y) &&
+	    p.x < (b.x - a.x) * (p.y - a.y) / (b.y - a.y) + a.x {
+	    inside = !inside;
+	}
+	j = i;
+    }
+
+    inside
+}
+
+impl NavMesh {
+    // Triangulate the box from min to max less the obstacles, simple
+    // polygons inside the box that don't touch each other
+    pub fn build(min: Vec2f, max: Vec2f, obstacles: &[Vec<Vec2f>]) -> NavMesh {
+	let mut points = vec![min,
+			      Vec2f::new(max.x, min.y),
+			      max,
+			      Vec2f::new(min.x, max.y)];
+
+	// obstacle k's vertices start at offsets[k]
+	let mut offsets = Vec::<usize>::new();
+	for o in obstacles {
+	    offsets.push(points.len());
+	    points.extend(o.iter().cloned());
+	}
+
+	let mut triangulation = Triangulation::delaunay(&points);
+
+	let mut failed_constraints = 0;
+	for (k, o) in obstacles.iter().enumerate() {
+	    for i in 0 .. o.len() {
+		let a = offsets[k] + i;
+		let b = offsets[k] + (i + 1) % o.len();
+		if !triangulation.insert_constraint(a, b) {
+		    failed_constraints += 1;
+		}
+	    }
+	}
+
+	triangulation.legalize();
+
+	// what's left inside an obstacle isn't walkable
+	triangulation.retain_triangles(|tri, t| {
+	    let c = tri.centroid(t);
+	    !obstacles.iter().any(|o| point_in_polygon(o, c))
+	});
+
+	let mut portal_edges = Vec::<(usize, usize)>::new();
+	let mut edge_triangles = Vec::<[usize; 2]>::new();
+	let mut triangle_portals = vec![Vec::<usize>::new(); triangulation.triangles.len()];
+
+	for t in 0 .. triangulation.triangles.len() {
+	    for k in 0 .. 3 {
+		let n = triangulation.neighbor(t, k);
+		if n > t as i32 {
+		    let tri = triangulation.triangles[t];
+		    triangle_portals[t].push(portal_edges.len());
+		    triangle_portals[n as usize].push(portal_edges.len());
+		    portal_edges.push((tri[k], tri[(k + 1) % 3]));
+		    edge_triangles.push([t, n as usize]);
+		}
+	    }
+	}
+
+	NavMesh {
+	    triangulation: triangulation,
+	    failed_constraints: failed_constraints,
+	    portal_edges: portal_edges,
+	    edge_triangles: edge_triangles,
+	    triangle_portals: triangle_portals,
+	}
+    }
+
+    pub fn portal_segment(&self, e: usize) -> (Vec2f, Vec2f) {
+	let (a, b) = self.portal_edges[e];
+	(self.triangulation.points[a], self.triangulation.points[b])
+    }
+
+    pub fn triangle_count(&self) -> usize {
+	self.triangulation.triangles.len()
+    }
+
+    pub fn corners(&self, t: usize) -> [Vec2f; 3] {
+	let tri = self.triangulation.triangles[t];
+	let p = &self.triangulation.points;
+	[p[tri[0]], p[tri[1]], p[tri[2]]]
+    }
+
+    // the triangle p is in, -1 if it's in an obstacle or off the mesh
+    pub fn locate(&self, p: Vec2f) -> i32 {
+	for t in 0 .. self.triangle_count() {
+	    let [a, b, c] = self.corners(t);
+	    if orient(a, b, p) >= 0.0 && orient(b, c, p) >= 0.0 && orient(c, a, p) >= 0.0 {
+		return t as i32;
+	    }
+	}
+	-1
+    }
+
+    // the edges crossed walking the corridor, with start and goal as
+    // zero width portals at either end. Each is (right, left) as seen
+    // travelling along, which is what the funnel wants.
+    pub fn portals(&self, corridor: &[i32], start: Vec2f, goal: Vec2f) -> Vec<(Vec2f, Vec2f)> {
+	let mut out_vec = vec![(start, start)];
+
+	for w in corridor.windows(2) {
+	    let from = self.triangulation.triangles[w[0] as usize];
+	    let to = self.triangulation.triangles[w[1] as usize];
+
+	    // the shared edge in from's winding has the right side first
+	    for k in 0 .. 3 {
+		let a = from[k];
+		let b = from[(k + 1) % 3];
+		if to.contains(&a) && to.contains(&b) {
+		    out_vec.push((self.triangulation.points[a], self.triangulation.points[b]));
+		    break;
+		}
+	    }
+	}
+
+	out_vec.push((goal, goal));
+	out_vec
+    }
+
+    pub fn find_path(&self, start: Vec2f, goal: Vec2f) -> NavMeshPath {
+	let start_tri = self.locate(start);
+	let goal_tri = self.locate(goal);
+
+	let mut out = NavMeshPath {
+	    corridor: Vec::new(),
+	    expanded: Vec::new(),
+	    portals: Vec::new(),
+	    path: Vec::new(),
+	};
+
+	if start_tri < 0 || goal_tri < 0 {
+	    return out;
+	}
+
+	let graph = PortalGraph {
+	    mesh: self,
+	    start: start,
+	    goal: goal,
+	    start_tri: start_tri as usize,
+	    goal_tri: goal_tri as usize,
+	};
+	let start_node = self.portal_edges.len() as i32;
+	let goal_node = start_node + 1;
+
+	let result = a_star(&graph, start_node, goal_node,
+			    |a, b| (graph.node_point(a) - graph.node_point(b)).mag());
+
+	out.expanded = result.expanded.iter()
+	    .filter(|e| **e < start_node)
+	    .map(|e| *e as usize)
+	    .collect();
+
+	if !result.found() {
+	    return out;
+	}
+
+	// walk the edges, stepping into whichever side we aren't on
+	let mut corridor = vec![start_tri];
+	for e in &result.path[1 .. result.path.len() - 1] {
+	    let [t1, t2] = self.edge_triangles[*e as usize];
+	    let here = corridor[corridor.len() - 1] as usize;
+	    corridor.push(if here == t1 { t2 as i32 } else { t1 as i32 });
+	}
+
+	out.portals = self.portals(&corridor, start, goal);
+	out.path = funnel(&out.portals);
+	out.corridor = corridor;
+	out
+    }
+}
+
+// What find_path searches: the portal edges by index, then start and
+// goal on the end, linked when they share a triangle
+struct PortalGraph<'a>
+{
+    mesh: &'a NavMesh,
+    start: Vec2f,
+    goal: Vec2f,
+    start_tri: usize,
+    goal_tri: usize,
+}
+
+impl<'a> NavGraph for PortalGraph<'a> {
+    fn node_count(&self) -> usize {
+	self.mesh.portal_edges.len() + 2
+    }
+
+    fn node_point(&self, i: i32) -> Vec2f {
+	let edge_count = self.mesh.portal_edges.len() as i32;
+	if i == edge_count {
+	    return self.start;
+	}
+	if i == edge_count + 1 {
+	    return self.goal;
+	}
+
+	let (a, b) = self.mesh.portal_segment(i as usize);
+	Vec2f::new((a.x + b.x) * 0.5, (a.y + b.y) * 0.5)
+    }
+
+    fn neighbors(&self, i: i32) -> Vec<(i32, f32)> {
+	let edge_count = self.mesh.portal_edges.len() as i32;
+
+	let triangles = if i == edge_count {
+	    vec![self.start_tri]
+	} else if i == edge_count + 1 {
+	    vec![self.goal_tri]
+	} else {
+	    self.mesh.edge_triangles[i as usize].to_vec()
+	};
+
+	let mut out_vec = Vec::<i32>::new();
+	for t in triangles {
+	    for e in &self.mesh.triangle_portals[t] {
+		if *e as i32 != i {
+		    out_vec.push(*e as i32);
+		}
+	    }
+	    if t == self.start_tri && i != edge_count {
+		out_vec.push(edge_count);
+	    }
+	    if t == self.goal_tri && i != edge_count + 1 {
+		out_vec.push(edge_count + 1);
+	    }
+	}
+
+	let here = self.node_point(i);
+	out_vec.into_iter()
+	    .map(|n| (n, (self.node_point(n) - here).mag()))
+	    .collect()
+    }
+}
+
+// triangles, linked across shared edges, centroid to centroid
+impl NavGraph for NavMesh {
+    fn node_count(&self) -> usize {
+	self.triangle_count()
+    }
+
+    fn node_point(&self, i: i32) -> Vec2f {
+	self.triangulation.centroid(i as usize)
+    }
+
+    fn neighbors(&self, i: i32) -> Vec<(i32, f32)> {
+	let here = self.node_point(i);
+
+	(0 .. 3)
+	    .map(|k| self.triangulation.neighbor(i as usize, k))
+	    .filter(|n| *n >= 0)
+	    .map(|n| (n, (self.node_point(n) - here).mag()))
+	    .collect()
+    }
+}
+
+// Simple stupid funnel: keep a wedge from the apex through the current
+// right and left points, narrowing it portal by portal. When one side
+// would cross over the other, that other side's point is a corner of
+// the path; it becomes the apex and we carry on from just after it.
+pub fn funnel(portals: &[(Vec2f, Vec2f)]) -> Vec<Vec2f> {
+    let mut out_vec = Vec::<Vec2f>::new();
+    if portals.is_empty() {
+	return out_vec;
+    }
+
+    let same = |a: Vec2f, b: Vec2f| (a - b).mag_sq() < 1.0e-6;
+
+    let mut apex = portals[0].0;
+    let mut right = portals[0].0;
+    let mut left = portals[0].1;
+    let mut right_index = 0;
+    let mut left_index = 0;
+
+    out_vec.push(apex);
+
+    let mut i = 1;
+    while i < portals.len() {
+	let (new_right, new_left) = portals[i];
+
+	// try to swing the right side in
+	if orient(apex, right, new_right) >= 0.0 {
+	    if same(apex, right) || orient(apex, left, new_right) < 0.0 {
+		right = new_right;
+		right_index = i;
+	    } else {
+		// crossed the left side: that's a corner
+		apex = left;
+		if !same(out_vec[out_vec.len() - 1], apex) {
+		    out_vec.push(apex);
+		}
+		right = apex;
+		right_index = left_index;
+		i = left_index + 1;
+		continue;
+	    }
+	}
+
+	// and the left
+	if orient(apex, left, new_left) <= 0.0 {
+	    if same(apex, left) || orient(apex, right, new_left) > 0.0 {
+		left = new_left;
+		left_index = i;
+	    } else {
+		apex = right;
+		if !same(out_vec[out_vec.len() - 1], apex) {
+		    out_vec.push(apex);
+		}
+		left = apex;
+		left_index = right_index;
+		i = right_index + 1;
+		continue;
+	    }
+	}
+
+	i += 1;
+    }
+
+    let goal = portals[portals.len() - 1].0;
+    if !same(out_vec[out_vec.len() - 1], goal) {
+	out_vec.push(goal);
+    }
+
+    out_vec
+}
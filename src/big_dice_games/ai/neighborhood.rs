@@ -0,0 +1,196 @@
+// big_dice_games/ai/neighborhood.rs
+//
+// Move sets for square grids. Beyond the usual 4 and 8 neighbors, the
+// 16 and 32 neighborhoods add knight moves and longer jumps (Rivera,
+// Hernández & Baier 2017), which cut the zig-zag out of grid paths. A
+// long move sweeps across the spaces between its ends, and those have
+// to be open; where it only grazes the corner between two spaces the
+// corner cutting rule decides.
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum Connectivity {
+    Four,
+    Eight,
+    Sixteen,
+    ThirtyTwo,
+}
+
+pub const CONNECTIVITIES: [Connectivity; 4] = [Connectivity::Four, Connectivity::Eight,
+					       Connectivity::Sixteen, Connectivity::ThirtyTwo];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum CornerCutting {
+    Allow,
+    // no squeezing past a wall at all
+    ForbidEither,
+    // only forbidden through a gap between two walls
+    ForbidBoth,
+}
+
+pub const CORNER_CUTTINGS: [CornerCutting; 3] = [CornerCutting::Allow, CornerCutting::ForbidEither,
+						 CornerCutting::ForbidBoth];
+
+impl Connectivity {
+    pub fn count(&self) -> usize {
+	match self {
+	    Connectivity::Four => 4,
+	    Connectivity::Eight => 8,
+	    Connectivity::Sixteen => 16,
+	    Connectivity::ThirtyTwo => 32,
+	}
+    }
+
+    // move directions in the first octant and its mirror, counter
+    // clockwise from +x to +y
+    fn quadrant_moves(&self) -> Vec<(i32, i32)> {
+	match self {
+	    Connectivity::Four => vec![(1, 0), (0, 1)],
+	    Connectivity::Eight => vec![(1, 0), (1, 1), (0, 1)],
+	    Connectivity::Sixteen => vec![(1, 0), (2, 1), (1, 1), (1, 2), (0, 1)],
+	    Connectivity::ThirtyTwo => vec![(1, 0), (3, 1), (2, 1), (3, 2), (1, 1),
+					    (2, 3), (1, 2), (1, 3), (0, 1)],
+	}
+    }
+}
+
+impl CornerCutting {
+    pub fn name(&self) -> &str {
+	match self {
+	    CornerCutting::Allow => "allow",
+	    CornerCutting::ForbidEither => "forbid either",
+	    CornerCutting::ForbidBoth => "forbid both",
+	}
+    }
+}
+
+pub struct GridMove
+{
+    pub dx: i32,
+    pub dy: i32,
+    // offsets of the spaces the move passes through, ends excluded
+    pub through: Vec<(i32, i32)>,
+    // pairs of spaces the move squeezes between at a corner
+    pub corners: Vec<((i32, i32), (i32, i32))>,
+}
+
+impl GridMove {
+    fn new(dx: i32, dy: i32) -> GridMove {
+	let mut m = GridMove {
+	    dx: dx,
+	    dy: dy,
+	    through: Vec::new(),
+	    corners: Vec::new(),
+	};
+
+	// from the middle of space 0,0 to the middle of space dx,dy,
+	// noting where it crosses the grid lines
+	let (fx, fy) = (dx as f64, dy as f64);
+	let mut crossings = Vec::<f64>::new();
+	for k in 1 ..= dx.abs() {
+	    crossings.push((k as f64 - 0.5) / fx.abs());
+	}
+	for k in 1 ..= dy.abs() {
+	    crossings.push((k as f64 - 0.5) / fy.abs());
+	}
+	crossings.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+	let space_at = |t: f64| ((0.5 + fx * t).floor() as i32, (0.5 + fy * t).floor() as i32);
+
+	let mut prev = 0.0;
+	for i in 0 .. crossings.len() {
+	    let t = crossings[i];
+
+	    // both lines at once: a grid corner
+	    if i > 0 && t - prev < 1e-9 {
+		let before = space_at(t - 1e-6);
+		let after = space_at(t + 1e-6);
+		m.corners.push(((before.0, after.1), (after.0, before.1)));
+	    }
+	    prev = t;
+
+	    let s = space_at(t + 1e-6);
+	    if s != (0, 0) && s != (dx, dy) && !m.through.contains(&s) {
+		m.through.push(s);
+	    }
+	}
+
+	m
+    }
+
+    // blocked(x, y) should be true off the edge of the grid too
+    pub fn allowed<F>(&self, x: i32, y: i32, policy: CornerCutting, blocked: F) -> bool
+    where F: Fn(i32, i32) -> bool
+    {
+	if self.through.iter().any(|(ox, oy)| blocked(x + ox, y + oy)) {
+	    return false;
+	}
+
+	self.corners.iter().all(|((ax, ay), (bx, by))| {
+	    let a = blocked(x + ax, y + ay);
+	    let b = blocked(x + bx, y + by);
+	    match policy {
+		CornerCutting::Allow => true,
+		CornerCutting::ForbidEither => !a && !b,
+		CornerCutting::ForbidBoth => !(a && b),
+	    }
+	})
+    }
+}
+
+pub fn grid_moves(connectivity: Connectivity) -> Vec<GridMove> {
+    let mut offsets = Vec::<(i32, i32)>::new();
+
+    for (a, b) in connectivity.quadrant_moves() {
+	for (sx, sy) in [(1, 1), (-1, 1), (1, -1), (-1, -1)] {
+	    let o = (a * sx, b * sy);
+	    if !offsets.contains(&o) {
+		offsets.push(o);
+	    }
+	}
+    }
+
+    offsets.into_iter().map(|(dx, dy)| GridMove::new(dx, dy)).collect()
+}
+
+// how far from a move's ends a space can be and still decide whether
+// the move is allowed; walls changing that close change the move
+pub fn move_reach(moves: &[GridMove], policy: CornerCutting) -> i32 {
+    let mut reach = 0;
+
+    for m in moves {
+	for (x, y) in &m.through {
+	    reach = reach.max(x.abs().max(y.abs()));
+	}
+	if policy != CornerCutting::Allow {
+	    for ((ax, ay), (bx, by)) in &m.corners {
+		reach = reach.max(ax.abs().max(ay.abs())).max(bx.abs().max(by.abs()));
+	    }
+	}
+    }
+
+    reach
+}
+
+// shortest distance in spaces across an empty grid with these moves:
+// split the offset between the two move directions either side of it
+pub fn free_distance(connectivity: Connectivity, dx: f32, dy: f32) -> f32 {
+    let (px, py) = (dx.abs(), dy.abs());
+    let directions = connectivity.quadrant_moves();
+
+    for w in directions.windows(2) {
+	let (a1, b1) = (w[0].0 as f32, w[0].1 as f32);
+	let (a2, b2) = (w[1].0 as f32, w[1].1 as f32);
+
+	// p is between the two directions, counter clockwise
+	if a1 * py - b1 * px < 0.0 || px * b2 - py * a2 < 0.0 {
+	    continue;
+	}
+
+	let det = a1 * b2 - a2 * b1;
+	let s = (px * b2 - py * a2) / det;
+	let t = (a1 * py - b1 * px) / det;
+	return s * (a1 * a1 + b1 * b1).sqrt() + t * (a2 * a2 + b2 * b2).sqrt();
+    }
+
+    (px * px + py * py).sqrt()
+}
@@ -0,0 +1,415 @@
+// big_dice_games/ai/orca.rs
+//
+// Optimal Reciprocal Collision Avoidance (van den Berg, Guy, Lin &
+// Manocha, "Reciprocal n-body Collision Avoidance", 2011). Each agent
+// turns every nearby agent into a half plane of allowed velocities,
+// taking half the responsibility for avoiding it, then picks the
+// allowed velocity closest to the one it would like. The linear
+// programs follow the RVO2 library.
+//
+// Obstacles are circles that don't react: they have a velocity but no
+// say, so agents take the whole responsibility for avoiding them.
+// Static walls are rows of obstacles with zero velocity.
+
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::math::vector::Vector;
+
+const ORCA_EPSILON: f32 = 0.00001;
+
+// agents' preferred velocities are turned by up to this many radians,
+// a different amount each, to break perfectly symmetric deadlocks
+const PERTURB_ANGLE: f32 = 0.05;
+
+pub struct OrcaAgent
+{
+    pub position: Vec2f,
+    pub velocity: Vec2f,
+    pub pref_velocity: Vec2f,
+    pub radius: f32,
+    pub max_speed: f32,
+
+    // waypoints to follow; pref_velocity is aimed at path[path_index]
+    pub path: Vec<Vec2f>,
+    pub path_index: usize,
+}
+
+impl OrcaAgent {
+    pub fn new(position: Vec2f, radius: f32, max_speed: f32, path: Vec<Vec2f>) -> OrcaAgent {
+	OrcaAgent {
+	    position: position,
+	    velocity: Vec2f::zero(),
+	    pref_velocity: Vec2f::zero(),
+	    radius: radius,
+	    max_speed: max_speed,
+	    path: path,
+	    path_index: 0,
+	}
+    }
+
+    pub fn is_finished(&self) -> bool {
+	self.path_index >= self.path.len()
+    }
+
+    // head for the current waypoint at full speed, slowing for the last.
+    // A waypoint counts as reached once we're close, or past it in the
+    // direction of the leg leading to it. If the crowd shoves us back
+    // behind the previous one we go back for it rather than cutting
+    // the corner.
+    pub fn update_pref_velocity(&mut self, waypoint_radius: f32) {
+	if self.path_index >= 2 && self.path_index < self.path.len() {
+	    let prev = self.path[self.path_index - 1];
+	    let incoming = prev - self.path[self.path_index - 2];
+	    let from_prev = self.position - prev;
+
+	    if from_prev.dot(&incoming) < 0.0 && from_prev.mag() > waypoint_radius {
+		self.path_index -= 1;
+	    }
+	}
+
+	while self.path_index < self.path.len() {
+	    let target = self.path[self.path_index];
+	    let to_target = target - self.position;
+	    let last = self.path_index + 1 == self.path.len();
+
+	    if !last {
+		let passed = self.path_index > 0 &&
+		    (self.position - target).dot(&(target - self.path[self.path_index - 1])) > 0.0;
+
+		if passed || to_target.mag() < waypoint_radius {
+		    self.path_index += 1;
+		    continue;
+		}
+	    }
+
+	    if last && to_target.mag() < self.radius * 0.25 {
+		self.path_index += 1;
+		break;
+	    }
+
+	    self.pref_velocity = if last && to_target.mag() < self.max_speed {
+		to_target
+	    } else {
+		to_target.normalized().scale(self.max_speed)
+	    };
+	    return;
+	}
+
+	self.pref_velocity = Vec2f::zero();
+    }
+}
+
+pub struct OrcaObstacle
+{
+    pub position: Vec2f,
+    pub velocity: Vec2f,
+    pub radius: f32,
+}
+
+// velocities on the left of the directed line are allowed
+#[derive(Copy, Clone, Debug)]
+pub struct OrcaLine
+{
+    pub point: Vec2f,
+    pub direction: Vec2f,
+}
+
+pub struct OrcaSimulator
+{
+    pub agents: Vec<OrcaAgent>,
+    pub obstacles: Vec<OrcaObstacle>,
+
+    // how far ahead, in seconds, collisions are looked for
+    pub time_horizon: f32,
+    pub obstacle_time_horizon: f32,
+    // others further than this are ignored
+    pub neighbor_dist: f32,
+}
+
+// the half plane of velocities for which a body at relative_position
+// moving with relative_velocity (ours minus theirs) doesn't hit us
+// within time_horizon; responsibility is our share of the dodge
+fn orca_line(velocity: Vec2f, relative_position: Vec2f, relative_velocity: Vec2f,
+	     combined_radius: f32, time_horizon: f32, dt: f32,
+	     responsibility: f32) -> OrcaLine
+{
+    let dist_sq = relative_position.mag_sq();
+    let combined_radius_sq = combined_radius * combined_radius;
+    let inv_time_horizon = 1.0 / time_horizon;
+
+    let direction;
+    let u;
+
+    if dist_sq > combined_radius_sq {
+	// no collision yet; w is from the cutoff circle's center
+	let w = relative_velocity - relative_position.scale(inv_time_horizon);
+	let w_len_sq = w.mag_sq();
+	let dot_product = w.dot(&relative_position);
+
+	if dot_product < 0.0 && dot_product * dot_product > combined_radius_sq * w_len_sq {
+	    // closest to the cutoff circle
+	    let w_len = w_len_sq.sqrt();
+	    let unit_w = w.scale(1.0 / w_len);
+
+	    direction = Vec2f::new(unit_w.y, -unit_w.x);
+	    u = unit_w.scale(combined_radius * inv_time_horizon - w_len);
+	} else {
+	    // closest to one of the legs of the cone
+	    let leg = (dist_sq - combined_radius_sq).sqrt();
+	    let p = relative_position;
+	    let r = combined_radius;
+
+	    direction = if p.cross(&w) > 0.0 {
+		Vec2f::new(p.x * leg - p.y * r, p.x * r + p.y * leg).scale(1.0 / dist_sq)
+	    } else {
+		Vec2f::new(p.x * leg + p.y * r, -p.x * r + p.y * leg).scale(-1.0 / dist_sq)
+	    };
+
+	    let dot_product = relative_velocity.dot(&direction);
+	    u = direction.scale(dot_product) - relative_velocity;
+	}
+    } else {
+	// already overlapping, get apart within this time step
+	let inv_time_step = 1.0 / dt;
+	let w = relative_velocity - relative_position.scale(inv_time_step);
+	let w_len = w.mag();
+	let unit_w = w.normalized();
+
+	direction = Vec2f::new(unit_w.y, -unit_w.x);
+	u = unit_w.scale(combined_radius * inv_time_step - w_len);
+    }
+
+    OrcaLine {
+	point: velocity + u.scale(responsibility),
+	direction: direction,
+    }
+}
+
+// optimize along line line_no, subject to the lines before it
+fn linear_program_1(lines: &[OrcaLine], line_no: usize, radius: f32,
+		    opt_velocity: Vec2f, direction_opt: bool,
+		    result: &mut Vec2f) -> bool
+{
+    let line = &lines[line_no];
+    let dot_product = line.point.dot(&line.direction);
+    let discriminant = dot_product * dot_product + radius * radius - line.point.mag_sq();
+
+    if discriminant < 0.0 {
+	// max speed circle misses the line entirely
+	return false;
+    }
+
+    let sqrt_discriminant = discriminant.sqrt();
+    let mut t_left = -dot_product - sqrt_discriminant;
+    let mut t_right = -dot_product + sqrt_discriminant;
+
+    for i in 0 .. line_no {
+	let denominator = line.direction.cross(&lines[i].direction);
+	let numerator = lines[i].direction.cross(&(line.point - lines[i].point));
+
+	if denominator.abs() <= ORCA_EPSILON {
+	    // parallel
+	    if numerator < 0.0 {
+		return false;
+	    }
+	    continue;
+	}
+
+	let t = numerator / denominator;
+	if denominator >= 0.0 {
+	    t_right = t_right.min(t);
+	} else {
+	    t_left = t_left.max(t);
+	}
+
+	if t_left > t_right {
+	    return false;
+	}
+    }
+
+    let t = if direction_opt {
+	if opt_velocity.dot(&line.direction) > 0.0 { t_right } else { t_left }
+    } else {
+	line.direction.dot(&(opt_velocity - line.point)).clamp(t_left, t_right)
+    };
+
+    *result = line.point + line.direction.scale(t);
+    true
+}
+
+// closest velocity to opt_velocity inside every half plane and the max
+// speed circle; returns the index of the line it failed on, or
+// lines.len() on success
+fn linear_program_2(lines: &[OrcaLine], radius: f32, opt_velocity: Vec2f,
+		    direction_opt: bool, result: &mut Vec2f) -> usize
+{
+    *result = if direction_opt {
+	opt_velocity.scale(radius)
+    } else if opt_velocity.mag_sq() > radius * radius {
+	opt_velocity.normalized().scale(radius)
+    } else {
+	opt_velocity
+    };
+
+    for i in 0 .. lines.len() {
+	if lines[i].direction.cross(&(lines[i].point - *result)) > 0.0 {
+	    // result is on the wrong side of this line
+	    let temp_result = *result;
+	    if !linear_program_1(lines, i, radius, opt_velocity, direction_opt, result) {
+		*result = temp_result;
+		return i;
+	    }
+	}
+    }
+
+    lines.len()
+}
+
+// when there's no feasible velocity, minimize the worst violation of
+// the soft lines while still keeping the first hard_line_count
+fn linear_program_3(lines: &[OrcaLine], hard_line_count: usize, begin_line: usize,
+		    radius: f32, result: &mut Vec2f)
+{
+    let mut distance = 0.0;
+
+    for i in begin_line .. lines.len() {
+	if lines[i].direction.cross(&(lines[i].point - *result)) <= distance {
+	    continue;
+	}
+
+	let mut proj_lines: Vec<OrcaLine> = lines[.. hard_line_count].to_vec();
+
+	for j in hard_line_count .. i {
+	    let determinant = lines[i].direction.cross(&lines[j].direction);
+
+	    let point = if determinant.abs() <= ORCA_EPSILON {
+		if lines[i].direction.dot(&lines[j].direction) > 0.0 {
+		    // same direction
+		    continue;
+		}
+		(lines[i].point + lines[j].point).scale(0.5)
+	    } else {
+		let t = lines[j].direction.cross(&(lines[i].point - lines[j].point)) / determinant;
+		lines[i].point + lines[i].direction.scale(t)
+	    };
+
+	    proj_lines.push(OrcaLine {
+		point: point,
+		direction: (lines[j].direction - lines[i].direction).normalized(),
+	    });
+	}
+
+	let temp_result = *result;
+	let opt = Vec2f::new(-lines[i].direction.y, lines[i].direction.x);
+	if linear_program_2(&proj_lines, radius, opt, true, result) < proj_lines.len() {
+	    // shouldn't happen, the result is feasible by definition,
+	    // but floating point can say otherwise
+	    *result = temp_result;
+	}
+
+	distance = lines[i].direction.cross(&(lines[i].point - *result));
+    }
+}
+
+impl OrcaSimulator {
+    pub fn new(time_horizon: f32, obstacle_time_horizon: f32, neighbor_dist: f32) -> OrcaSimulator {
+	OrcaSimulator {
+	    agents: Vec::new(),
+	    obstacles: Vec::new(),
+	    time_horizon: time_horizon,
+	    obstacle_time_horizon: obstacle_time_horizon,
+	    neighbor_dist: neighbor_dist,
+	}
+    }
+
+    // the ORCA half planes for agent i; obstacles come first, they're
+    // the ones that mustn't be given up when things get crowded
+    pub fn compute_lines(&self, i: usize, dt: f32) -> (Vec<OrcaLine>, usize) {
+	let agent = &self.agents[i];
+	let mut lines = Vec::<OrcaLine>::new();
+
+	for o in &self.obstacles {
+	    let relative_position = o.position - agent.position;
+	    if relative_position.mag() - o.radius > self.neighbor_dist {
+		continue;
+	    }
+
+	    lines.push(orca_line(agent.velocity,
+				 relative_position,
+				 agent.velocity - o.velocity,
+				 agent.radius + o.radius,
+				 self.obstacle_time_horizon,
+				 dt,
+				 1.0));
+	}
+
+	let hard_line_count = lines.len();
+
+	for (j, other) in self.agents.iter().enumerate() {
+	    if j == i {
+		continue;
+	    }
+
+	    let relative_position = other.position - agent.position;
+	    if relative_position.mag() > self.neighbor_dist {
+		continue;
+	    }
+
+	    lines.push(orca_line(agent.velocity,
+				 relative_position,
+				 agent.velocity - other.velocity,
+				 agent.radius + other.radius,
+				 self.time_horizon,
+				 dt,
+				 // parked agents won't be dodging back
+				 if other.is_finished() { 1.0 } else { 0.5 }));
+	}
+
+	(lines, hard_line_count)
+    }
+
+    pub fn step(&mut self, dt: f32, waypoint_radius: f32) {
+	if dt <= 0.0 {
+	    return;
+	}
+
+	for (i, agent) in self.agents.iter_mut().enumerate() {
+	    agent.update_pref_velocity(waypoint_radius);
+
+	    let theta = ((i as f32 * 0.618034).fract() - 0.5) * PERTURB_ANGLE;
+	    let v = agent.pref_velocity;
+	    agent.pref_velocity = Vec2f::new(v.x * theta.cos() - v.y * theta.sin(),
+					     v.x * theta.sin() + v.y * theta.cos());
+	}
+
+	let mut new_velocities = Vec::<Vec2f>::new();
+
+	for i in 0 .. self.agents.len() {
+	    let agent = &self.agents[i];
+	    if agent.is_finished() {
+		new_velocities.push(Vec2f::zero());
+		continue;
+	    }
+
+	    let (lines, hard_line_count) = self.compute_lines(i, dt);
+
+	    let mut new_velocity = Vec2f::zero();
+	    let fail_line = linear_program_2(&lines, agent.max_speed, agent.pref_velocity,
+					     false, &mut new_velocity);
+	    if fail_line < lines.len() {
+		linear_program_3(&lines, hard_line_count, fail_line,
+				 agent.max_speed, &mut new_velocity);
+	    }
+
+	    new_velocities.push(new_velocity);
+	}
+
+	for (agent, v) in self.agents.iter_mut().zip(new_velocities) {
+	    agent.velocity = v;
+	    agent.position = agent.position + v.scale(dt);
+	}
+
+	for o in self.obstacles.iter_mut() {
+	    o.position = o.position + o.velocity.scale(dt);
+	}
+    }
+}
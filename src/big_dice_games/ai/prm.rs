@@ -0,0 +1,223 @@
+// big_dice_games/ai/prm.rs
+//
+// Probabilistic roadmaps: link each sample to the ones near it with
+// straight edges, keep the edges that don't hit anything, and search
+// the result. The samples can come from anywhere; the nav screens feed
+// in their Bridson points. Lazy PRM skips the up front edge checks,
+// searching as if every edge were clear and only checking the edges
+// on the path it finds, throwing out the ones that fail and searching
+// again. Collision checks are usually the expensive part, and most
+// edges never end up on a candidate path.
+
+use std::collections::HashMap;
+
+use crate::big_dice_games::ai::astar::a_star;
+use crate::big_dice_games::ai::astar::SearchResult;
+use crate::big_dice_games::ai::graph::NavGraph;
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::math::vector::Vector;
+
+// which samples get linked
+#[derive(Copy, Clone, Debug)]
+pub enum Connection {
+    Nearest(usize),
+    Radius(f32),
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum EdgeState {
+    Unchecked,
+    Free,
+    Blocked,
+}
+
+pub struct Roadmap
+{
+    pub points: Vec<Vec2f>,
+    open: Vec<bool>,
+
+    // candidate edges, each listed from both ends
+    pub candidates: Vec<Vec<(i32, f32)>>,
+    // by edge_key; anything missing is unchecked
+    edge_states: HashMap<(i32, i32), EdgeState>,
+
+    // collision checks run so far
+    pub checks: usize,
+}
+
+pub struct LazyResult
+{
+    pub result: SearchResult,
+    // how many times A* ran before the path held up
+    pub searches: usize,
+}
+
+fn edge_key(a: i32, b: i32) -> (i32, i32) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+// distance from p to the segment a-b
+pub fn segment_distance(a: Vec2f, b: Vec2f, p: Vec2f) -> f32 {
+    let ab = b - a;
+    let len_sq = ab.mag_sq();
+    if len_sq <= 0.0 {
+	return (p - a).mag();
+    }
+
+    let t = ((p - a).dot(&ab) / len_sq).clamp(0.0, 1.0);
+    (p - (a + ab.scale(t))).mag()
+}
+
+impl Roadmap {
+    // closed points stay in, so indices match the caller's, but get no
+    // edges
+    pub fn new(points: &[Vec2f], open: &[bool], connection: Connection) -> Roadmap {
+	let n = points.len();
+	let mut candidates = vec![Vec::<(i32, f32)>::new(); n];
+
+	let add_edge = |candidates: &mut Vec<Vec<(i32, f32)>>, i: usize, j: usize| {
+	    if candidates[i].iter().any(|(k, _)| *k == j as i32) {
+		return;
+	    }
+	    let cost = (points[j] - points[i]).mag();
+	    candidates[i].push((j as i32, cost));
+	    candidates[j].push((i as i32, cost));
+	};
+
+	for i in 0 .. n {
+	    if !open[i] {
+		continue;
+	    }
+
+	    let mut near: Vec<(usize, f32)> = (0 .. n)
+		.filter(|j| *j != i && open[*j])
+		.map(|j| (j, (points[j] - points[i]).mag()))
+		.collect();
+
+	    match connection {
+		Connection::Nearest(k) => {
+		    near.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+		    near.truncate(k);
+		}
+		Connection::Radius(r) => {
+		    near.retain(|(_, d)| *d <= r);
+		}
+	    }
+
+	    for (j, _) in near {
+		add_edge(&mut candidates, i, j);
+	    }
+	}
+
+	Roadmap {
+	    points: points.to_vec(),
+	    open: open.to_vec(),
+	    candidates: candidates,
+	    edge_states: HashMap::new(),
+	    checks: 0,
+	}
+    }
+
+    pub fn edge_state(&self, a: i32, b: i32) -> EdgeState {
+	match self.edge_states.get(&edge_key(a, b)) {
+	    None => EdgeState::Unchecked,
+	    Some(s) => *s,
+	}
+    }
+
+    pub fn edge_count(&self) -> usize {
+	self.candidates.iter().map(|c| c.len()).sum::<usize>() / 2
+    }
+
+    fn check_edge<F>(&mut self, a: i32, b: i32, is_free: &F) -> bool
+    where F: Fn(Vec2f, Vec2f) -> bool
+    {
+	let state = self.edge_state(a, b);
+	if state != EdgeState::Unchecked {
+	    return state == EdgeState::Free;
+	}
+
+	self.checks += 1;
+	let free = is_free(self.points[a as usize], self.points[b as usize]);
+	self.edge_states.insert(edge_key(a, b),
+				if free { EdgeState::Free } else { EdgeState::Blocked });
+	free
+    }
+
+    // plain PRM: check every candidate edge now
+    pub fn check_all<F>(&mut self, is_free: F)
+    where F: Fn(Vec2f, Vec2f) -> bool
+    {
+	for i in 0 .. self.candidates.len() {
+	    for k in 0 .. self.candidates[i].len() {
+		let j = self.candidates[i][k].0;
+		if j > i as i32 {
+		    self.check_edge(i as i32, j, &is_free);
+		}
+	    }
+	}
+    }
+
+    pub fn find_path(&self, start: i32, goal: i32) -> SearchResult {
+	a_star(self, start, goal,
+	       |a, b| (self.points[a as usize] - self.points[b as usize]).mag())
+    }
+
+    // Lazy PRM: search, check the path's edges, drop any that fail and
+    // go again until a path survives or there isn't one
+    pub fn lazy_find_path<F>(&mut self, start: i32, goal: i32, is_free: F) -> LazyResult
+    where F: Fn(Vec2f, Vec2f) -> bool
+    {
+	let mut searches = 0;
+
+	loop {
+	    let result = self.find_path(start, goal);
+	    searches += 1;
+
+	    if !result.found() {
+		return LazyResult {
+		    result: result,
+		    searches: searches,
+		};
+	    }
+
+	    // check them all, not just up to the first failure, so the
+	    // next search knows as much as possible
+	    let mut all_free = true;
+	    for w in result.path.windows(2) {
+		if !self.check_edge(w[0], w[1], &is_free) {
+		    all_free = false;
+		}
+	    }
+
+	    if all_free {
+		return LazyResult {
+		    result: result,
+		    searches: searches,
+		};
+	    }
+	}
+    }
+}
+
+// every candidate edge that isn't known to be blocked
+impl NavGraph for Roadmap {
+    fn node_count(&self) -> usize {
+	self.points.len()
+    }
+
+    fn node_point(&self, i: i32) -> Vec2f {
+	self.points[i as usize]
+    }
+
+    fn neighbors(&self, i: i32) -> Vec<(i32, f32)> {
+	self.candidates[i as usize].iter()
+	    .filter(|(j, _)| self.edge_state(i, *j) != EdgeState::Blocked)
+	    .cloned()
+	    .collect()
+    }
+
+    fn is_open(&self, i: i32) -> bool {
+	self.open[i as usize]
+    }
+}
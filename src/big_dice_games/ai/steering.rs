@@ -0,0 +1,224 @@
+// big_dice_games/ai/steering.rs
+//
+// Reynolds style steering behaviours ("Steering Behaviors For
+// Autonomous Characters", GDC 1999). Each behaviour returns a desired
+// acceleration; mix them with weights, then integrate() clamps to the
+// agent's limits so motion stays smooth whatever the planner hands us.
+
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::math::vector::Vector;
+
+pub struct SteeringAgent
+{
+    pub position: Vec2f,
+    pub velocity: Vec2f,
+    pub radius: f32,
+    pub max_speed: f32,
+    pub max_acceleration: f32,
+
+    // where on the wander circle we're currently aiming, in radians
+    pub wander_angle: f32,
+}
+
+impl SteeringAgent {
+    pub fn new(position: Vec2f, radius: f32, max_speed: f32, max_acceleration: f32)
+	       -> SteeringAgent {
+	SteeringAgent {
+	    position: position,
+	    velocity: Vec2f::zero(),
+	    radius: radius,
+	    max_speed: max_speed,
+	    max_acceleration: max_acceleration,
+	    wander_angle: 0.0,
+	}
+    }
+
+    // facing, in radians, along the velocity; 0 if standing still
+    pub fn heading(&self) -> f32 {
+	if self.velocity.mag_sq() <= 0.0 {
+	    return 0.0;
+	}
+	self.velocity.y.atan2(self.velocity.x)
+    }
+
+    // apply acceleration for dt, within max_acceleration and max_speed
+    pub fn integrate(&mut self, acceleration: Vec2f, dt: f32) {
+	let accel = truncate(acceleration, self.max_acceleration);
+	self.velocity = truncate(self.velocity + accel.scale(dt), self.max_speed);
+	self.position = self.position + self.velocity.scale(dt);
+    }
+
+    // full speed straight at target
+    pub fn seek(&self, target: Vec2f) -> Vec2f {
+	let desired = (target - self.position).normalized().scale(self.max_speed);
+	desired - self.velocity
+    }
+
+    // like seek, but slowing down to stop at target, starting
+    // slowing_radius away
+    pub fn arrive(&self, target: Vec2f, slowing_radius: f32) -> Vec2f {
+	let to_target = target - self.position;
+	let dist = to_target.mag();
+	if dist <= 0.0 {
+	    return self.velocity.scale(-1.0);
+	}
+
+	let speed = if dist < slowing_radius {
+	    self.max_speed * dist / slowing_radius
+	} else {
+	    self.max_speed
+	};
+
+	to_target.scale(speed / dist) - self.velocity
+    }
+
+    // predict where we'll be lookahead_time from now, find the closest
+    // point on the path to that, and aim lookahead_dist further along.
+    // Arrives at the end of the path. Returns the acceleration and the
+    // point aimed at, which is handy for drawing.
+    pub fn follow_path(&self, path: &[Vec2f], lookahead_time: f32,
+		       lookahead_dist: f32, slowing_radius: f32) -> (Vec2f, Vec2f) {
+	if path.is_empty() {
+	    return (self.velocity.scale(-1.0), self.position);
+	}
+	if path.len() == 1 {
+	    return (self.arrive(path[0], slowing_radius), path[0]);
+	}
+
+	let predicted = self.position + self.velocity.scale(lookahead_time);
+
+	// distance along the path of the closest point to predicted
+	let mut best_dist = f32::INFINITY;
+	let mut best_along = 0.0;
+	let mut along = 0.0;
+
+	for i in 1 .. path.len() {
+	    let a = path[i - 1];
+	    let seg = path[i] - a;
+	    let seg_len = seg.mag();
+	    if seg_len <= 0.0 {
+		continue;
+	    }
+
+	    let t = ((predicted - a).dot(&seg) / (seg_len * seg_len)).clamp(0.0, 1.0);
+	    let closest = a + seg.scale(t);
+	    let d = (predicted - closest).mag();
+	    if d < best_dist {
+		best_dist = d;
+		best_along = along + t * seg_len;
+	    }
+
+	    along += seg_len;
+	}
+
+	let total_len = along;
+	let target_along = best_along + lookahead_dist;
+
+	if target_along >= total_len {
+	    let end = path[path.len() - 1];
+	    return (self.arrive(end, slowing_radius), end);
+	}
+
+	let target = point_along(path, target_along);
+	(self.seek(target), target)
+    }
+
+    // push away from anybody inside radius, harder the closer they are
+    pub fn separation(&self, neighbors: &[Vec2f], radius: f32) -> Vec2f {
+	let mut push = Vec2f::zero();
+
+	for n in neighbors {
+	    let away = self.position - *n;
+	    let dist = away.mag();
+	    if dist <= 0.0 || dist >= radius {
+		continue;
+	    }
+	    push = push + away.scale((radius - dist) / (radius * dist));
+	}
+
+	if push.mag_sq() <= 0.0 {
+	    return push;
+	}
+
+	push.normalized().scale(self.max_speed) - self.velocity
+    }
+
+    // steer sideways around the nearest circle (center, radius) that our
+    // path for the next lookahead_time seconds would clip
+    pub fn avoid_obstacles(&self, obstacles: &[(Vec2f, f32)], lookahead_time: f32) -> Vec2f {
+	let speed = self.velocity.mag();
+	if speed <= 0.0 {
+	    return Vec2f::zero();
+	}
+
+	let forward = self.velocity.scale(1.0 / speed);
+	let reach = speed * lookahead_time;
+
+	let mut nearest_along = f32::INFINITY;
+	let mut lateral_push = Vec2f::zero();
+
+	for (center, obstacle_radius) in obstacles {
+	    let to_center = *center - self.position;
+	    let along = to_center.dot(&forward);
+	    if along <= 0.0 || along > reach + obstacle_radius {
+		continue;
+	    }
+
+	    // how far off our line of travel it is
+	    let lateral = to_center.cross(&forward);
+	    let clearance = obstacle_radius + self.radius;
+	    if lateral.abs() >= clearance || along >= nearest_along {
+		continue;
+	    }
+
+	    nearest_along = along;
+
+	    // turn away from the side the obstacle is on
+	    let side = if lateral > 0.0 { 1.0 } else { -1.0 };
+	    let urgency = 1.0 - along / (reach + obstacle_radius);
+	    lateral_push = forward.perp().scale(side * self.max_acceleration * urgency);
+	}
+
+	lateral_push
+    }
+
+    // aim at a point on a circle out in front, nudging that point round
+    // the circle each frame. jitter is in -1 .. 1, from the caller's
+    // random source, scaled by max_jitter radians.
+    pub fn wander(&mut self, jitter: f32, max_jitter: f32,
+		  circle_distance: f32, circle_radius: f32) -> Vec2f {
+	self.wander_angle += jitter * max_jitter;
+
+	let heading = self.heading();
+	let ahead = Vec2f::new(heading.cos(), heading.sin()).scale(circle_distance);
+	let offset = Vec2f::new((heading + self.wander_angle).cos(),
+				(heading + self.wander_angle).sin()).scale(circle_radius);
+
+	self.seek(self.position + ahead + offset)
+    }
+}
+
+// v, shortened to max_len if it's longer
+pub fn truncate(v: Vec2f, max_len: f32) -> Vec2f {
+    let len = v.mag();
+    if len > max_len && len > 0.0 {
+	return v.scale(max_len / len);
+    }
+    v
+}
+
+// the point dist along the polyline
+pub fn point_along(path: &[Vec2f], dist: f32) -> Vec2f {
+    let mut remaining = dist.max(0.0);
+
+    for i in 1 .. path.len() {
+	let seg = path[i] - path[i - 1];
+	let seg_len = seg.mag();
+	if remaining <= seg_len && seg_len > 0.0 {
+	    return path[i - 1] + seg.scale(remaining / seg_len);
+	}
+	remaining -= seg_len;
+    }
+
+    path[path.len() - 1]
+}
@@ -0,0 +1,148 @@
+// big_dice_games/ai/visibility.rs
+//
+// Visibility graph: among polygonal obstacles the shortest path only
+// ever bends at obstacle corners that stick out into free space, so
+// linking every pair of those corners (and the start and goal) that
+// can see each other gives a graph whose shortest path is the exact
+// Euclidean shortest path. Building it is O(n^3) here, fine for the
+// tens of corners a hand drawn map has.
+
+use crate::big_dice_games::ai::astar::a_star;
+use crate::big_dice_games::ai::astar::SearchResult;
+use crate::big_dice_games::ai::graph::NavGraph;
+use crate::big_dice_games::ai::navmesh::point_in_polygon;
+use crate::big_dice_games::math::triangulation::orient;
+use crate::big_dice_games::math::triangulation::segments_cross;
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::math::vector::Vector;
+
+// how far along a sight line to check it leaves its corner outward
+const NUDGE: f32 = 0.01;
+
+pub struct VisibilityGraph
+{
+    // the corners used, then start and goal as the last two
+    pub points: Vec<Vec2f>,
+    pub edges: Vec<Vec<(i32, f32)>>,
+}
+
+// corners where the polygon bulges out, which is where paths can wrap
+pub fn convex_corners(polygon: &[Vec2f]) -> Vec<Vec2f> {
+    let n = polygon.len();
+    if n < 3 {
+	return polygon.to_vec();
+    }
+
+    // which way round it's wound
+    let mut area = 0.0;
+    for i in 0 .. n {
+	area += orient(Vec2f::zero(), polygon[i], polygon[(i + 1) % n]);
+    }
+
+    (0 .. n)
+	.filter(|i| {
+	    let turn = orient(polygon[(i + n - 1) % n], polygon[*i], polygon[(i + 1) % n]);
+	    (turn > 0.0) == (area > 0.0)
+	})
+	.map(|i| polygon[i])
+	.collect()
+}
+
+fn same_point(a: Vec2f, b: Vec2f) -> bool {
+    a.x == b.x && a.y == b.y
+}
+
+// can you see b from a without passing through an obstacle? Grazing
+// corners and running along edges is fine.
+pub fn is_visible(obstacles: &[Vec<Vec2f>], a: Vec2f, b: Vec2f) -> bool {
+    for o in obstacles {
+	for i in 0 .. o.len() {
+	    let c = o[i];
+	    let d = o[(i + 1) % o.len()];
+
+	    // walking along an obstacle's side; the probes below would
+	    // land right on its boundary
+	    if (same_point(a, c) && same_point(b, d)) || (same_point(a, d) && same_point(b, c)) {
+		return true;
+	    }
+
+	    if segments_cross(a, b, c, d) {
+		return false;
+	    }
+	}
+    }
+
+    // no edge crossed, but a line between two corners can still cut
+    // straight through the middle of an obstacle
+    let d = b - a;
+    let len = d.mag();
+    if len <= 0.0 {
+	return true;
+    }
+
+    let nudge = (NUDGE * len).min(0.5);
+    let probes = [a + d.scale(nudge / len),
+		  a + d.scale(0.5),
+		  b - d.scale(nudge / len)];
+
+    !probes.iter().any(|p| obstacles.iter().any(|o| point_in_polygon(o, *p)))
+}
+
+impl VisibilityGraph {
+    pub fn build(obstacles: &[Vec<Vec2f>], start: Vec2f, goal: Vec2f) -> VisibilityGraph {
+	let mut points = Vec::<Vec2f>::new();
+	for o in obstacles {
+	    points.extend(convex_corners(o));
+	}
+	points.push(start);
+	points.push(goal);
+
+	let mut edges = vec![Vec::<(i32, f32)>::new(); points.len()];
+
+	for i in 0 .. points.len() {
+	    for j in i + 1 .. points.len() {
+		if is_visible(obstacles, points[i], points[j]) {
+		    let cost = (points[j] - points[i]).mag();
+		    edges[i].push((j as i32, cost));
+		    edges[j].push((i as i32, cost));
+		}
+	    }
+	}
+
+	VisibilityGraph {
+	    points: points,
+	    edges: edges,
+	}
+    }
+
+    pub fn start_index(&self) -> i32 {
+	self.points.len() as i32 - 2
+    }
+
+    pub fn goal_index(&self) -> i32 {
+	self.points.len() as i32 - 1
+    }
+
+    pub fn edge_count(&self) -> usize {
+	self.edges.iter().map(|e| e.len()).sum::<usize>() / 2
+    }
+
+    pub fn find_path(&self) -> SearchResult {
+	a_star(self, self.start_index(), self.goal_index(),
+	       |a, b| (self.points[a as usize] - self.points[b as usize]).mag())
+    }
+}
+
+impl NavGraph for VisibilityGraph {
+    fn node_count(&self) -> usize {
+	self.points.len()
+    }
+
+    fn node_point(&self, i: i32) -> Vec2f {
+	self.points[i as usize]
+    }
+
+    fn neighbors(&self, i: i32) -> Vec<(i32, f32)> {
+	self.edges[i as usize].clone()
+    }
+}
@@ -0,0 +1,144 @@
+// big_dice_games/math/hex.rs
+//
+// Hex grid coordinates, after Red Blob Games' hex guide. Hexes are
+// stored axial (q, r); the third cube coordinate s = -q - r is worked
+// out when needed. A layout turns them into pixels for either pointy
+// topped hexes (rows) or flat topped ones (columns).
+
+use crate::big_dice_games::math::vector::Vec2f;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Hex
+{
+    pub q: i32,
+    pub r: i32,
+}
+
+// the six neighbors, going round
+pub const HEX_DIRECTIONS: [Hex; 6] = [Hex { q: 1, r: 0 }, Hex { q: 1, r: -1 }, Hex { q: 0, r: -1 },
+				      Hex { q: -1, r: 0 }, Hex { q: -1, r: 1 }, Hex { q: 0, r: 1 }];
+
+impl Hex {
+    pub fn new(q: i32, r: i32) -> Hex {
+	Hex {
+	    q: q,
+	    r: r,
+	}
+    }
+
+    pub fn s(&self) -> i32 {
+	-self.q - self.r
+    }
+
+    pub fn neighbor(&self, direction: usize) -> Hex {
+	let d = HEX_DIRECTIONS[direction % 6];
+	Hex::new(self.q + d.q, self.r + d.r)
+    }
+
+    pub fn neighbors(&self) -> [Hex; 6] {
+	[self.neighbor(0), self.neighbor(1), self.neighbor(2),
+	 self.neighbor(3), self.neighbor(4), self.neighbor(5)]
+    }
+
+    // steps between, which is also an exact heuristic on an open grid
+    pub fn distance(&self, other: &Hex) -> i32 {
+	let dq = (self.q - other.q).abs();
+	let dr = (self.r - other.r).abs();
+	let ds = (self.s() - other.s()).abs();
+	(dq + dr + ds) / 2
+    }
+
+    // the hex holding fractional cube coordinates: round each, then fix
+    // up whichever was rounded furthest so they sum to zero again
+    pub fn round(q: f32, r: f32) -> Hex {
+	let s = -q - r;
+	let mut rq = q.round();
+	let mut rr = r.round();
+	let rs = s.round();
+
+	let dq = (rq - q).abs();
+	let dr = (rr - r).abs();
+	let ds = (rs - s).abs();
+
+	if dq > dr && dq > ds {
+	    rq = -rr - rs;
+	} else if dr > ds {
+	    rr = -rq - rs;
+	}
+
+	Hex::new(rq as i32, rr as i32)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum HexOrientation {
+    Pointy,
+    Flat,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct HexLayout
+{
+    pub orientation: HexOrientation,
+    // centre to corner
+    pub size: f32,
+    // where hex 0, 0 goes
+    pub origin: Vec2f,
+}
+
+impl HexLayout {
+    pub fn new(orientation: HexOrientation, size: f32, origin: Vec2f) -> HexLayout {
+	HexLayout {
+	    orientation: orientation,
+	    size: size,
+	    origin: origin,
+	}
+    }
+
+    pub fn hex_to_pixel(&self, h: Hex) -> Vec2f {
+	let sqrt_3 = 3.0_f32.sqrt();
+	let q = h.q as f32;
+	let r = h.r as f32;
+
+	let (x, y) = match self.orientation {
+	    HexOrientation::Pointy => (sqrt_3 * q + sqrt_3 * 0.5 * r, 1.5 * r),
+	    HexOrientation::Flat => (1.5 * q, sqrt_3 * 0.5 * q + sqrt_3 * r),
+	};
+
+	Vec2f::new(self.origin.x + x * self.size, self.origin.y + y * self.size)
+    }
+
+    pub fn pixel_to_hex(&self, p: Vec2f) -> Hex {
+	let sqrt_3 = 3.0_f32.sqrt();
+	let x = (p.x - self.origin.x) / self.size;
+	let y = (p.y - self.origin.y) / self.size;
+
+	let (q, r) = match self.orientation {
+	    HexOrientation::Pointy => (sqrt_3 / 3.0 * x - y / 3.0, 2.0 / 3.0 * y),
+	    HexOrientation::Flat => (2.0 / 3.0 * x, -x / 3.0 + sqrt_3 / 3.0 * y),
+	};
+
+	Hex::round(q, r)
+    }
+
+    pub fn corners(&self, h: Hex) -> [Vec2f; 6] {
+	let center = self.hex_to_pixel(h);
+	let start_angle: f32 = match self.orientation {
+	    HexOrientation::Pointy => 30.0,
+	    HexOrientation::Flat => 0.0,
+	};
+
+	let mut out = [center; 6];
+	for (i, corner) in out.iter_mut().enumerate() {
+	    let angle = (start_angle + 60.0 * i as f32).to_radians();
+	    *corner = Vec2f::new(center.x + self.size * angle.cos(),
+				 center.y + self.size * angle.sin());
+	}
+	out
+    }
+
+    // centre to centre
+    pub fn spacing(&self) -> f32 {
+	self.size * 3.0_f32.sqrt()
+    }
+}
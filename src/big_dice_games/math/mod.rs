@@ -1,3 +1,7 @@
 // BigDiceGames/Math/mod.rs
 
 pub mod vector;
+pub mod triangulation;
+pub mod voronoi;
+pub mod sampling;
+pub mod hex;
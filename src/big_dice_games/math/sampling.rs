@@ -0,0 +1,168 @@
+// big_dice_games/math/sampling.rs
+//
+// Ways of scattering nav graph points over a rectangle, behind one
+// interface so a screen can swap between them. Each is asked for about
+// the same number of points, so graphs built on them are comparable.
+// Bridson sampling isn't here; the nav screens grow that one a point
+// at a time so it can be watched.
+
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::util::rng::Rng;
+
+pub trait PointGenerator {
+    fn name(&self) -> &str;
+
+    // about count points over 0,0 - width,height
+    fn generate(&self, width: f32, height: f32, count: usize, seed: u64) -> Vec<Vec2f>;
+}
+
+pub struct UniformRandom;
+
+// one point per square cell, nudged up to jitter cells from the centre
+pub struct JitteredGrid
+{
+    pub jitter: f32,
+}
+
+// rows of equilateral triangles, every other row shifted half a step
+pub struct HexLattice;
+
+// low discrepancy: van der Corput in bases 2 and 3
+pub struct Halton;
+
+// low discrepancy: the first two Sobol dimensions, digitally shifted by
+// the seed so different seeds give different sets
+pub struct Sobol;
+
+impl PointGenerator for UniformRandom {
+    fn name(&self) -> &str {
+	"uniform random"
+    }
+
+    fn generate(&self, width: f32, height: f32, count: usize, seed: u64) -> Vec<Vec2f> {
+	let mut rng = Rng::new(seed);
+	(0 .. count)
+	    .map(|_| Vec2f::new(rng.range_f32(0.0, width), rng.range_f32(0.0, height)))
+	    .collect()
+    }
+}
+
+impl PointGenerator for JitteredGrid {
+    fn name(&self) -> &str {
+	"jittered grid"
+    }
+
+    fn generate(&self, width: f32, height: f32, count: usize, seed: u64) -> Vec<Vec2f> {
+	let mut rng = Rng::new(seed);
+	let spacing = (width * height / count.max(1) as f32).sqrt();
+	let num_x = (width / spacing).round().max(1.0) as i32;
+	let num_y = (height / spacing).round().max(1.0) as i32;
+	let step_x = width / num_x as f32;
+	let step_y = height / num_y as f32;
+
+	let mut out_vec = Vec::<Vec2f>::new();
+	for y in 0 .. num_y {
+	    for x in 0 .. num_x {
+		let jx = rng.range_f32(-0.5, 0.5) * self.jitter;
+		let jy = rng.range_f32(-0.5, 0.5) * self.jitter;
+		out_vec.push(Vec2f::new((x as f32 + 0.5 + jx) * step_x,
+					(y as f32 + 0.5 + jy) * step_y));
+	    }
+	}
+	out_vec
+    }
+}
+
+impl PointGenerator for HexLattice {
+    fn name(&self) -> &str {
+	"hex lattice"
+    }
+
+    fn generate(&self, width: f32, height: f32, count: usize, _seed: u64) -> Vec<Vec2f> {
+	// each point takes sqrt(3)/2 * spacing^2 of the area
+	let spacing = (2.0 * width * height / (3.0_f32.sqrt() * count.max(1) as f32)).sqrt();
+	let row_height = spacing * 3.0_f32.sqrt() * 0.5;
+
+	let mut out_vec = Vec::<Vec2f>::new();
+	let mut row = 0;
+	let mut y = row_height * 0.5;
+	while y < height {
+	    let mut x = if row % 2 == 0 { spacing * 0.25 } else { spacing * 0.75 };
+	    while x < width {
+		out_vec.push(Vec2f::new(x, y));
+		x += spacing;
+	    }
+	    y += row_height;
+	    row += 1;
+	}
+	out_vec
+    }
+}
+
+// i'th term of the van der Corput sequence in the given base
+pub fn radical_inverse(mut i: u32, base: u32) -> f32 {
+    let mut out = 0.0;
+    let mut digit_scale = 1.0 / base as f32;
+    while i > 0 {
+	out += (i % base) as f32 * digit_scale;
+	i /= base;
+	digit_scale /= base as f32;
+    }
+    out
+}
+
+impl PointGenerator for Halton {
+    fn name(&self) -> &str {
+	"Halton"
+    }
+
+    fn generate(&self, width: f32, height: f32, count: usize, seed: u64) -> Vec<Vec2f> {
+	// skip a seeded run of the sequence so seeds differ
+	let skip = 1 + (seed % 4096) as u32;
+	(0 .. count as u32)
+	    .map(|i| Vec2f::new(radical_inverse(i + skip, 2) * width,
+				radical_inverse(i + skip, 3) * height))
+	    .collect()
+    }
+}
+
+impl PointGenerator for Sobol {
+    fn name(&self) -> &str {
+	"Sobol"
+    }
+
+    fn generate(&self, width: f32, height: f32, count: usize, seed: u64) -> Vec<Vec2f> {
+	// direction numbers: dimension one is plain binary, dimension two
+	// comes from the primitive polynomial x + 1, m_k = 2 m_(k-1) ^ m_(k-1)
+	let mut dir_x = [0u32; 32];
+	let mut dir_y = [0u32; 32];
+	let mut m: u32 = 1;
+	for k in 0 .. 32 {
+	    dir_x[k] = 1 << (31 - k);
+	    if k > 0 {
+		m = (m << 1) ^ m;
+	    }
+	    dir_y[k] = m << (31 - k);
+	}
+
+	let mut rng = Rng::new(seed);
+	let shift_x = rng.next_u64() as u32;
+	let shift_y = rng.next_u64() as u32;
+
+	let mut x: u32 = 0;
+	let mut y: u32 = 0;
+	let scale = 1.0 / 4294967296.0;
+
+	let mut out_vec = Vec::<Vec2f>::new();
+	for i in 0 .. count as u32 {
+	    out_vec.push(Vec2f::new(((x ^ shift_x) as f64 * scale) as f32 * width,
+				    ((y ^ shift_y) as f64 * scale) as f32 * height));
+
+	    // gray code order: flip the direction for i's lowest zero bit
+	    let c = (!i).trailing_zeros() as usize;
+	    x ^= dir_x[c];
+	    y ^= dir_y[c];
+	}
+	out_vec
+    }
+}
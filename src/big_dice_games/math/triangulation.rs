@@ -0,0 +1,410 @@
+// big_dice_games/math/triangulation.rs
+//
+// Delaunay triangulation by incremental insertion, with constraint edges forced
+// in by flipping (Sloan, "A fast algorithm for generating constrained
+// Delaunay triangulations", 1993) and the Delaunay property restored
+// around them by Lawson flips.
+//
+// Triangles are index triples wound so orient() is positive.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use crate::big_dice_games::math::vector::Vec2f;
+
+// flips allowed per constraint / per legalize pass before giving up on
+// degenerate input
+const MAX_FLIPS: usize = 100000;
+
+// twice the signed area of abc; positive one way round, negative the
+// other, 0 when they're in a line
+pub fn orient(a: Vec2f, b: Vec2f, c: Vec2f) -> f64 {
+    let abx = (b.x - a.x) as f64;
+    let aby = (b.y - a.y) as f64;
+    let acx = (c.x - a.x) as f64;
+    let acy = (c.y - a.y) as f64;
+    abx * acy - aby * acx
+}
+
+// is d strictly inside the circle through a, b, c? abc must have
+// positive orient()
+pub fn in_circumcircle(a: Vec2f, b: Vec2f, c: Vec2f, d: Vec2f) -> bool {
+    let adx = (a.x - d.x) as f64;
+    let ady = (a.y - d.y) as f64;
+    let bdx = (b.x - d.x) as f64;
+    let bdy = (b.y - d.y) as f64;
+    let cdx = (c.x - d.x) as f64;
+    let cdy = (c.y - d.y) as f64;
+
+    let det = (adx * adx + ady * ady) * (bdx * cdy - cdx * bdy)
+	- (bdx * bdx + bdy * bdy) * (adx * cdy - cdx * ady)
+	+ (cdx * cdx + cdy * cdy) * (adx * bdy - bdx * ady);
+
+    det > 0.0
+}
+
+// do segments ab and cd cross at a point inside both? touching at an
+// end or overlapping in a line doesn't count
+pub fn segments_cross(a: Vec2f, b: Vec2f, c: Vec2f, d: Vec2f) -> bool {
+    let o1 = orient(a, b, c);
+    let o2 = orient(a, b, d);
+    let o3 = orient(c, d, a);
+    let o4 = orient(c, d, b);
+
+    ((o1 > 0.0 && o2 < 0.0) || (o1 < 0.0 && o2 > 0.0)) &&
+	((o3 > 0.0 && o4 < 0.0) || (o3 < 0.0 && o4 > 0.0))
+}
+
+pub struct Triangulation
+{
+    pub points: Vec<Vec2f>,
+    pub triangles: Vec<[usize; 3]>,
+
+    // directed edge -> the triangle it winds round
+    edges: HashMap<(usize, usize), usize>,
+    // undirected, lower index first
+    constraints: HashSet<(usize, usize)>,
+}
+
+fn edge_key(a: usize, b: usize) -> (usize, usize) {
+    if a < b { (a, b) } else { (b, a) }
+}
+
+impl Triangulation {
+    // Delaunay triangulation of the points, which shouldn't repeat.
+    // Each point goes into the triangle holding it, which is split in
+    // three (or two either side of an edge it lands on), then edges are
+    // flipped outward until Delaunay again. Flipping only on strictly
+    // inside circles copes with cocircular points, such as the corners
+    // of regular polygons.
+    pub fn delaunay(points: &[Vec2f]) -> Triangulation {
+	let n = points.len();
+
+	let mut out = Triangulation {
+	    points: points.to_vec(),
+	    triangles: Vec::new(),
+	    edges: HashMap::new(),
+	    constraints: HashSet::new(),
+	};
+
+	if n < 3 {
+	    return out;
+	}
+
+	// a triangle round everything, taken away again at the end
+	let mut min = points[0];
+	let mut max = points[0];
+	for p in points {
+	    min = Vec2f::new(min.x.min(p.x), min.y.min(p.y));
+	    max = Vec2f::new(max.x.max(p.x), max.y.max(p.y));
+	}
+	let size = (max.x - min.x).max(max.y - min.y).max(1.0) * 2000.0;
+	let mid = Vec2f::new((min.x + max.x) * 0.5, (min.y + max.y) * 0.5);
+
+	out.points.push(Vec2f::new(mid.x - size, mid.y - size));
+	out.points.push(Vec2f::new(mid.x + size, mid.y - size));
+	out.points.push(Vec2f::new(mid.x, mid.y + size));
+
+	if orient(out.points[n], out.points[n + 1], out.points[n + 2]) > 0.0 {
+	    out.triangles.push([n, n + 1, n + 2]);
+	} else {
+	    out.triangles.push([n, n + 2, n + 1]);
+	}
+	out.rebuild_edges();
+
+	for i in 0 .. n {
+	    out.insert_point(i);
+	}
+
+	out.triangles.retain(|t| t.iter().all(|v| *v < n));
+	out.points.truncate(n);
+	out.rebuild_edges();
+	out
+    }
+
+    fn set_triangle(&mut self, t: usize, tri: [usize; 3]) {
+	if t == self.triangles.len() {
+	    self.triangles.push(tri);
+	} else {
+	    self.triangles[t] = tri;
+	}
+	for k in 0 .. 3 {
+	    self.edges.insert((tri[k], tri[(k + 1) % 3]), t);
+	}
+    }
+
+    // split whatever point i falls in, then restore Delaunay round it
+    fn insert_point(&mut self, i: usize) {
+	let p = self.points[i];
+
+	let mut found = None;
+	for (t, tri) in self.triangles.iter().enumerate() {
+	    let o = [orient(self.points[tri[0]], self.points[tri[1]], p),
+		     orient(self.points[tri[1]], self.points[tri[2]], p),
+		     orient(self.points[tri[2]], self.points[tri[0]], p)];
+	    if o.iter().all(|v| *v >= 0.0) {
+		found = Some((t, o));
+		break;
+	    }
+	}
+
+	let (t, o) = match found {
+	    None => { return; }
+	    Some(f) => f,
+	};
+
+	// edges that might not be Delaunay any more, wound with p on the left
+	let mut suspect = Vec::<(usize, usize)>::new();
+	let tri = self.triangles[t];
+
+	match (0 .. 3).find(|k| o[*k] == 0.0) {
+	    None => {
+		let [a, b, c] = tri;
+		self.set_triangle(t, [a, b, i]);
+		let t1 = self.triangles.len();
+		self.set_triangle(t1, [b, c, i]);
+		let t2 = self.triangles.len();
+		self.set_triangle(t2, [c, a, i]);
+		suspect.extend([(a, b), (b, c), (c, a)]);
+	    }
+	    Some(k) => {
+		// on edge a-b: split this triangle and the one across
+		let a = tri[k];
+		let b = tri[(k + 1) % 3];
+		let c = tri[(k + 2) % 3];
+		let across = self.edges.get(&(b, a)).cloned();
+
+		self.edges.remove(&(a, b));
+		self.set_triangle(t, [b, c, i]);
+		let t1 = self.triangles.len();
+		self.set_triangle(t1, [c, a, i]);
+		suspect.extend([(b, c), (c, a)]);
+
+		if let Some(t2) = across {
+		    let d = self.triangles[t2].iter().cloned().find(|v| *v != a && *v != b).unwrap();
+		    self.edges.remove(&(b, a));
+		    self.set_triangle(t2, [a, d, i]);
+		    let t3 = self.triangles.len();
+		    self.set_triangle(t3, [d, b, i]);
+		    suspect.extend([(a, d), (d, b)]);
+		}
+	    }
+	}
+
+	let mut flips = 0;
+	while let Some((x, y)) = suspect.pop() {
+	    flips += 1;
+	    if flips > MAX_FLIPS {
+		return;
+	    }
+
+	    let d = match self.edges.get(&(y, x)) {
+		None => continue,
+		Some(t2) => match self.triangles[*t2].iter().cloned().find(|v| *v != x && *v != y) {
+		    None => continue,
+		    Some(d) => d,
+		},
+	    };
+
+	    if in_circumcircle(self.points[x], self.points[y], p, self.points[d]) &&
+		self.flip(x, y).is_some() {
+		suspect.push((x, d));
+		suspect.push((d, y));
+	    }
+	}
+    }
+
+    fn rebuild_edges(&mut self) {
+	self.edges.clear();
+	for (i, t) in self.triangles.iter().enumerate() {
+	    for k in 0 .. 3 {
+		self.edges.insert((t[k], t[(k + 1) % 3]), i);
+	    }
+	}
+    }
+
+    pub fn has_edge(&self, a: usize, b: usize) -> bool {
+	self.edges.contains_key(&(a, b)) || self.edges.contains_key(&(b, a))
+    }
+
+    pub fn is_constraint(&self, a: usize, b: usize) -> bool {
+	self.constraints.contains(&edge_key(a, b))
+    }
+
+    // the triangle on the far side of triangle t's edge k (from vertex
+    // k to k + 1), -1 on the hull
+    pub fn neighbor(&self, t: usize, k: usize) -> i32 {
+	let tri = self.triangles[t];
+	match self.edges.get(&(tri[(k + 1) % 3], tri[k])) {
+	    None => -1,
+	    Some(n) => *n as i32,
+	}
+    }
+
+    pub fn centroid(&self, t: usize) -> Vec2f {
+	let tri = self.triangles[t];
+	let a = self.points[tri[0]];
+	let b = self.points[tri[1]];
+	let c = self.points[tri[2]];
+	Vec2f::new((a.x + b.x + c.x) / 3.0, (a.y + b.y + c.y) / 3.0)
+    }
+
+    // centre of the circle through t's corners; a Voronoi vertex
+    pub fn circumcenter(&self, t: usize) -> Vec2f {
+	let tri = self.triangles[t];
+	let a = self.points[tri[0]];
+	let b = self.points[tri[1]];
+	let c = self.points[tri[2]];
+
+	let bx = (b.x - a.x) as f64;
+	let by = (b.y - a.y) as f64;
+	let cx = (c.x - a.x) as f64;
+	let cy = (c.y - a.y) as f64;
+	let d = 2.0 * (bx * cy - by * cx);
+	if d == 0.0 {
+	    return self.centroid(t);
+	}
+
+	let b_sq = bx * bx + by * by;
+	let c_sq = cx * cx + cy * cy;
+	let ux = (cy * b_sq - by * c_sq) / d;
+	let uy = (bx * c_sq - cx * b_sq) / d;
+	Vec2f::new(a.x + ux as f32, a.y + uy as f32)
+    }
+
+    // every edge once, lower index first
+    pub fn undirected_edges(&self) -> Vec<(usize, usize)> {
+	self.edges.keys()
+	    .filter(|(a, b)| a < b || !self.edges.contains_key(&(*b, *a)))
+	    .map(|(a, b)| edge_key(*a, *b))
+	    .collect()
+    }
+
+    // Swap the diagonal shared by the triangles either side of a->b.
+    // Returns the new diagonal, or None if that isn't possible because
+    // the quad isn't strictly convex or ab is on the hull.
+    fn flip(&mut self, a: usize, b: usize) -> Option<(usize, usize)> {
+	let t1 = *self.edges.get(&(a, b))?;
+	let t2 = *self.edges.get(&(b, a))?;
+
+	let c = self.triangles[t1].iter().cloned().find(|v| *v != a && *v != b)?;
+	let d = self.triangles[t2].iter().cloned().find(|v| *v != a && *v != b)?;
+
+	let pa = self.points[a];
+	let pb = self.points[b];
+	let pc = self.points[c];
+	let pd = self.points[d];
+	if orient(pc, pa, pd) <= 0.0 || orient(pd, pb, pc) <= 0.0 {
+	    return None;
+	}
+
+	self.edges.remove(&(a, b));
+	self.edges.remove(&(b, a));
+
+	self.triangles[t1] = [c, a, d];
+	self.triangles[t2] = [d, b, c];
+
+	self.edges.insert((c, a), t1);
+	self.edges.insert((a, d), t1);
+	self.edges.insert((d, c), t1);
+	self.edges.insert((d, b), t2);
+	self.edges.insert((b, c), t2);
+	self.edges.insert((c, d), t2);
+
+	Some((c, d))
+    }
+
+    // Force the edge a-b in by flipping away everything across it.
+    // False if it couldn't be done, say because another constraint or
+    // a vertex is in the way.
+    pub fn insert_constraint(&mut self, a: usize, b: usize) -> bool {
+	if a == b {
+	    return false;
+	}
+
+	let pa = self.points[a];
+	let pb = self.points[b];
+
+	let mut crossing: VecDeque<(usize, usize)> = self.undirected_edges().into_iter()
+	    .filter(|(u, v)| segments_cross(pa, pb, self.points[*u], self.points[*v]))
+	    .collect();
+
+	if crossing.iter().any(|(u, v)| self.is_constraint(*u, *v)) {
+	    return false;
+	}
+
+	let mut flips = 0;
+	while let Some((u, v)) = crossing.pop_front() {
+	    flips += 1;
+	    if flips > MAX_FLIPS {
+		return false;
+	    }
+
+	    match self.flip(u, v) {
+		None => {
+		    // not convex yet, come back to it once others have moved
+		    crossing.push_back((u, v));
+		}
+		Some((c, d)) => {
+		    if segments_cross(pa, pb, self.points[c], self.points[d]) {
+			crossing.push_back((c, d));
+		    }
+		}
+	    }
+	}
+
+	if !self.has_edge(a, b) {
+	    return false;
+	}
+
+	self.constraints.insert(edge_key(a, b));
+	true
+    }
+
+    // Lawson flips until every edge but the constraints is locally
+    // Delaunay
+    pub fn legalize(&mut self) {
+	for _ in 0 .. MAX_FLIPS {
+	    let mut flipped = false;
+
+	    for (a, b) in self.undirected_edges() {
+		if self.is_constraint(a, b) {
+		    continue;
+		}
+
+		let t1 = match self.edges.get(&(a, b)) { None => continue, Some(t) => *t };
+		let t2 = match self.edges.get(&(b, a)) { None => continue, Some(t) => *t };
+
+		let tri = self.triangles[t1];
+		let d = match self.triangles[t2].iter().cloned().find(|v| *v != a && *v != b) {
+		    None => continue,
+		    Some(d) => d,
+		};
+
+		if in_circumcircle(self.points[tri[0]], self.points[tri[1]],
+				   self.points[tri[2]], self.points[d]) &&
+		    self.flip(a, b).is_some() {
+		    flipped = true;
+		}
+	    }
+
+	    if !flipped {
+		return;
+	    }
+	}
+    }
+
+    // drop the triangles keep() says no to
+    pub fn retain_triangles<F>(&mut self, keep: F)
+    where F: Fn(&Triangulation, usize) -> bool
+    {
+	let kept: Vec<[usize; 3]> = (0 .. self.triangles.len())
+	    .filter(|t| keep(self, *t))
+	    .map(|t| self.triangles[t])
+	    .collect();
+
+	self.triangles = kept;
+	self.rebuild_edges();
+    }
+}
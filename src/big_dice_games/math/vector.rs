@@ -7,6 +7,11 @@ pub trait Vector {
     fn mag(&self) -> f32;
 
     fn scale(&self, factor: f32) -> Self;
+
+    fn mag_sq(&self) -> f32;
+
+    // unit length, or unchanged if zero
+    fn normalized(&self) -> Self;
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -23,6 +28,24 @@ impl Vec2f {
 	    y: y
 	}
     }
+
+    pub fn zero() -> Vec2f {
+	Vec2f::new(0.0, 0.0)
+    }
+
+    pub fn dot(&self, other: &Vec2f) -> f32 {
+	self.x * other.x + self.y * other.y
+    }
+
+    // z of the 3d cross product, > 0 when other is counterclockwise
+    pub fn cross(&self, other: &Vec2f) -> f32 {
+	self.x * other.y - self.y * other.x
+    }
+
+    // rotated 90 degrees counterclockwise
+    pub fn perp(&self) -> Vec2f {
+	Vec2f::new(-self.y, self.x)
+    }
 }
 
 impl Add for Vec2f {
@@ -58,6 +81,18 @@ impl Vector for Vec2f {
 	    y: self.y * factor
 	}
     }
+
+    fn mag_sq(&self) -> f32 {
+	self.x * self.x + self.y * self.y
+    }
+
+    fn normalized(&self) -> Self {
+	let m = self.mag();
+	if m <= 0.0 {
+	    return *self;
+	}
+	self.scale(1.0 / m)
+    }
 }
 
     
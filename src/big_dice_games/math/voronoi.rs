@@ -0,0 +1,103 @@
+// big_dice_games/math/voronoi.rs
+//
+// Helpers over voronoice shared by the Bridson screens: building the
+// diagram clipped to the screen, cell areas and centroids, and Lloyd
+// relaxation. A Lloyd step moves every site to the centroid of its
+// cell; repeated, the sites settle towards a centroidal Voronoi
+// tessellation, where the cells are about as even as they can be.
+
+use voronoice::*;
+
+use crate::big_dice_games::math::vector::Vec2f;
+
+// the Voronoi diagram of the points, clipped to 0,0 - width,height
+pub fn build_voronoi(points: &[Vec2f], width: f32, height: f32) -> Option<Voronoi> {
+    let sites: Vec<Point> = points.iter()
+	.map(|p| Point { x: p.x as f64, y: p.y as f64 })
+	.collect();
+
+    let half_w = (width / 2.0) as f64;
+    let half_h = (height / 2.0) as f64;
+
+    let bounding_box = BoundingBox::new(
+	Point{
+	    x: half_w,
+	    y: half_h,
+	},
+	half_w * 2.0,
+	half_h * 2.0,
+    );
+
+    VoronoiBuilder::default()
+	.set_sites(sites)
+	.set_bounding_box(bounding_box)
+	.set_lloyd_relaxation_iterations(0)
+	.build()
+}
+
+// area and centroid of each cell, by the shoelace formula; a cell
+// clipped away to nothing has no area and stays on its site
+pub fn cell_areas_and_centroids(voronoi: &Voronoi) -> Vec<(f32, Vec2f)> {
+    let verts = voronoi.vertices();
+    let sites = voronoi.sites();
+
+    voronoi.cells().iter()
+	.enumerate()
+	.map(|(site_index, cell)| {
+	    if cell.is_empty() {
+		let site = &sites[site_index];
+		return (0.0, Vec2f::new(site.x as f32, site.y as f32));
+	    }
+
+	    let mut area = 0.0;
+	    let mut cx = 0.0;
+	    let mut cy = 0.0;
+
+	    for i in 0 .. cell.len() {
+		let a = &verts[cell[i]];
+		let b = &verts[cell[(i + 1) % cell.len()]];
+		let cross = a.x * b.y - b.x * a.y;
+		area += cross;
+		cx += (a.x + b.x) * cross;
+		cy += (a.y + b.y) * cross;
+	    }
+
+	    area *= 0.5;
+	    if area.abs() < 1e-9 {
+		return (0.0, Vec2f::new(verts[cell[0]].x as f32, verts[cell[0]].y as f32));
+	    }
+
+	    (area.abs() as f32,
+	     Vec2f::new((cx / (6.0 * area)) as f32, (cy / (6.0 * area)) as f32))
+	})
+	.collect()
+}
+
+pub fn cell_area_variance(voronoi: &Voronoi) -> f32 {
+    let areas: Vec<f32> = cell_areas_and_centroids(voronoi).iter().map(|(a, _)| *a).collect();
+    if areas.is_empty() {
+	return 0.0;
+    }
+
+    let mean = areas.iter().sum::<f32>() / areas.len() as f32;
+    areas.iter().map(|a| (a - mean) * (a - mean)).sum::<f32>() / areas.len() as f32
+}
+
+// One Lloyd step. Each point moves to its cell's centroid if accept()
+// allows it, otherwise it stays put; returns the new points and how
+// many moved. Cells are clipped to the box, so centroids stay in it.
+pub fn lloyd_step<F>(voronoi: &Voronoi, points: &[Vec2f], accept: F) -> (Vec<Vec2f>, usize)
+where F: Fn(usize, Vec2f) -> bool
+{
+    let mut moved = 0;
+    let mut out_vec = points.to_vec();
+
+    for (i, (_, centroid)) in cell_areas_and_centroids(voronoi).into_iter().enumerate() {
+	if i < out_vec.len() && accept(i, centroid) {
+	    out_vec[i] = centroid;
+	    moved += 1;
+	}
+    }
+
+    (out_vec, moved)
+}
@@ -1,5 +1,7 @@
 // big_dice_games/util/mod.rs
 
+pub mod rng;
+
 use std::ops::Mul;
 
 pub trait ScaleByFloat {
@@ -0,0 +1,56 @@
+// big_dice_games/util/rng.rs
+//
+// A small seeded random source (xorshift64*) for the library code, so
+// generated content can be repeated from its seed, and doesn't depend
+// on macroquad's global generator.
+
+pub struct Rng
+{
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+	// xorshift sticks at zero, and nearby seeds should still differ
+	let mut state = seed.wrapping_mul(0x9E3779B97F4A7C15) ^ 0xD1B54A32D192ED03;
+	if state == 0 {
+	    state = 1;
+	}
+	Rng {
+	    state: state,
+	}
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+	self.state ^= self.state >> 12;
+	self.state ^= self.state << 25;
+	self.state ^= self.state >> 27;
+	self.state.wrapping_mul(0x2545F4914F6CDD1D)
+    }
+
+    // 0 .. 1
+    pub fn next_f32(&mut self) -> f32 {
+	(self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    // lo .. hi
+    pub fn range_f32(&mut self, lo: f32, hi: f32) -> f32 {
+	lo + (hi - lo) * self.next_f32()
+    }
+
+    // lo .. hi, hi excluded
+    pub fn range_i32(&mut self, lo: i32, hi: i32) -> i32 {
+	if hi <= lo {
+	    return lo;
+	}
+	lo + (self.next_u64() % (hi - lo) as u64) as i32
+    }
+
+    // Fisher-Yates
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+	for i in (1 .. items.len()).rev() {
+	    let j = self.range_i32(0, i as i32 + 1) as usize;
+	    items.swap(i, j);
+	}
+    }
+}
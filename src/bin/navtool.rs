@@ -0,0 +1,220 @@
+// bin/navtool.rs
+//
+// Headless tools over the AI library, for making and checking maps
+// from scripts without opening a window.
+//
+//   cargo run --bin navtool -- maze <kind> [seed] [width] [height]
+//   cargo run --release --bin navtool -- bench <kind> [seed] [queries] [width] [height]
+
+use navexp::big_dice_games::ai::maze::generate_maze;
+use navexp::big_dice_games::ai::maze::maze_to_string;
+use navexp::big_dice_games::ai::maze::MazeKind;
+use navexp::big_dice_games::ai::maze::MAZE_KINDS;
+use navexp::big_dice_games::ai::astar::a_star;
+use navexp::big_dice_games::ai::contraction::ContractionHierarchy;
+use navexp::big_dice_games::ai::graph::NavGraph;
+use navexp::big_dice_games::math::vector::Vec2f;
+use navexp::big_dice_games::math::vector::Vector;
+use navexp::big_dice_games::util::rng::Rng;
+
+use std::collections::HashSet;
+use std::time::Duration;
+use std::time::Instant;
+
+// the square nav screen's grid
+const DEFAULT_WIDTH: i32 = 55;
+const DEFAULT_HEIGHT: i32 = 43;
+const DEFAULT_QUERIES: usize = 1000;
+// a border and one space inside it
+const MIN_SIZE: i32 = 3;
+
+// an 8 way grid of unit spaces, as the square nav screen moves
+struct Grid
+{
+    num_x: i32,
+    num_y: i32,
+    walls: HashSet<i32>,
+}
+
+impl NavGraph for Grid {
+    fn node_count(&self) -> usize {
+	(self.num_x * self.num_y) as usize
+    }
+
+    fn node_point(&self, i: i32) -> Vec2f {
+	Vec2f::new((i % self.num_x) as f32, (i / self.num_x) as f32)
+    }
+
+    fn neighbors(&self, i: i32) -> Vec<(i32, f32)> {
+	let x = i % self.num_x;
+	let y = i / self.num_x;
+	let mut out = Vec::new();
+
+	for dy in -1 ..= 1 {
+	    for dx in -1 ..= 1 {
+		let nx = x + dx;
+		let ny = y + dy;
+		if (dx == 0 && dy == 0) || nx < 0 || ny < 0 || nx >= self.num_x || ny >= self.num_y {
+		    continue;
+		}
+
+		let n = nx + ny * self.num_x;
+		if !self.walls.contains(&n) {
+		    out.push((n, ((dx * dx + dy * dy) as f32).sqrt()));
+		}
+	    }
+	}
+	out
+    }
+
+    fn is_open(&self, i: i32) -> bool {
+	!self.walls.contains(&i)
+    }
+}
+
+fn usage() {
+    let kinds: Vec<&str> = MAZE_KINDS.iter().map(|k| k.name()).collect();
+    println!("usage: navtool maze <kind> [seed] [width] [height]");
+    println!("       navtool bench <kind> [seed] [queries] [width] [height]");
+    println!("  kinds: {}", kinds.join(", "));
+}
+
+fn arg_or<T: std::str::FromStr>(args: &[String], i: usize, default: T) -> T {
+    match args.get(i) {
+	None => default,
+	Some(a) => a.parse::<T>().unwrap_or(default),
+    }
+}
+
+fn kind_arg(args: &[String]) -> Option<MazeKind> {
+    args.get(2).and_then(|name| MAZE_KINDS.iter().cloned().find(|k| k.name() == name))
+}
+
+// width and height from args[i] and args[i + 1], None if either is too small
+fn size_args(args: &[String], i: usize) -> Option<(i32, i32)> {
+    let width = arg_or(args, i, DEFAULT_WIDTH);
+    let height = arg_or(args, i + 1, DEFAULT_HEIGHT);
+
+    if width < MIN_SIZE || height < MIN_SIZE {
+	println!("width and height must be at least {}", MIN_SIZE);
+	return None;
+    }
+    Some((width, height))
+}
+
+fn maze(args: &[String]) {
+    let kind = match kind_arg(args) {
+	None => {
+	    usage();
+	    return;
+	}
+	Some(k) => k,
+    };
+
+    let seed = arg_or(args, 3, 0u64);
+    let (width, height) = match size_args(args, 4) {
+	None => {
+	    usage();
+	    return;
+	}
+	Some(size) => size,
+    };
+
+    let walls = generate_maze(kind, width, height, seed);
+    print!("{}", maze_to_string(&walls, width, height));
+    println!("{} seed {}: {}x{}, {} walls, {} open",
+	     kind.name(), seed, width, height, walls.len(), width * height - walls.len() as i32);
+}
+
+fn millis(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+// contraction hierarchy against plain A* on the same random queries
+fn bench(args: &[String]) {
+    let kind = match kind_arg(args) {
+	None => {
+	    usage();
+	    return;
+	}
+	Some(k) => k,
+    };
+
+    let seed = arg_or(args, 3, 0u64);
+    let queries = arg_or(args, 4, DEFAULT_QUERIES);
+    let (width, height) = match size_args(args, 5) {
+	None => {
+	    usage();
+	    return;
+	}
+	Some(size) => size,
+    };
+
+    let grid = Grid {
+	num_x: width,
+	num_y: height,
+	walls: generate_maze(kind, width, height, seed),
+    };
+    let open: Vec<i32> = (0 .. width * height).filter(|i| grid.is_open(*i)).collect();
+    if open.is_empty() {
+	println!("{} seed {}: {}x{}, no open spaces", kind.name(), seed, width, height);
+	return;
+    }
+
+    let timer = Instant::now();
+    let ch = ContractionHierarchy::new(&grid);
+    let build_time = timer.elapsed();
+
+    println!("{} seed {}: {}x{}, {} open spaces", kind.name(), seed, width, height, open.len());
+    println!("contraction: {:.1} ms, {} edges, {} shortcuts",
+	     millis(build_time), ch.edge_count(), ch.shortcut_count());
+
+    let h = |a: i32, b: i32| (grid.node_point(a) - grid.node_point(b)).mag();
+    let mut rng = Rng::new(seed);
+    let mut a_star_time = Duration::ZERO;
+    let mut ch_time = Duration::ZERO;
+    let mut a_star_expanded = 0;
+    let mut ch_settled = 0;
+    let mut mismatches = 0;
+
+    for _ in 0 .. queries {
+	let start = open[rng.range_i32(0, open.len() as i32) as usize];
+	let goal = open[rng.range_i32(0, open.len() as i32) as usize];
+
+	let timer = Instant::now();
+	let plain = a_star(&grid, start, goal, &h);
+	a_star_time += timer.elapsed();
+
+	let timer = Instant::now();
+	let fast = ch.find_path(start, goal);
+	ch_time += timer.elapsed();
+
+	a_star_expanded += plain.expanded.len();
+	ch_settled += fast.settled.len();
+
+	if plain.found() != fast.found() || (plain.found() && (plain.cost - fast.cost).abs() > 0.01) {
+	    mismatches += 1;
+	}
+    }
+
+    let n = queries.max(1) as f64;
+    println!("{} queries, {} cost mismatches", queries, mismatches);
+    println!("  A*:          {:8.3} ms/query, {:8.1} expanded", millis(a_star_time) / n, a_star_expanded as f64 / n);
+    println!("  contraction: {:8.3} ms/query, {:8.1} settled", millis(ch_time) / n, ch_settled as f64 / n);
+
+    // queries before the preprocessing has paid for itself
+    let saved = millis(a_star_time) / n - millis(ch_time) / n;
+    if saved > 0.0 {
+	println!("  break even after {:.0} queries", millis(build_time) / saved);
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().collect();
+
+    match args.get(1).map(|a| a.as_str()) {
+	Some("maze") => maze(&args),
+	Some("bench") => bench(&args),
+	_ => usage(),
+    }
+}
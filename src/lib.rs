@@ -0,0 +1,6 @@
+// lib.rs
+//
+// The AI, math and util library, shared by the game and the headless
+// navtool.
+
+pub mod big_dice_games;
@@ -2,11 +2,13 @@ use macroquad::prelude::*;
 
 mod screens;
 mod demos;
-mod big_dice_games;
 mod sprite;
+mod scene;
 
 mod texture_mgr;
 
+use navexp::big_dice_games;
+
 //use big_dice_games::util::map;
 use screens::Screen;
 
@@ -19,6 +21,10 @@ pub enum GameScreen {
     BridsonNav,
     SquareNav,
     HouseNav,
+    Orca,
+    NavMesh,
+    Visibility,
+    HexNav,
 }
 
 fn window_conf() -> Conf {
@@ -50,6 +56,10 @@ async fn main() {
     let mut bridson_nav_screen = screens::bridson_nav::BridsonNavScreen::new();
     let mut square_nav_screen = screens::square_nav::SquareNavScreen::new();
     let mut house_nav_demo = screens::house_nav_demo::HouseNavDemo::new();
+    let mut orca_demo_screen = screens::orca_demo::OrcaDemoScreen::new();
+    let mut navmesh_screen = screens::navmesh_nav::NavMeshScreen::new();
+    let mut visibility_screen = screens::visibility_nav::VisibilityScreen::new();
+    let mut hex_nav_screen = screens::hex_nav::HexNavScreen::new();
 
     let mut game_mode = GameScreen::BdgLogo;
 
@@ -68,6 +78,10 @@ async fn main() {
 	    GameScreen::BridsonNav => &mut bridson_nav_screen,
 	    GameScreen::SquareNav => &mut square_nav_screen,
 	    GameScreen::HouseNav => &mut house_nav_demo,
+	    GameScreen::Orca => &mut orca_demo_screen,
+	    GameScreen::NavMesh => &mut navmesh_screen,
+	    GameScreen::Visibility => &mut visibility_screen,
+	    GameScreen::HexNav => &mut hex_nav_screen,
 	};
 
 	if !(scr.is_loaded()) {
@@ -110,6 +124,22 @@ async fn main() {
 		GameScreen::HouseNav => {
 		    game_mode = GameScreen::Menu;
 		}
+		GameScreen::Orca => {
+		    orca_demo_screen.reset();
+		    game_mode = GameScreen::Menu;
+		}
+		GameScreen::NavMesh => {
+		    navmesh_screen.reset();
+		    game_mode = GameScreen::Menu;
+		}
+		GameScreen::Visibility => {
+		    visibility_screen.reset();
+		    game_mode = GameScreen::Menu;
+		}
+		GameScreen::HexNav => {
+		    hex_nav_screen.reset();
+		    game_mode = GameScreen::Menu;
+		}
 	    }
 	} else {
 	    scr.render(&texture_mgr);
@@ -0,0 +1,254 @@
+// scene.rs
+//
+// Scenes are JSON files listing the sprites to place, what they collide
+// as, where the robot starts and the region it should reach. See
+// Scenes/house.json for a worked example.
+
+use macroquad::prelude::*;
+use macroquad::file::load_string;
+
+use serde::Deserialize;
+
+use crate::big_dice_games::ai::cspace::circle_polygon;
+use crate::big_dice_games::ai::cspace::convex_contains;
+use crate::big_dice_games::ai::cspace::inflate_polygon;
+use crate::big_dice_games::ai::cspace::Footprint;
+use crate::big_dice_games::math::vector::Vec2f;
+
+use crate::sprite::Sprite;
+use crate::texture_mgr::TextureMgr;
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(tag = "shape", rename_all = "lowercase")]
+pub enum CollisionDesc {
+    // the sprite's oriented bounds
+    Box,
+    // centered on the pivot
+    Circle { radius: f32 },
+}
+
+#[derive(Deserialize)]
+pub struct SpriteDesc {
+    pub texture: String,
+
+    // unrotated top left corner, as with draw_texture
+    pub x: f32,
+    pub y: f32,
+
+    // degrees, about the pivot
+    #[serde(default)]
+    pub rotation: f32,
+    #[serde(default = "default_pivot")]
+    pub pivot: [f32; 2],
+    #[serde(default = "default_scale")]
+    pub scale: [f32; 2],
+    #[serde(default)]
+    pub z: i32,
+
+    #[serde(default)]
+    pub collision: Option<CollisionDesc>,
+
+    // tile this many copies across and down, one sprite size apart
+    #[serde(default = "default_repeat")]
+    pub repeat: [u32; 2],
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub struct Pose {
+    pub x: f32,
+    pub y: f32,
+    // degrees
+    #[serde(default)]
+    pub rotation: f32,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub struct Region {
+    pub x: f32,
+    pub y: f32,
+    pub w: f32,
+    pub h: f32,
+}
+
+#[derive(Deserialize)]
+pub struct SceneDesc {
+    pub name: String,
+    pub sprites: Vec<SpriteDesc>,
+
+    pub robot_texture: String,
+    pub start: Pose,
+    pub goal: Region,
+}
+
+fn default_pivot() -> [f32; 2] {
+    [0.5, 0.5]
+}
+
+fn default_scale() -> [f32; 2] {
+    [1.0, 1.0]
+}
+
+fn default_repeat() -> [u32; 2] {
+    [1, 1]
+}
+
+// collision geometry in world space
+pub enum Collider {
+    // corners in order
+    Polygon(Vec<Vec2>),
+    Circle(Vec2, f32),
+}
+
+impl Collider {
+    pub fn draw(&self, color: Color) {
+	match self {
+	    Collider::Circle(center, radius) => {
+		draw_circle_lines(center.x, center.y, *radius, 2.0, color);
+	    }
+	    Collider::Polygon(corners) => {
+		for i in 0 .. corners.len() {
+		    let a = corners[i];
+		    let b = corners[(i + 1) % corners.len()];
+		    draw_line(a.x, a.y, b.x, b.y, 2.0, color);
+		}
+	    }
+	}
+    }
+}
+
+pub struct Scene {
+    pub name: String,
+    pub sprites: Vec<Sprite>,
+    // which sprites collide, and as what
+    pub collision: Vec<(usize, CollisionDesc)>,
+
+    pub robot: Sprite,
+    pub start: Pose,
+    pub goal: Rect,
+}
+
+impl Scene {
+    pub fn empty() -> Scene {
+	Scene {
+	    name: String::new(),
+	    sprites: Vec::new(),
+	    collision: Vec::new(),
+	    robot: Sprite::new(Texture2D::empty()),
+	    start: Pose { x: 0.0, y: 0.0, rotation: 0.0 },
+	    goal: Rect::new(0.0, 0.0, 0.0, 0.0),
+	}
+    }
+
+    // put the robot back on its start pose
+    pub fn reset_robot(&mut self) {
+	self.robot.x = self.start.x;
+	self.robot.y = self.start.y;
+	self.robot.theta = self.start.rotation.to_radians();
+    }
+
+    // world space shapes, following the sprites as they move
+    pub fn colliders(&self) -> Vec<Collider> {
+	self.collision.iter()
+	    .map(|(i, desc)| {
+		let s = &self.sprites[*i];
+		match desc {
+		    CollisionDesc::Box => Collider::Polygon(s.corners().to_vec()),
+		    CollisionDesc::Circle { radius } => Collider::Circle(vec2(s.x, s.y), *radius),
+		}
+	    })
+	    .collect()
+    }
+
+    // the robot's outline around its pivot, at its current heading
+    pub fn robot_footprint(&self) -> Footprint {
+	let corners = self.robot.corners();
+	Footprint::Polygon(corners.iter()
+			   .map(|c| Vec2f::new(c.x - self.robot.x, c.y - self.robot.y))
+			   .collect())
+    }
+
+    // every collider grown by the footprint; the robot's pivot must
+    // stay outside all of them
+    pub fn cspace_obstacles(&self, footprint: &Footprint) -> Vec<Vec<Vec2f>> {
+	self.colliders().iter()
+	    .map(|c| {
+		let outline = match c {
+		    Collider::Polygon(corners) => corners.iter()
+			.map(|p| Vec2f::new(p.x, p.y))
+			.collect(),
+		    Collider::Circle(center, radius) =>
+			circle_polygon(Vec2f::new(center.x, center.y), *radius, 16),
+		};
+		inflate_polygon(&outline, footprint)
+	    })
+	    .collect()
+    }
+
+    // could the robot, as it's turned now, stand with its pivot at p?
+    pub fn robot_fits(&self, p: Vec2) -> bool {
+	let p = Vec2f::new(p.x, p.y);
+	!self.cspace_obstacles(&self.robot_footprint()).iter()
+	    .any(|obstacle| convex_contains(obstacle, p))
+    }
+
+    pub fn robot_in_goal(&self) -> bool {
+	self.goal.contains(vec2(self.robot.x, self.robot.y))
+    }
+}
+
+// read a scene file, load every texture it names and lay it out
+pub async fn load_scene(path: &str, tex_mgr: &mut TextureMgr) -> Result<Scene, String> {
+    let text = load_string(path).await
+	.map_err(|e| format!("can't read {}: {:?}", path, e))?;
+
+    let desc: SceneDesc = serde_json::from_str(&text)
+	.map_err(|e| format!("bad scene {}: {}", path, e))?;
+
+    let mut texture_filenames = Vec::<String>::new();
+    for s in &desc.sprites {
+	if !texture_filenames.contains(&s.texture) {
+	    texture_filenames.push(s.texture.clone());
+	}
+    }
+    if !texture_filenames.contains(&desc.robot_texture) {
+	texture_filenames.push(desc.robot_texture.clone());
+    }
+
+    tex_mgr.try_load(texture_filenames).await?;
+
+    let mut scene = Scene::empty();
+    scene.name = desc.name;
+
+    for s in &desc.sprites {
+	let texture = *tex_mgr.get_texture(&s.texture);
+
+	for across in 0 .. s.repeat[0] {
+	    for down in 0 .. s.repeat[1] {
+		let mut sprite = Sprite::new(texture);
+		sprite.pivot_x = s.pivot[0];
+		sprite.pivot_y = s.pivot[1];
+		sprite.scale_x = s.scale[0];
+		sprite.scale_y = s.scale[1];
+		sprite.z = s.z;
+
+		sprite.set_top_left(s.x + across as f32 * sprite.scaled_width().abs(),
+				    s.y + down as f32 * sprite.scaled_height().abs());
+		sprite.theta = s.rotation.to_radians();
+
+		if let Some(c) = s.collision {
+		    scene.collision.push((scene.sprites.len(), c));
+		}
+		scene.sprites.push(sprite);
+	    }
+	}
+    }
+
+    scene.robot = Sprite::new(*tex_mgr.get_texture(&desc.robot_texture));
+    // above anything in the scene
+    scene.robot.z = scene.sprites.iter().map(|s| s.z).max().unwrap_or(0) + 1;
+    scene.start = desc.start;
+    scene.goal = Rect::new(desc.goal.x, desc.goal.y, desc.goal.w, desc.goal.h);
+    scene.reset_robot();
+
+    Ok(scene)
+}
@@ -0,0 +1,77 @@
+// screens/bidir_draw.rs
+
+use macroquad::prelude::*;
+
+use crate::big_dice_games::ai::graph::NavGraph;
+use crate::big_dice_games::ai::astar::SearchResult;
+use crate::big_dice_games::ai::bidirectional::BidirectionalResult;
+use crate::big_dice_games::ai::bidirectional::SearchDirection;
+
+// The first reveal expansions of a bidirectional search, each side in
+// its own colour with its search tree, then the path and meeting node
+// once everything is revealed.
+pub fn draw_bidirectional(graph: &dyn NavGraph, result: &BidirectionalResult,
+			  reveal: usize, dot_radius: f32, path_color: Color) {
+    for (idx, dir) in result.expanded.iter().take(reveal) {
+	let p = graph.node_point(*idx);
+	let (c, prev) = match dir {
+	    SearchDirection::Forward => (ORANGE, &result.forward_prev),
+	    SearchDirection::Backward => (SKYBLUE, &result.backward_prev),
+	};
+	draw_circle(p.x, p.y, dot_radius, c);
+
+	// each side's search tree, back to where the node was reached from
+	if let Some(from) = prev.get(idx).filter(|i| **i >= 0) {
+	    let q = graph.node_point(*from);
+	    draw_line(p.x, p.y, q.x, q.y, 1.0, c);
+	}
+    }
+
+    if reveal < result.expanded.len() {
+	return;
+    }
+
+    for i in 1 .. result.path.len() {
+	let a = graph.node_point(result.path[i - 1]);
+	let b = graph.node_point(result.path[i]);
+	draw_line(a.x, a.y, b.x, b.y, 3.0, path_color);
+    }
+
+    if result.meeting_index >= 0 {
+	let m = graph.node_point(result.meeting_index);
+	draw_circle(m.x, m.y, dot_radius + 1.0, MAGENTA);
+    }
+}
+
+// One line for the bidirectional search, then one per unidirectional
+// baseline with how many expansions the bidirectional search saved.
+pub fn bidirectional_labels(label: &str, result: &BidirectionalResult,
+			    baselines: &[(&str, &SearchResult)]) -> Vec<String> {
+    let bidir_count = result.expanded.len();
+    let outcome = if result.found() {
+	format!("cost {:.1}", result.cost)
+    } else {
+	"no path".to_string()
+    };
+
+    let mut lines = vec![format!("{}: {} expanded ({} forward, {} backward) {}",
+				 label,
+				 bidir_count,
+				 result.expanded_count(SearchDirection::Forward),
+				 result.expanded_count(SearchDirection::Backward),
+				 outcome)];
+
+    for (name, baseline) in baselines {
+	let count = baseline.expanded.len();
+	let saved = count as i32 - bidir_count as i32;
+	let percent = if count > 0 {
+	    100.0 * saved as f32 / count as f32
+	} else {
+	    0.0
+	};
+	lines.push(format!("{}: {} expanded cost {:.1}, saved {} ({:.0}%)",
+			   name, count, baseline.cost, saved, percent));
+    }
+
+    lines
+}
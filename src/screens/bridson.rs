@@ -15,6 +15,9 @@ use crate::texture_mgr::TextureMgr;
 use crate::big_dice_games::math::vector::Vec2f;
 use crate::big_dice_games::util::map;
 use crate::big_dice_games::math::vector::Vector;
+use crate::big_dice_games::math::voronoi::build_voronoi;
+use crate::big_dice_games::math::voronoi::cell_area_variance;
+use crate::big_dice_games::math::voronoi::lloyd_step;
 
 use voronoice::*;
 
@@ -44,6 +47,12 @@ pub struct BridsonScreen
 
     sub_mode: SubMode,
     voronoi_data: Option<Voronoi>,
+
+    // Lloyd steps run on the finished points, and the cell area
+    // variance before any and now
+    lloyd_steps: i32,
+    initial_variance: f32,
+    area_variance: f32,
 }
 
 impl BridsonScreen {
@@ -93,6 +102,9 @@ impl BridsonScreen {
 	    cell_width: cell_width,
 	    sub_mode: SubMode::AddPoints,
 	    voronoi_data: Option::<Voronoi>::None,
+	    lloyd_steps: 0,
+	    initial_variance: 0.0,
+	    area_variance: 0.0,
 	}
     }
 
@@ -104,6 +116,7 @@ impl BridsonScreen {
 	self.is_complete_flag = false;
 	self.sub_mode = SubMode::AddPoints;
 	self.voronoi_data = Option::<Voronoi>::None;	
+	self.lloyd_steps = 0;
     }
 
     fn point_in_box(&self, p: &Vec2f) -> bool {
@@ -116,33 +129,29 @@ impl BridsonScreen {
     fn make_voronoi(&mut self) {
 	println!("making voronoi");
 
-	let mut sites:Vec<Point> = Vec::new();
-	for p in &self.points {
-	    let site_point = Point {
-		x: p.x as f64,
-		y: p.y as f64,
-	    };
-	    
-	    sites.push(site_point);
+	self.voronoi_data = build_voronoi(&self.points, screen_width(), screen_height());
+
+	if let Some(vd) = &self.voronoi_data {
+	    self.area_variance = cell_area_variance(vd);
+	    if self.lloyd_steps == 0 {
+		self.initial_variance = self.area_variance;
+	    }
 	}
+    }
 
-	let scr_w = (screen_width() / 2.0) as f64;
-	let scr_h = (screen_height() / 2.0) as f64;
-
-	let bounding_box = BoundingBox::new(
-	    Point{
-		x: scr_w,
-		y: scr_h,
-	    },
-	    scr_w * 2.0,
-	    scr_h * 2.0,
-	);
-	let my_voronoi = VoronoiBuilder::default()
-	    .set_sites(sites)
-	    .set_bounding_box(bounding_box)
-	    .set_lloyd_relaxation_iterations(0)
-	    .build();
-	self.voronoi_data = my_voronoi;
+    fn relax(&mut self) {
+	let vd = match &self.voronoi_data {
+	    None => { return; }
+	    Some(vd) => vd,
+	};
+
+	let (points, _) = lloyd_step(vd, &self.points, |_, _| true);
+	self.points = points;
+	self.lloyd_steps += 1;
+	self.make_voronoi();
+
+	println!("lloyd step {}: cell area variance {:.1} -> {:.1}",
+		 self.lloyd_steps, self.initial_variance, self.area_variance);
     }
 
     fn cell_index(&self, p: &Vec2f) -> i32 {
@@ -263,10 +272,15 @@ impl Screen for BridsonScreen {
 	    return;
 	}	
 
+	// C relaxes toward a centroidal Voronoi tessellation
+	if self.sub_mode == SubMode::Show && is_key_pressed(KeyCode::C) {
+	    self.relax();
+	}
+
 	if self.sub_mode == SubMode::AddPoints {
 
- 	    if self.points.len() == 0 {
- 		let p = Vec2f {
+	    if self.points.len() == 0 {
+		let p = Vec2f {
 		    x: screen_width() / 2.0,
 		    y: screen_height() / 2.0
 		};
@@ -352,6 +366,10 @@ impl Screen for BridsonScreen {
 				  BLUE);
 		    }
 		}
+
+		let label = format!("Lloyd steps: {}  cell area variance: {:.0} -> {:.0}",
+				    self.lloyd_steps, self.initial_variance, self.area_variance);
+		draw_text(&label, 10.0, 20.0, 24.0, BLACK);
 	    }
 	}
     }
@@ -8,12 +8,39 @@ use macroquad::rand::gen_range;
 use async_trait::async_trait;
 
 use crate::screens::Screen;
+use crate::screens::bidir_draw::draw_bidirectional;
+use crate::screens::bidir_draw::bidirectional_labels;
 
 use crate::texture_mgr::TextureMgr;
 
 use crate::big_dice_games::math::vector::Vec2f;
 use crate::big_dice_games::util::map;
 use crate::big_dice_games::math::vector::Vector;
+use crate::big_dice_games::math::voronoi::build_voronoi;
+use crate::big_dice_games::math::voronoi::cell_area_variance;
+use crate::big_dice_games::math::voronoi::lloyd_step;
+use crate::big_dice_games::math::sampling::PointGenerator;
+use crate::big_dice_games::math::sampling::JitteredGrid;
+use crate::big_dice_games::math::sampling::HexLattice;
+use crate::big_dice_games::math::sampling::Halton;
+use crate::big_dice_games::math::sampling::Sobol;
+use crate::big_dice_games::math::sampling::UniformRandom;
+
+use crate::big_dice_games::ai::graph::NavGraph;
+use crate::big_dice_games::ai::astar::a_star;
+use crate::big_dice_games::ai::astar::dijkstra;
+use crate::big_dice_games::ai::astar::SearchResult;
+use crate::big_dice_games::ai::bidirectional::bidirectional_a_star;
+use crate::big_dice_games::ai::bidirectional::bidirectional_dijkstra;
+use crate::big_dice_games::ai::bidirectional::BidirectionalResult;
+use crate::big_dice_games::ai::cspace::circle_polygon;
+use crate::big_dice_games::ai::delaunay::DelaunayGraph;
+use crate::big_dice_games::ai::prm::segment_distance;
+use crate::big_dice_games::ai::prm::Connection;
+use crate::big_dice_games::ai::prm::EdgeState;
+use crate::big_dice_games::ai::prm::Roadmap;
+use crate::big_dice_games::ai::landmarks::LandmarkSelection;
+use crate::big_dice_games::ai::landmarks::Landmarks;
 
 //use priority_queue::PriorityQueue;
 use std::collections::BinaryHeap;
@@ -23,12 +50,53 @@ use std::collections::HashSet;
 
 use voronoice::*;
 
+// walls block roadmap edges passing within this fraction of the
+// sample radius, so neighboring walls join up into a solid barrier
+const WALL_CLEARANCE: f32 = 0.5;
+
+// the point sources D steps through, Bridson first
+const POINT_GENERATOR_COUNT: usize = 6;
+// walls painted on one point set carry over to points this close
+const WALL_CARRY_FRACTION: f32 = 0.8;
+
+fn point_generator(index: usize) -> Option<Box<dyn PointGenerator>> {
+    match index {
+	1 => Some(Box::new(JitteredGrid { jitter: 0.8 })),
+	2 => Some(Box::new(HexLattice)),
+	3 => Some(Box::new(Halton)),
+	4 => Some(Box::new(Sobol)),
+	5 => Some(Box::new(UniformRandom)),
+	_ => None,
+    }
+}
+
+fn point_generator_name(index: usize) -> String {
+    match point_generator(index) {
+	None => "Bridson".to_string(),
+	Some(g) => g.name().to_string(),
+    }
+}
+
+// the roadmap connection choices K steps through
+const PRM_CONNECTIONS: [Connection; 3] = [Connection::Nearest(6),
+					  Connection::Nearest(12),
+					  Connection::Radius(60.0)];
+
+const LANDMARK_COUNT: usize = 8;
 
 #[derive(PartialEq)]
 enum SubMode {
     AddPoints,
     Show,
     FindPath,
+    FindBidirectional,
+}
+
+// where the nav graph's links come from
+#[derive(PartialEq, Debug)]
+enum GraphKind {
+    Voronoi,
+    Delaunay,
 }
 
 #[derive(Debug)]
@@ -37,7 +105,6 @@ struct AStarRecord
     combined_distances: f32,
     distance_travelled: f32,
     heuristic_remaining: f32,
-    point: Vec2f,
     index: i32,
 }
 
@@ -91,6 +158,21 @@ pub struct BridsonNavScreen
 
     sub_mode: SubMode,
     voronoi_data: Option<Voronoi>,
+    graph_kind: GraphKind,
+    delaunay: Option<DelaunayGraph>,
+
+    // Lloyd steps run on the sampled sites, and the cell area variance
+    // before any and now
+    lloyd_steps: i32,
+    initial_variance: f32,
+    area_variance: f32,
+
+    // where the points come from; other sources are asked for as many
+    // points as Bridson made, and keep the walls and ends from before
+    point_generator: usize,
+    sample_count: usize,
+    carried_walls: Vec<Vec2f>,
+    carried_ends: Option<(Vec2f, Vec2f)>,
 
     start_index: i32,
     end_index: i32,
@@ -103,6 +185,28 @@ pub struct BridsonNavScreen
     prev_index: HashMap<i32, i32>,
 
     wall_nodes: HashSet<i32>,
+
+    bidir_result: Option<BidirectionalResult>,
+    bidir_label: String,
+    // plain A* over the same start/end, to compare expansion counts
+    bidir_baseline: Option<SearchResult>,
+    // plain Dijkstra as well, after bidirectional Dijkstra
+    bidir_dijkstra: Option<SearchResult>,
+    bidir_reveal: usize,
+
+    prm_connection: usize,
+    roadmap: Option<Roadmap>,
+    roadmap_result: Option<SearchResult>,
+    roadmap_label: String,
+
+    // None searches on straight line distance alone
+    landmark_selection: Option<LandmarkSelection>,
+    landmarks: Option<Landmarks>,
+
+    // walls or the graph kind changed since these were last brought
+    // up to date
+    delaunay_stale: bool,
+    landmarks_stale: bool,
 }
 
 impl BridsonNavScreen {
@@ -156,6 +260,15 @@ impl BridsonNavScreen {
 	    cell_width: cell_width,
 	    sub_mode: SubMode::AddPoints,
 	    voronoi_data: Option::<Voronoi>::None,
+	    graph_kind: GraphKind::Voronoi,
+	    delaunay: None,
+	    lloyd_steps: 0,
+	    initial_variance: 0.0,
+	    area_variance: 0.0,
+	    point_generator: 0,
+	    sample_count: 0,
+	    carried_walls: Vec::new(),
+	    carried_ends: None,
 	    start_index: -1,
 	    end_index: -1,
 	    a_star_nodes: BinaryHeap::new(),
@@ -163,6 +276,19 @@ impl BridsonNavScreen {
 	    open_set: HashSet::new(),
 	    prev_index: HashMap::new(),
 	    wall_nodes: HashSet::new(),
+	    bidir_result: None,
+	    bidir_label: String::new(),
+	    bidir_baseline: None,
+	    bidir_dijkstra: None,
+	    bidir_reveal: 0,
+	    prm_connection: 0,
+	    roadmap: None,
+	    roadmap_result: None,
+	    roadmap_label: String::new(),
+	    landmark_selection: None,
+	    landmarks: None,
+	    delaunay_stale: true,
+	    landmarks_stale: true,
 	}
     }
 
@@ -174,6 +300,10 @@ impl BridsonNavScreen {
 	self.is_complete_flag = false;
 	self.sub_mode = SubMode::AddPoints;
 	self.voronoi_data = Option::<Voronoi>::None;
+	self.delaunay = None;
+	self.lloyd_steps = 0;
+	self.carried_walls.clear();
+	self.carried_ends = None;
 	self.start_index = -1;
 	self.end_index = -1;
 	self.prev_index.clear();
@@ -181,6 +311,14 @@ impl BridsonNavScreen {
 	self.found_distances.clear();
 	self.a_star_nodes.clear();
 	self.wall_nodes.clear();
+	self.bidir_result = None;
+	self.bidir_baseline = None;
+	self.bidir_dijkstra = None;
+	self.bidir_reveal = 0;
+	self.roadmap = None;
+	self.roadmap_result = None;
+	self.landmark_selection = None;
+	self.landmarks = None;
     }
 
     fn point_in_box(&self, p: &Vec2f) -> bool {
@@ -193,33 +331,134 @@ impl BridsonNavScreen {
     fn make_voronoi(&mut self) {
 	println!("making voronoi for nav");
 
-	let mut sites:Vec<Point> = Vec::new();
-	for p in &self.points {
-	    let site_point = Point {
-		x: p.x as f64,
-		y: p.y as f64,
-	    };
+	self.voronoi_data = build_voronoi(&self.points, screen_width(), screen_height());
 
-	    sites.push(site_point);
+	if let Some(vd) = &self.voronoi_data {
+	    self.area_variance = cell_area_variance(vd);
+	    if self.lloyd_steps == 0 {
+		self.initial_variance = self.area_variance;
+	    }
 	}
 
-	let scr_w = (screen_width() / 2.0) as f64;
-	let scr_h = (screen_height() / 2.0) as f64;
+	self.delaunay = Some(DelaunayGraph::new(&self.points));
+	self.delaunay_stale = true;
+	self.update_delaunay();
 
-	let bounding_box = BoundingBox::new(
-	    Point{
-		x: scr_w,
-		y: scr_h,
-	    },
-	    scr_w * 2.0,
-	    scr_h * 2.0,
-	);
-	let my_voronoi = VoronoiBuilder::default()
-	    .set_sites(sites)
-	    .set_bounding_box(bounding_box)
-	    .set_lloyd_relaxation_iterations(0)
-	    .build();
-	self.voronoi_data = my_voronoi;
+	// landmark distances are per point, so new points mean new landmarks
+	self.landmarks = None;
+    }
+
+    // the points are all in; build the graphs and pick ends
+    fn finish_points(&mut self) {
+	self.sub_mode = SubMode::Show;
+	if self.point_generator == 0 {
+	    self.sample_count = self.points.len();
+	}
+
+	let carry = self.radius * WALL_CARRY_FRACTION;
+	for i in 0 .. self.points.len() {
+	    let p = self.points[i];
+	    if self.carried_walls.iter().any(|w| (*w - p).mag() < carry) {
+		self.wall_nodes.insert(i as i32);
+	    }
+	}
+	self.carried_walls.clear();
+
+	self.make_voronoi();
+
+	match self.carried_ends.take() {
+	    Some((start, end)) => {
+		self.start_index = self.find_index(&start);
+		self.end_index = self.find_index(&end);
+	    }
+	    None => {
+		self.start_index = gen_range::<i32>(0, self.points.len() as i32);
+		self.end_index = gen_range::<i32>(0, self.points.len() as i32);
+	    }
+	}
+	println!("{} points from {}, start {} end {}",
+		 self.points.len(), point_generator_name(self.point_generator),
+		 self.start_index, self.end_index);
+    }
+
+    // resample with the next point source, keeping the walls, start and
+    // end where they were on screen
+    fn next_point_generator(&mut self) {
+	let walls: Vec<Vec2f> = self.wall_nodes.iter().map(|w| self.points[*w as usize]).collect();
+	let ends = if self.start_index >= 0 && self.end_index >= 0 {
+	    Some((self.points[self.start_index as usize], self.points[self.end_index as usize]))
+	} else {
+	    None
+	};
+
+	self.point_generator = (self.point_generator + 1) % POINT_GENERATOR_COUNT;
+	self.reset();
+	self.carried_walls = walls;
+	self.carried_ends = ends;
+    }
+
+    // One Lloyd step on the sites, then the graphs are rebuilt on the
+    // moved points. Walls stay where they were painted, and nothing
+    // moves into one.
+    fn relax(&mut self) {
+	let vd = match &self.voronoi_data {
+	    None => { return; }
+	    Some(vd) => vd,
+	};
+
+	let clearance = self.radius * WALL_CLEARANCE;
+	let walls: Vec<Vec2f> = self.wall_nodes.iter().map(|w| self.points[*w as usize]).collect();
+
+	let (points, moved) = lloyd_step(vd, &self.points, |i, p| {
+	    !self.wall_nodes.contains(&(i as i32)) &&
+		walls.iter().all(|w| (*w - p).mag() > clearance)
+	});
+
+	self.points = points;
+	self.lloyd_steps += 1;
+
+	self.prev_index.clear();
+	self.open_set.clear();
+	self.found_distances.clear();
+	self.a_star_nodes.clear();
+	self.bidir_result = None;
+	self.roadmap = None;
+	self.sub_mode = SubMode::Show;
+	self.make_voronoi();
+
+	println!("lloyd step {}: {} moved, cell area variance {:.1} -> {:.1}",
+		 self.lloyd_steps, moved, self.initial_variance, self.area_variance);
+    }
+
+    // bring the Delaunay graph up to date with the painted walls: wall
+    // points are closed, and edges squeezing past one are cut
+    fn update_delaunay(&mut self) {
+	if !self.delaunay_stale {
+	    return;
+	}
+
+	let dg = match &mut self.delaunay {
+	    None => { return; }
+	    Some(dg) => dg,
+	};
+
+	let clearance = self.radius * WALL_CLEARANCE;
+	let walls: Vec<Vec<Vec2f>> = self.wall_nodes.iter()
+	    .map(|w| circle_polygon(self.points[*w as usize], clearance, 8))
+	    .collect();
+
+	for i in 0 .. self.points.len() {
+	    dg.set_open(i as i32, !self.wall_nodes.contains(&(i as i32)));
+	}
+
+	dg.restore_edges();
+	dg.remove_edges_crossing(&walls);
+	self.delaunay_stale = false;
+    }
+
+    fn walls_changed(&mut self) {
+	self.delaunay_stale = true;
+	self.landmarks_stale = true;
     }
 
     fn cell_index(&self, p: &Vec2f) -> i32 {
@@ -331,7 +570,143 @@ impl BridsonNavScreen {
     }
 
     fn calc_heuristic_by_indices(&self, a:i32, b:i32) -> f32 {
-	(self.points[a as usize] - self.points[b as usize]).mag()
+	let euclid = (self.points[a as usize] - self.points[b as usize]).mag();
+
+	match &self.landmarks {
+	    None => euclid,
+	    Some(landmarks) => euclid.max(landmarks.estimate(a, b)),
+	}
+    }
+
+    // like the Delaunay cuts, landmark distances are brought up to date
+    // with the walls and graph kind before each search, when they've
+    // changed
+    fn update_landmarks(&mut self) {
+	let selection = match self.landmark_selection {
+	    None => {
+		self.landmarks = None;
+		return;
+	    },
+	    Some(s) => s,
+	};
+
+	if self.voronoi_data.is_none() {
+	    return;
+	}
+
+	let current = self.landmarks.as_ref().is_some_and(|l| l.selection == selection);
+	if current && !self.landmarks_stale {
+	    return;
+	}
+
+	let first = if self.start_index >= 0 && self.is_open(self.start_index) {
+	    self.start_index
+	} else {
+	    self.find_open_index()
+	};
+
+	let seed = gen_range::<u32>(0, u32::MAX) as u64;
+	let landmarks = Landmarks::new(self, LANDMARK_COUNT, selection, first, seed);
+	println!("{} landmarks by {}: {:?}",
+		 landmarks.nodes.len(), landmarks.selection.name(), landmarks.nodes);
+	self.landmarks = Some(landmarks);
+	self.landmarks_stale = false;
+    }
+
+    fn start_bidirectional(&mut self, use_heuristic: bool) {
+	if self.voronoi_data.is_none() {
+	    return;
+	}
+
+	self.prev_index.clear();
+	self.open_set.clear();
+	self.found_distances.clear();
+	self.a_star_nodes.clear();
+
+	self.roadmap = None;
+	self.update_delaunay();
+	self.update_landmarks();
+
+	let h = |a: i32, b: i32| self.calc_heuristic_by_indices(a, b);
+
+	let result = if use_heuristic {
+	    bidirectional_a_star(self, self.start_index, self.end_index, h)
+	} else {
+	    bidirectional_dijkstra(self, self.start_index, self.end_index)
+	};
+	let baseline = a_star(self, self.start_index, self.end_index, h);
+	let dijkstra_baseline = if use_heuristic {
+	    None
+	} else {
+	    Some(dijkstra(self, self.start_index, self.end_index))
+	};
+
+	self.bidir_label = if use_heuristic {
+	    "bidirectional A*".to_string()
+	} else {
+	    "bidirectional Dijkstra".to_string()
+	};
+	self.bidir_result = Some(result);
+	self.bidir_baseline = Some(baseline);
+	self.bidir_dijkstra = dijkstra_baseline;
+	self.bidir_reveal = 0;
+	self.sub_mode = SubMode::FindBidirectional;
+    }
+
+    // a roadmap over the open Bridson points as an alternative to the
+    // Voronoi neighbors; lazy only checks edges on candidate paths
+    fn start_roadmap(&mut self, lazy: bool) {
+	if self.voronoi_data.is_none() {
+	    return;
+	}
+
+	self.prev_index.clear();
+	self.open_set.clear();
+	self.found_distances.clear();
+	self.a_star_nodes.clear();
+	self.bidir_result = None;
+
+	let open: Vec<bool> = (0 .. self.points.len())
+	    .map(|i| !self.wall_nodes.contains(&(i as i32)))
+	    .collect();
+	let connection = PRM_CONNECTIONS[self.prm_connection];
+	let mut roadmap = Roadmap::new(&self.points, &open, connection);
+
+	let walls: Vec<Vec2f> = self.wall_nodes.iter().map(|w| self.points[*w as usize]).collect();
+	let clearance = self.radius * WALL_CLEARANCE;
+	let is_free = |a: Vec2f, b: Vec2f| walls.iter().all(|w| segment_distance(a, b, *w) > clearance);
+
+	let (result, searches) = if lazy {
+	    let lazy_result = roadmap.lazy_find_path(self.start_index, self.end_index, is_free);
+	    (lazy_result.result, lazy_result.searches)
+	} else {
+	    roadmap.check_all(is_free);
+	    (roadmap.find_path(self.start_index, self.end_index), 1)
+	};
+
+	self.roadmap_label = format!("{} {:?}: {} edges, {} checked, {} searches, {} expanded, cost {:.1}",
+				     if lazy { "lazy PRM" } else { "PRM" },
+				     connection,
+				     roadmap.edge_count(),
+				     roadmap.checks,
+				     searches,
+				     result.expanded.len(),
+				     result.cost);
+	println!("{}", self.roadmap_label);
+
+	self.roadmap = Some(roadmap);
+	self.roadmap_result = Some(result);
+	self.sub_mode = SubMode::Show;
+    }
+
+    fn advance_bidirectional(&mut self) {
+	if let Some(result) = &self.bidir_result {
+	    if self.bidir_reveal < result.expanded.len() {
+		self.bidir_reveal += 1;
+	    } else {
+		self.sub_mode = SubMode::Show;
+	    }
+	}
     }
 
     fn advance_a_star(&mut self) {
@@ -352,7 +727,6 @@ impl BridsonNavScreen {
 		    combined_distances: h,
 		    distance_travelled: 0.0,
 		    heuristic_remaining: h,
-		    point: self.points[self.start_index as usize],
 		    index: self.start_index,
 		});
 		self.found_distances.insert(self.start_index, 0.0);
@@ -367,21 +741,14 @@ impl BridsonNavScreen {
 		    None => {
 			self.sub_mode = SubMode::Show;
 		    }
-		    Some(vd) => {
-			let cell = &vd.cell(n.index as usize);
-
-			for neighbor_index in cell.iter_neighbors() {
-			    if self.wall_nodes.contains(&(neighbor_index as i32)) {
-				continue;
-			    }
+		    Some(_) => {
+			// Voronoi or Delaunay neighbors, walls left out
+			for (neighbor_index_i32, this_step_dist) in self.neighbors(n.index) {
 			    //println!("considering neighbor index {}", neighbor_index);
-			    let neighbor_point = self.points[neighbor_index as usize];
-			    let this_step_dist = (neighbor_point - n.point).mag();
 			    let new_elapsed_dist = n.distance_travelled + this_step_dist;
 
 			    let mut insert_node = true;
 
-			    let neighbor_index_i32 = neighbor_index as i32;
 			    if self.found_distances.contains_key(&neighbor_index_i32) {
 				insert_node = self.found_distances[&neighbor_index_i32] > new_elapsed_dist;
 			    }
@@ -399,7 +766,6 @@ impl BridsonNavScreen {
 				    combined_distances: new_h + new_elapsed_dist,
 				    distance_travelled: new_elapsed_dist,
 				    heuristic_remaining: new_h,
-				    point: neighbor_point,
 				    index: neighbor_index_i32,
 				});
 				self.found_distances.insert(neighbor_index_i32,
@@ -417,6 +783,157 @@ impl BridsonNavScreen {
     }
 }
 
+impl BridsonNavScreen {
+    fn render_bidirectional(&self) {
+	let result = match &self.bidir_result {
+	    None => { return; }
+	    Some(r) => r,
+	};
+
+	draw_bidirectional(self, result, self.bidir_reveal, 6.0, DARKGREEN);
+	if self.bidir_reveal < result.expanded.len() {
+	    return;
+	}
+
+	let mut baselines = Vec::new();
+	if let Some(baseline) = &self.bidir_baseline {
+	    baselines.push(("A*", baseline));
+	}
+	if let Some(baseline) = &self.bidir_dijkstra {
+	    baselines.push(("Dijkstra", baseline));
+	}
+
+	let lines = bidirectional_labels(&self.bidir_label, result, &baselines);
+	for (i, line) in lines.iter().enumerate() {
+	    draw_text(line, 10.0, 20.0 + 24.0 * i as f32, 24.0, BLACK);
+	}
+    }
+}
+
+impl BridsonNavScreen {
+    fn render_delaunay(&self) {
+	let dg = match &self.delaunay {
+	    None => { return; }
+	    Some(dg) => dg,
+	};
+
+	let to_vec2 = |p: Vec2f| vec2(p.x, p.y);
+
+	for t in 0 .. dg.triangles().len() {
+	    let [a, b, c] = dg.triangle_corners(t);
+	    draw_triangle_lines(to_vec2(a), to_vec2(b), to_vec2(c), 1.0, SKYBLUE);
+
+	    let cc = dg.circumcenter(t);
+	    draw_circle(cc.x, cc.y, 1.0, GRAY);
+	}
+
+	for (a, b, _) in dg.edges() {
+	    if dg.is_removed(a, b) {
+		let pa = self.points[a as usize];
+		let pb = self.points[b as usize];
+		draw_line(pa.x, pa.y, pb.x, pb.y, 1.5, RED);
+	    }
+	}
+
+	let label = format!("Delaunay: {} triangles, {} edges, {} cut",
+			    dg.triangles().len(), dg.edges().len(), dg.removed_count());
+	draw_text(&label, 10.0, screen_height() - 10.0, 20.0, BLACK);
+    }
+
+    fn render_landmarks(&self) {
+	let landmarks = match &self.landmarks {
+	    None => { return; }
+	    Some(landmarks) => landmarks,
+	};
+
+	// ring the landmark doing the most for the current start and end
+	let best = if self.start_index >= 0 && self.end_index >= 0 {
+	    landmarks.best_landmark(self.start_index, self.end_index)
+	} else {
+	    None
+	};
+
+	for (k, i) in landmarks.nodes.iter().enumerate() {
+	    let p = self.points[*i as usize];
+	    if best == Some(k) {
+		draw_circle_lines(p.x, p.y, 14.0, 3.0, ORANGE);
+	    }
+	    draw_circle(p.x, p.y, 9.0, PURPLE);
+	    draw_text(&k.to_string(), p.x - 4.0, p.y + 5.0, 18.0, WHITE);
+	}
+    }
+
+    fn render_roadmap(&self) {
+	let roadmap = match &self.roadmap {
+	    None => { return; }
+	    Some(r) => r,
+	};
+
+	for (i, edges) in roadmap.candidates.iter().enumerate() {
+	    let a = roadmap.points[i];
+	    for (j, _) in edges {
+		if (*j as usize) < i {
+		    continue;
+		}
+		let b = roadmap.points[*j as usize];
+		let c = match roadmap.edge_state(i as i32, *j) {
+		    EdgeState::Unchecked => Color::new(0.6, 0.6, 0.6, 0.5),
+		    EdgeState::Free => Color::new(0.2, 0.3, 0.9, 0.6),
+		    EdgeState::Blocked => Color::new(0.9, 0.1, 0.1, 0.6),
+		};
+		draw_line(a.x, a.y, b.x, b.y, 1.0, c);
+	    }
+	}
+
+	if let Some(result) = &self.roadmap_result {
+	    for w in result.path.windows(2) {
+		let a = roadmap.points[w[0] as usize];
+		let b = roadmap.points[w[1] as usize];
+		draw_line(a.x, a.y, b.x, b.y, 3.0, DARKGREEN);
+	    }
+	}
+
+	draw_text(&self.roadmap_label, 10.0, 20.0, 24.0, BLACK);
+    }
+}
+
+impl NavGraph for BridsonNavScreen {
+    fn node_count(&self) -> usize {
+	self.points.len()
+    }
+
+    fn node_point(&self, i: i32) -> Vec2f {
+	self.points[i as usize]
+    }
+
+    fn neighbors(&self, i: i32) -> Vec<(i32, f32)> {
+	if self.graph_kind == GraphKind::Delaunay {
+	    return match &self.delaunay {
+		None => Vec::new(),
+		Some(dg) => dg.neighbors(i),
+	    };
+	}
+
+	let vd = match &self.voronoi_data {
+	    None => { return Vec::new(); }
+	    Some(vd) => vd,
+	};
+
+	let p = self.points[i as usize];
+
+	vd.cell(i as usize)
+	    .iter_neighbors()
+	    .map(|n| n as i32)
+	    .filter(|n| !self.wall_nodes.contains(n))
+	    .map(|n| (n, (self.points[n as usize] - p).mag()))
+	    .collect()
+    }
+
+    fn is_open(&self, i: i32) -> bool {
+	!self.wall_nodes.contains(&i)
+    }
+}
+
 #[async_trait]
 impl Screen for BridsonNavScreen {
     fn is_loaded(&self) -> bool {
@@ -453,9 +970,63 @@ impl Screen for BridsonNavScreen {
 	}
 
 	if is_key_down(KeyCode::F) {
+	    self.bidir_result = None;
+	    self.roadmap = None;
+	    self.update_delaunay();
+	    self.update_landmarks();
 	    self.sub_mode = SubMode::FindPath;
 	}
 
+	if is_key_pressed(KeyCode::B) {
+	    self.start_bidirectional(true);
+	}
+
+	if is_key_pressed(KeyCode::N) {
+	    self.start_bidirectional(false);
+	}
+
+	if is_key_pressed(KeyCode::P) {
+	    self.start_roadmap(false);
+	}
+
+	if is_key_pressed(KeyCode::L) {
+	    self.start_roadmap(true);
+	}
+
+	if is_key_pressed(KeyCode::G) {
+	    self.graph_kind = match self.graph_kind {
+		GraphKind::Voronoi => GraphKind::Delaunay,
+		GraphKind::Delaunay => GraphKind::Voronoi,
+	    };
+	    self.landmarks_stale = true;
+	    self.update_delaunay();
+	    println!("nav graph {:?}", self.graph_kind);
+	}
+
+	if is_key_pressed(KeyCode::D) && self.sub_mode != SubMode::AddPoints {
+	    self.next_point_generator();
+	}
+
+	// C relaxes toward a centroidal Voronoi tessellation
+	if is_key_pressed(KeyCode::C) && self.sub_mode != SubMode::AddPoints {
+	    self.relax();
+	}
+
+	// Y: landmark heuristic, placed farthest then avoid, then off
+	if is_key_pressed(KeyCode::Y) {
+	    self.landmark_selection = match self.landmark_selection {
+		None => Some(LandmarkSelection::Farthest),
+		Some(LandmarkSelection::Farthest) => Some(LandmarkSelection::Avoid),
+		Some(LandmarkSelection::Avoid) => None,
+	    };
+	    self.update_landmarks();
+	}
+
+	if is_key_pressed(KeyCode::K) {
+	    self.prm_connection = (self.prm_connection + 1) % PRM_CONNECTIONS.len();
+	    println!("roadmap connection {:?}", PRM_CONNECTIONS[self.prm_connection]);
+	}
+
 	if is_key_pressed(KeyCode::W) {
 	    println!("W");
 	    let m_pos:Vec2 = mouse_position().into();
@@ -467,6 +1038,7 @@ impl Screen for BridsonNavScreen {
 	    } else {
 		self.wall_nodes.insert(idx);
 	    }
+	    self.walls_changed();
 	}
 
 
@@ -480,8 +1052,8 @@ impl Screen for BridsonNavScreen {
 		let p = &self.points[idx];
 
 		let dist = (mouse_pos_vec - *p).mag();
-		if dist < paint_radius {
-		    self.wall_nodes.insert(idx as i32);
+		if dist < paint_radius && self.wall_nodes.insert(idx as i32) {
+		    self.walls_changed();
 		}
 	    }
 	}
@@ -491,16 +1063,37 @@ impl Screen for BridsonNavScreen {
 	    let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
 	    let idx = self.find_index(&mouse_pos_vec);
 
-	    self.wall_nodes.remove(&idx);
+	    if self.wall_nodes.remove(&idx) {
+		self.walls_changed();
+	    }
 	}
 
 	if self.sub_mode == SubMode::FindPath {
 	    self.advance_a_star();
 	}
 
+	if self.sub_mode == SubMode::FindBidirectional {
+	    self.advance_bidirectional();
+	}
+
 	if self.sub_mode == SubMode::AddPoints {
+	    if let Some(generator) = point_generator(self.point_generator) {
+		let count = if self.sample_count > 0 {
+		    self.sample_count
+		} else {
+		    (screen_width() * screen_height() / (self.radius * self.radius * 1.5)) as usize
+		};
+		let seed = gen_range::<u32>(0, u32::MAX) as u64;
 
- 	    if self.points.len() == 0 {
+		self.points = generator.generate(screen_width(), screen_height(), count, seed);
+		self.points_open = vec![false; self.points.len()];
+		self.finish_points();
+	    }
+	}
+
+	if self.sub_mode == SubMode::AddPoints {
+
+	    if self.points.len() == 0 {
 		let p = Vec2f {
 		    x: gen_range::<f32>(0.0, screen_width()),
 		    y: gen_range::<f32>(0.0, screen_height()),
@@ -511,11 +1104,7 @@ impl Screen for BridsonNavScreen {
 		loop {
 		    let open_index = self.find_open_index();
 		    if open_index < 0 {
-			self.sub_mode = SubMode::Show;
-			self.make_voronoi();
-			self.start_index = gen_range::<i32>(0, self.points.len() as i32);
-			self.end_index = gen_range::<i32>(0, self.points.len() as i32);
-			println!("start {} end {}", self.start_index, self.end_index);
+			self.finish_points();
 			break;
 		    }
 		    else
@@ -594,6 +1183,11 @@ impl Screen for BridsonNavScreen {
 
 	match &self.voronoi_data {
 	    Option::<Voronoi>::None => {}
+	    // the roadmap replaces the cells as the graph being shown
+	    Option::<Voronoi>::Some(_) if self.roadmap.is_some() => {}
+	    Option::<Voronoi>::Some(_) if self.graph_kind == GraphKind::Delaunay => {
+		self.render_delaunay();
+	    }
 	    Option::<Voronoi>::Some(data) => {
 		let verts = data.vertices();
 		let cells = data.cells();
@@ -626,6 +1220,34 @@ impl Screen for BridsonNavScreen {
 		      1.5,
 		      BLUE);
 	}
+
+	self.render_bidirectional();
+	self.render_roadmap();
+	self.render_landmarks();
+
+	if self.voronoi_data.is_some() {
+	    let label = format!("{} points from {}  Lloyd steps: {}  cell area variance: {:.0} -> {:.0}",
+				self.points.len(),
+				point_generator_name(self.point_generator),
+				self.lloyd_steps, self.initial_variance, self.area_variance);
+	    draw_text(&label, 10.0, screen_height() - 30.0, 20.0, BLACK);
+	}
+
+	// how far the found path strays from a straight line, to compare
+	// point sources by
+	if self.sub_mode == SubMode::Show && self.found_distances.contains_key(&self.end_index) {
+	    let cost = self.found_distances[&self.end_index];
+	    let straight = (self.points[self.end_index as usize] -
+			    self.points[self.start_index as usize]).mag();
+	    let heuristic = match &self.landmarks {
+		None => "euclid".to_string(),
+		Some(landmarks) => format!("{} landmarks", landmarks.selection.name()),
+	    };
+	    let label = format!("A* {:?} ({}): cost {:.1}, {:.3} x straight line, {} reached",
+				self.graph_kind, heuristic, cost, cost / straight.max(0.001),
+				self.found_distances.len());
+	    draw_text(&label, 10.0, 20.0, 24.0, BLACK);
+	}
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -0,0 +1,297 @@
+// screens/hex_nav.rs
+
+use std::any::Any;
+
+use macroquad::prelude::*;
+
+use async_trait::async_trait;
+
+use crate::screens::Screen;
+
+use crate::texture_mgr::TextureMgr;
+
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::math::vector::Vector;
+use crate::big_dice_games::math::hex::HexLayout;
+use crate::big_dice_games::math::hex::HexOrientation;
+
+use crate::big_dice_games::ai::graph::NavGraph;
+use crate::big_dice_games::ai::astar::a_star;
+use crate::big_dice_games::ai::astar::dijkstra;
+use crate::big_dice_games::ai::astar::SearchResult;
+use crate::big_dice_games::ai::hexgrid::HexGrid;
+
+// centre to corner
+const HEX_SIZE: f32 = 14.0;
+// expanded hexes revealed per second
+const REVEAL_RATE: f32 = 150.0;
+
+pub struct HexNavScreen
+{
+    time_elapsed: f32,
+    is_complete_flag: bool,
+
+    grid: HexGrid,
+
+    start_index: i32,
+    end_index: i32,
+
+    // hex distance, or none at all for Dijkstra to compare against
+    use_heuristic: bool,
+    result: Option<SearchResult>,
+    reveal: f32,
+}
+
+fn make_grid(orientation: HexOrientation) -> HexGrid {
+    let layout = HexLayout::new(orientation, HEX_SIZE, Vec2f::new(HEX_SIZE, HEX_SIZE));
+    HexGrid::new(layout, screen_width(), screen_height())
+}
+
+impl HexNavScreen {
+    pub fn new() -> HexNavScreen {
+	let grid = make_grid(HexOrientation::Pointy);
+	println!("hex grid: {} hexes", grid.hexes.len());
+
+	let mut screen = HexNavScreen {
+	    time_elapsed: 0.0,
+	    is_complete_flag: false,
+	    grid: grid,
+	    start_index: -1,
+	    end_index: -1,
+	    use_heuristic: true,
+	    result: None,
+	    reveal: 0.0,
+	};
+
+	screen.reset();
+	screen
+    }
+
+    pub fn reset(&mut self) {
+	self.time_elapsed = 0.0;
+	self.is_complete_flag = false;
+	self.grid.walls.clear();
+	self.result = None;
+	self.reveal = 0.0;
+
+	self.start_index = self.grid.index_at(Vec2f::new(screen_width() * 0.1, screen_height() * 0.5));
+	self.end_index = self.grid.index_at(Vec2f::new(screen_width() * 0.9, screen_height() * 0.5));
+    }
+
+    // swap pointy and flat, keeping walls and ends where they are on
+    // screen
+    fn flip_orientation(&mut self) {
+	let orientation = match self.grid.layout.orientation {
+	    HexOrientation::Pointy => HexOrientation::Flat,
+	    HexOrientation::Flat => HexOrientation::Pointy,
+	};
+
+	let walls: Vec<Vec2f> = self.grid.walls.iter().map(|w| self.grid.node_point(*w)).collect();
+	let start = self.grid.node_point(self.start_index);
+	let end = self.grid.node_point(self.end_index);
+
+	self.grid = make_grid(orientation);
+	for w in walls {
+	    let i = self.grid.index_at(w);
+	    if i >= 0 {
+		self.grid.walls.insert(i);
+	    }
+	}
+
+	self.start_index = self.grid.index_at(start).max(0);
+	self.end_index = self.grid.index_at(end).max(0);
+	self.result = None;
+	println!("hex grid {:?}: {} hexes", orientation, self.grid.hexes.len());
+    }
+
+    fn start_search(&mut self) {
+	if self.start_index < 0 || self.end_index < 0 {
+	    return;
+	}
+
+	let grid = &self.grid;
+	let result = if self.use_heuristic {
+	    a_star(grid, self.start_index, self.end_index, |a, b| grid.hex_distance(a, b))
+	} else {
+	    dijkstra(grid, self.start_index, self.end_index)
+	};
+
+	println!("hex search: {} expanded, {} steps", result.expanded.len(), result.cost);
+	self.result = Some(result);
+	self.reveal = 0.0;
+    }
+
+    fn mouse_index(&self) -> i32 {
+	let m_pos:Vec2 = mouse_position().into();
+	self.grid.index_at(Vec2f::new(m_pos.x, m_pos.y))
+    }
+
+    fn fill_hex(&self, i: i32, c: Color) {
+	let center = self.grid.node_point(i);
+	let corners = self.grid.layout.corners(self.grid.hexes[i as usize]);
+	for k in 0 .. 6 {
+	    let a = corners[k];
+	    let b = corners[(k + 1) % 6];
+	    draw_triangle(vec2(center.x, center.y), vec2(a.x, a.y), vec2(b.x, b.y), c);
+	}
+    }
+}
+
+#[async_trait]
+impl Screen for HexNavScreen {
+    fn is_loaded(&self) -> bool {
+	true
+    }
+
+    async fn load(&mut self, _tex_mgr: &mut TextureMgr) {
+    }
+
+    fn tick(&mut self, dt: f32) {
+	self.time_elapsed += dt;
+
+	if is_key_down(KeyCode::Escape) {
+	    self.is_complete_flag = true;
+	    return;
+	}
+
+	let mouse_index = self.mouse_index();
+
+	if is_key_down(KeyCode::S) && mouse_index >= 0 {
+	    self.start_index = mouse_index;
+	}
+
+	if is_key_down(KeyCode::E) && mouse_index >= 0 {
+	    self.end_index = mouse_index;
+	}
+
+	if is_key_down(KeyCode::R) {
+	    self.reset();
+	}
+
+	if is_key_pressed(KeyCode::W) && mouse_index >= 0 {
+	    if !self.grid.walls.remove(&mouse_index) {
+		self.grid.walls.insert(mouse_index);
+	    }
+	}
+
+	if is_mouse_button_down(MouseButton::Left) {
+	    let paint_radius = self.grid.layout.spacing() * 1.5;
+
+	    let m_pos:Vec2 = mouse_position().into();
+	    let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
+	    for idx in 0 .. self.grid.hexes.len() as i32 {
+		let dist = (mouse_pos_vec - self.grid.node_point(idx)).mag();
+		if dist < paint_radius {
+		    self.grid.walls.insert(idx);
+		}
+	    }
+	}
+
+	if is_mouse_button_down(MouseButton::Right) && mouse_index >= 0 {
+	    self.grid.walls.remove(&mouse_index);
+	}
+
+	if is_key_pressed(KeyCode::F) {
+	    self.start_search();
+	}
+
+	if is_key_pressed(KeyCode::H) {
+	    self.use_heuristic = !self.use_heuristic;
+	    self.start_search();
+	}
+
+	if is_key_pressed(KeyCode::O) {
+	    self.flip_orientation();
+	}
+
+	self.reveal += dt * REVEAL_RATE;
+    }
+
+    fn is_complete(&self) -> bool {
+	self.is_complete_flag
+    }
+
+    fn render(&self, _tex_mgr: &TextureMgr) {
+	clear_background(GRAY);
+
+	for w in &self.grid.walls {
+	    self.fill_hex(*w, PURPLE);
+	}
+
+	let mut search_done = false;
+	if let Some(result) = &self.result {
+	    let shown = (self.reveal as usize).min(result.expanded.len());
+	    search_done = shown == result.expanded.len();
+
+	    for i in &result.expanded[.. shown] {
+		self.fill_hex(*i, Color::new(1.0, 0.75, 0.4, 1.0));
+	    }
+
+	    for i in &result.expanded[.. shown] {
+		let prev = match result.prev_index.get(i) {
+		    None => { continue; }
+		    Some(p) => *p,
+		};
+		if prev < 0 {
+		    continue;
+		}
+		let n = self.grid.node_point(*i);
+		let p = self.grid.node_point(prev);
+		draw_line(n.x, n.y, p.x, p.y, 1.5, BLUE);
+	    }
+	}
+
+	if self.start_index >= 0 {
+	    self.fill_hex(self.start_index, GREEN);
+	}
+	if self.end_index >= 0 {
+	    self.fill_hex(self.end_index, RED);
+	}
+
+	for h in &self.grid.hexes {
+	    let corners = self.grid.layout.corners(*h);
+	    for k in 0 .. 6 {
+		let a = corners[k];
+		let b = corners[(k + 1) % 6];
+		draw_line(a.x, a.y, b.x, b.y, 1.0, BLACK);
+	    }
+	}
+
+	if let Some(result) = &self.result {
+	    if search_done {
+		for w in result.path.windows(2) {
+		    let a = self.grid.node_point(w[0]);
+		    let b = self.grid.node_point(w[1]);
+		    draw_line(a.x, a.y, b.x, b.y, 3.0, DARKGREEN);
+		}
+	    }
+
+	    let label = if !search_done {
+		format!("searching, {} expanded", (self.reveal as usize).min(result.expanded.len()))
+	    } else if result.found() {
+		format!("{}: {} expanded, {} steps",
+			if self.use_heuristic { "A* hex distance" } else { "Dijkstra" },
+			result.expanded.len(), result.cost)
+	    } else {
+		format!("no path, {} expanded", result.expanded.len())
+	    };
+	    draw_text(&label, 10.0, 20.0, 24.0, WHITE);
+	}
+
+	let orientation = match self.grid.layout.orientation {
+	    HexOrientation::Pointy => "pointy",
+	    HexOrientation::Flat => "flat",
+	};
+	let mut label = format!("{} topped, {} hexes", orientation, self.grid.hexes.len());
+	let mouse_index = self.mouse_index();
+	if mouse_index >= 0 {
+	    let h = self.grid.hexes[mouse_index as usize];
+	    label = format!("{}  mouse at q {} r {} s {}", label, h.q, h.r, h.s());
+	}
+	draw_text(&label, 10.0, screen_height() - 10.0, 24.0, WHITE);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+	self
+    }
+}
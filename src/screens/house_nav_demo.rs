@@ -13,16 +13,33 @@ use crate::screens::Screen;
 
 use crate::GameScreen;
 
-const BRICK_PATH: &str = "Textures/brick.png";
-const CAR_PATH: &str = "Textures/car.png";
-const HOUSE_PATH: &str = "Textures/house.png";
-const PARK_PATH: &str = "Textures/parking_space.png";
-const ROBOT_PATH: &str = "Textures/robot.png";
+use crate::sprite::draw_sprites;
+use crate::scene::load_scene;
+use crate::scene::Scene;
+
+// robot driving, pixels and radians per second
+const ROBOT_SPEED: f32 = 150.0;
+const ROBOT_TURN_RATE: f32 = 2.5;
+
+const SCENE_PATHS: [&str; 2] = [
+    "Scenes/house.json",
+    "Scenes/parking_lot.json",
+];
 
 pub struct HouseNavDemo
 {
     is_loaded_flag: bool,
     is_complete_flag: bool,
+
+    scene: Scene,
+    scene_index: usize,
+    show_bounds: bool,
+    // draw the obstacles grown by the robot's footprint
+    show_cspace: bool,
+
+    // sprite being dragged, and where on it we grabbed it
+    selected: Option<usize>,
+    grab_local: Vec2,
 }
 
 impl HouseNavDemo {
@@ -30,18 +47,124 @@ impl HouseNavDemo {
 	HouseNavDemo {
 	    is_loaded_flag: false,
 	    is_complete_flag: false,
+	    scene: Scene::empty(),
+	    scene_index: 0,
+	    show_bounds: false,
+	    show_cspace: false,
+	    selected: None,
+	    grab_local: Vec2::ZERO,
 	}
     }
+
+    // topmost sprite under a screen point
+    fn pick(&self, p: Vec2) -> Option<usize> {
+	let mut best: Option<usize> = None;
+
+	for (i, s) in self.scene.sprites.iter().enumerate() {
+	    if !s.contains_point(p) {
+		continue;
+	    }
+	    match best {
+		Some(b) if self.scene.sprites[b].z > s.z => {}
+		_ => { best = Some(i); }
+	    }
+	}
+
+	best
+    }
 }
 
 #[async_trait]
 impl Screen for HouseNavDemo {
-    fn tick(&mut self, _dt: f32) {
+    fn tick(&mut self, dt: f32) {
 	// todo move things around
 
 	if is_key_down(KeyCode::Escape) {
 	    self.is_complete_flag = true;
 	}
+
+	if is_key_pressed(KeyCode::B) {
+	    self.show_bounds = !self.show_bounds;
+	}
+
+	if is_key_pressed(KeyCode::I) {
+	    self.show_cspace = !self.show_cspace;
+	}
+
+	// N: next scene, loaded again before the next tick
+	if is_key_pressed(KeyCode::N) {
+	    self.scene_index = (self.scene_index + 1) % SCENE_PATHS.len();
+	    self.selected = None;
+	    self.is_loaded_flag = false;
+	    return;
+	}
+
+	if is_key_pressed(KeyCode::R) {
+	    self.scene.reset_robot();
+	}
+
+	// drive the robot with WASD; its whole body stops at anything
+	// solid, turning included
+	let mut turn = 0.0;
+	if is_key_down(KeyCode::A) {
+	    turn -= ROBOT_TURN_RATE * dt;
+	}
+	if is_key_down(KeyCode::D) {
+	    turn += ROBOT_TURN_RATE * dt;
+	}
+
+	if turn != 0.0 {
+	    let here = vec2(self.scene.robot.x, self.scene.robot.y);
+	    self.scene.robot.theta += turn;
+	    if !self.scene.robot_fits(here) {
+		self.scene.robot.theta -= turn;
+	    }
+	}
+
+	let robot = &self.scene.robot;
+
+	let mut drive = 0.0;
+	if is_key_down(KeyCode::W) {
+	    drive += ROBOT_SPEED * dt;
+	}
+	if is_key_down(KeyCode::S) {
+	    drive -= ROBOT_SPEED * dt;
+	}
+
+	// theta 0 faces up the screen, like the car texture
+	let next = vec2(robot.x + drive * robot.theta.sin(),
+			robot.y - drive * robot.theta.cos());
+	if drive != 0.0 && self.scene.robot_fits(next) {
+	    self.scene.robot.x = next.x;
+	    self.scene.robot.y = next.y;
+	}
+
+	// drag sprites about with the mouse, left and right turn them
+	let m_pos:Vec2 = mouse_position().into();
+
+	if is_mouse_button_pressed(MouseButton::Left) {
+	    self.selected = self.pick(m_pos);
+	    if let Some(i) = self.selected {
+		self.grab_local = self.scene.sprites[i].world_to_local(m_pos);
+	    }
+	}
+
+	if let Some(i) = self.selected {
+	    let s = &mut self.scene.sprites[i];
+
+	    if is_mouse_button_down(MouseButton::Left) {
+		let grabbed = s.local_to_world(self.grab_local);
+		s.x += m_pos.x - grabbed.x;
+		s.y += m_pos.y - grabbed.y;
+	    }
+
+	    if is_key_down(KeyCode::Left) {
+		s.theta -= 2.0 * dt;
+	    }
+	    if is_key_down(KeyCode::Right) {
+		s.theta += 2.0 * dt;
+	    }
+	}
     }
 
     fn is_complete(&self) -> bool {
@@ -53,112 +176,59 @@ impl Screen for HouseNavDemo {
     }
 
     async fn load(&mut self, tex_mgr: &mut TextureMgr) {
-	let texture_filenames = vec!(
-	    BRICK_PATH.to_string(),
-	    CAR_PATH.to_string(),
-	    HOUSE_PATH.to_string(),
-	    PARK_PATH.to_string(),
-	    ROBOT_PATH.to_string(),
-	);
-
-	tex_mgr.load(texture_filenames).await;
+	let path = SCENE_PATHS[self.scene_index];
+
+	self.scene = match load_scene(path, tex_mgr).await {
+	    Ok(scene) => scene,
+	    Err(e) => {
+		println!("{}", e);
+		Scene::empty()
+	    }
+	};
 
 	self.is_loaded_flag = true;
     }
 
-    fn render(&self, tex_mgr: &TextureMgr) {
+    fn render(&self, _tex_mgr: &TextureMgr) {
         clear_background(LIGHTGRAY);
 
-	let park_texture = tex_mgr.get_texture(&PARK_PATH.to_string());
-
-        draw_texture(
-            *park_texture,
-	    675.0,
-	    175.0,
-            WHITE,
-        );
-
-	let house_texture = tex_mgr.get_texture(&HOUSE_PATH.to_string());
-
-        draw_texture(
-            *house_texture,
-	    500.0,
-	    50.0,
-            WHITE,
-        );
-
-	let robot_texture = tex_mgr.get_texture(&ROBOT_PATH.to_string());
-
-        draw_texture(
-            *robot_texture,
-            300.0,
-	    150.0,
-            WHITE,
-        );
-
-	let brick_texture = tex_mgr.get_texture(&BRICK_PATH.to_string());
-
-	for x in (0..800).step_by(50) {
-            draw_texture(
-		*brick_texture,
-		x as f32,
-		0.0,
-		WHITE,
-            );
-            draw_texture(
-		*brick_texture,
-		x as f32,
-		550.0,
-		WHITE,
-            );
-	}
-
-	for y in (50..550).step_by(50) {
-            draw_texture(
-		*brick_texture,
-		0.0,
-		y as f32,
-		WHITE,
-            );
-            draw_texture(
-		*brick_texture,
-		750.0,
-		y as f32,
-		WHITE,
-            );
-	}
-
-	let car_params = DrawTextureParams {
-	    dest_size: None,
-	    source: None,
-	    rotation: std::f32::consts::PI / 2.0,
-	    flip_x: false,
-	    flip_y: false,
-	    pivot: None
-	};
-	/*
-	    dest_size : Some(vec2(25.0, 50.0)),
-	    source : Some(Rect {
-		x:0.0,
-		y:0.0,
-		w: 50.0,
-		h: 100.0}),
-	    rotation: 1.5,
-	    flip_x: false,
-	    flip_y: false,
-	    pivot: Some(vec2(25., 50.)),
-    };*/
-
-	let car_texture = tex_mgr.get_texture(&CAR_PATH.to_string());	
-
-        draw_texture_ex(
-            *car_texture,
-            100.0,
-	    400.0,
-            WHITE,
-	    car_params,
-        );
+	let goal = self.scene.goal;
+	draw_rectangle(goal.x, goal.y, goal.w, goal.h, Color::new(0.0, 0.9, 0.0, 0.25));
+
+	draw_sprites(&self.scene.sprites);
+	self.scene.robot.draw();
+
+	if self.show_bounds {
+	    for s in &self.scene.sprites {
+		s.draw_bounds(RED);
+	    }
+	    for c in self.scene.colliders() {
+		c.draw(BLUE);
+	    }
+	}
 
+	if self.show_cspace {
+	    let footprint = self.scene.robot_footprint();
+	    for obstacle in self.scene.cspace_obstacles(&footprint) {
+		for i in 0 .. obstacle.len() {
+		    let a = obstacle[i];
+		    let b = obstacle[(i + 1) % obstacle.len()];
+		    draw_line(a.x, a.y, b.x, b.y, 1.5, MAGENTA);
+		}
+	    }
+	    draw_circle(self.scene.robot.x, self.scene.robot.y, 4.0, MAGENTA);
+	}
+
+	if let Some(i) = self.selected {
+	    self.scene.sprites[i].draw_bounds(YELLOW);
+	}
+
+	let label = if self.scene.robot_in_goal() {
+	    format!("{}: robot parked", self.scene.name)
+	} else {
+	    format!("{}: robot not parked", self.scene.name)
+	};
+	draw_text(&label, 10.0, screen_height() - 10.0, 24.0, BLACK);
     }
 
     fn as_any(&self) -> &dyn Any {
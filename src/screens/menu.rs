@@ -112,6 +112,30 @@ impl Screen for MenuScreen {
 		    self.is_complete_flag = true;
 		    self.next_screen = Option::<GameScreen>::Some(GameScreen::HouseNav);
 		}
+
+		if ui.button(Vec2::new(40., 160.),
+			     "ORCA doorway") {
+		    self.is_complete_flag = true;
+		    self.next_screen = Option::<GameScreen>::Some(GameScreen::Orca);
+		}
+
+		if ui.button(Vec2::new(40., 180.),
+			     "navmesh funnel") {
+		    self.is_complete_flag = true;
+		    self.next_screen = Option::<GameScreen>::Some(GameScreen::NavMesh);
+		}
+
+		if ui.button(Vec2::new(40., 200.),
+			     "visibility graph") {
+		    self.is_complete_flag = true;
+		    self.next_screen = Option::<GameScreen>::Some(GameScreen::Visibility);
+		}
+
+		if ui.button(Vec2::new(40., 220.),
+			     "hex grid nav") {
+		    self.is_complete_flag = true;
+		    self.next_screen = Option::<GameScreen>::Some(GameScreen::HexNav);
+		}
 	    });
 	
     }
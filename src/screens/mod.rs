@@ -9,8 +9,14 @@ pub mod menu;
 pub mod bridson;
 pub mod bridson_nav;
 pub mod square_nav;
+pub mod hex_nav;
 
 pub mod house_nav_demo;
+pub mod orca_demo;
+pub mod navmesh_nav;
+pub mod visibility_nav;
+
+pub mod bidir_draw;
 
 use crate::texture_mgr::TextureMgr;
 
@@ -0,0 +1,238 @@
+// screens/navmesh_nav.rs
+
+use std::any::Any;
+
+use macroquad::prelude::*;
+use macroquad::rand::gen_range;
+
+use async_trait::async_trait;
+
+use crate::screens::Screen;
+
+use crate::texture_mgr::TextureMgr;
+
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::math::vector::Vector;
+
+use crate::big_dice_games::ai::navmesh::point_in_polygon;
+use crate::big_dice_games::ai::navmesh::NavMesh;
+use crate::big_dice_games::ai::navmesh::NavMeshPath;
+
+// one obstacle, maybe, per cell; they're kept inside so none touch
+const CELL_SIZE: f32 = 220.0;
+const OBSTACLE_CHANCE: f32 = 0.65;
+const L_SHAPE_CHANCE: f32 = 0.3;
+// portal edges revealed per second
+const REVEAL_RATE: f32 = 60.0;
+
+pub struct NavMeshScreen
+{
+    time_elapsed: f32,
+    is_complete_flag: bool,
+
+    obstacles: Vec<Vec<Vec2f>>,
+    mesh: NavMesh,
+
+    start: Vec2f,
+    goal: Vec2f,
+    path: Option<NavMeshPath>,
+    reveal: f32,
+}
+
+// a star shaped polygon round c, or an L
+fn random_obstacle(c: Vec2f) -> Vec<Vec2f> {
+    if gen_range::<f32>(0.0, 1.0) < L_SHAPE_CHANCE {
+	let s = gen_range::<f32>(50.0, 75.0);
+	let t = s * 0.5;
+	let mut corners = vec![Vec2f::new(-s, -s), Vec2f::new(s, -s), Vec2f::new(s, -s + t),
+			       Vec2f::new(-s + t, -s + t), Vec2f::new(-s + t, s), Vec2f::new(-s, s)];
+
+	// any of four ways round
+	let turns = gen_range::<i32>(0, 4);
+	for _ in 0 .. turns {
+	    for p in corners.iter_mut() {
+		*p = Vec2f::new(-p.y, p.x);
+	    }
+	}
+	return corners.into_iter().map(|p| c + p).collect();
+    }
+
+    let sides = gen_range::<i32>(3, 8);
+    let radius = gen_range::<f32>(40.0, 80.0);
+    let offset = gen_range::<f32>(0.0, std::f32::consts::PI);
+
+    (0 .. sides)
+	.map(|i| {
+	    let a = offset + i as f32 * std::f32::consts::PI * 2.0 / sides as f32;
+	    let r = radius * gen_range::<f32>(0.7, 1.0);
+	    c + Vec2f::new(a.cos(), a.sin()).scale(r)
+	})
+	.collect()
+}
+
+impl NavMeshScreen {
+    pub fn new() -> NavMeshScreen {
+	let mut screen = NavMeshScreen {
+	    time_elapsed: 0.0,
+	    is_complete_flag: false,
+	    obstacles: Vec::new(),
+	    // replaced by reset
+	    mesh: NavMesh::build(Vec2f::zero(), Vec2f::new(1.0, 1.0), &[]),
+	    start: Vec2f::zero(),
+	    goal: Vec2f::zero(),
+	    path: None,
+	    reveal: 0.0,
+	};
+
+	screen.reset();
+	screen
+    }
+
+    pub fn reset(&mut self) {
+	self.time_elapsed = 0.0;
+	self.is_complete_flag = false;
+	self.obstacles.clear();
+
+	let num_x = (screen_width() / CELL_SIZE).floor() as i32;
+	let num_y = (screen_height() / CELL_SIZE).floor() as i32;
+	let margin = Vec2f::new((screen_width() - num_x as f32 * CELL_SIZE) * 0.5,
+				(screen_height() - num_y as f32 * CELL_SIZE) * 0.5);
+
+	for y in 0 .. num_y {
+	    for x in 0 .. num_x {
+		if gen_range::<f32>(0.0, 1.0) > OBSTACLE_CHANCE {
+		    continue;
+		}
+		let jitter = Vec2f::new(gen_range::<f32>(-20.0, 20.0),
+					gen_range::<f32>(-20.0, 20.0));
+		let c = margin + Vec2f::new((x as f32 + 0.5) * CELL_SIZE,
+					    (y as f32 + 0.5) * CELL_SIZE) + jitter;
+		self.obstacles.push(random_obstacle(c));
+	    }
+	}
+
+	self.mesh = NavMesh::build(Vec2f::zero(),
+				   Vec2f::new(screen_width(), screen_height()),
+				   &self.obstacles);
+	println!("navmesh: {} triangles, {} failed constraints",
+		 self.mesh.triangle_count(), self.mesh.failed_constraints);
+
+	self.start = Vec2f::new(15.0, 15.0);
+	self.goal = Vec2f::new(screen_width() - 15.0, screen_height() - 15.0);
+	self.find_path();
+    }
+
+    fn find_path(&mut self) {
+	self.path = Some(self.mesh.find_path(self.start, self.goal));
+	self.reveal = 0.0;
+    }
+
+    fn in_obstacle(&self, p: Vec2f) -> bool {
+	self.obstacles.iter().any(|o| point_in_polygon(o, p))
+    }
+}
+
+#[async_trait]
+impl Screen for NavMeshScreen {
+    fn is_loaded(&self) -> bool {
+	true
+    }
+
+    async fn load(&mut self, _tex_mgr: &mut TextureMgr) {
+    }
+
+    fn tick(&mut self, dt: f32) {
+	self.time_elapsed += dt;
+
+	if is_key_down(KeyCode::Escape) {
+	    self.is_complete_flag = true;
+	    return;
+	}
+
+	if is_key_pressed(KeyCode::R) {
+	    self.reset();
+	}
+
+	let m_pos:Vec2 = mouse_position().into();
+	let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
+
+	if is_key_down(KeyCode::S) && !self.in_obstacle(mouse_pos_vec) {
+	    self.start = mouse_pos_vec;
+	    self.find_path();
+	}
+
+	if is_key_down(KeyCode::E) && !self.in_obstacle(mouse_pos_vec) {
+	    self.goal = mouse_pos_vec;
+	    self.find_path();
+	}
+
+	self.reveal += dt * REVEAL_RATE;
+    }
+
+    fn is_complete(&self) -> bool {
+	self.is_complete_flag
+    }
+
+    fn render(&self, _tex_mgr: &TextureMgr) {
+	// obstacles are whatever the walkable triangles don't cover
+	clear_background(DARKGRAY);
+
+	let to_vec2 = |p: Vec2f| vec2(p.x, p.y);
+
+	for t in 0 .. self.mesh.triangle_count() {
+	    let [a, b, c] = self.mesh.corners(t);
+	    draw_triangle(to_vec2(a), to_vec2(b), to_vec2(c), LIGHTGRAY);
+	}
+
+	let path = match &self.path {
+	    None => { return; }
+	    Some(p) => p,
+	};
+
+	let shown = (self.reveal as usize).min(path.expanded.len());
+	let search_done = shown == path.expanded.len();
+
+	if search_done {
+	    for t in &path.corridor {
+		let [a, b, c] = self.mesh.corners(*t as usize);
+		draw_triangle(to_vec2(a), to_vec2(b), to_vec2(c), Color::new(1.0, 0.95, 0.6, 1.0));
+	    }
+	}
+
+	for t in 0 .. self.mesh.triangle_count() {
+	    let [a, b, c] = self.mesh.corners(t);
+	    draw_triangle_lines(to_vec2(a), to_vec2(b), to_vec2(c), 1.0, GRAY);
+	}
+
+	for e in &path.expanded[.. shown] {
+	    let (a, b) = self.mesh.portal_segment(*e);
+	    draw_line(a.x, a.y, b.x, b.y, 2.0, ORANGE);
+	}
+
+	if search_done {
+	    for w in path.path.windows(2) {
+		draw_line(w[0].x, w[0].y, w[1].x, w[1].y, 3.0, DARKGREEN);
+	    }
+	    for p in &path.path {
+		draw_circle(p.x, p.y, 3.0, DARKGREEN);
+	    }
+	}
+
+	draw_circle(self.start.x, self.start.y, 7.5, GREEN);
+	draw_circle(self.goal.x, self.goal.y, 7.5, RED);
+
+	let label = if !search_done {
+	    format!("navmesh: searching, {} portals", shown)
+	} else if path.found() {
+	    format!("navmesh: {} triangles, corridor of {}, path {:.0} long",
+		    self.mesh.triangle_count(), path.corridor.len(), path.length())
+	} else {
+	    format!("navmesh: {} triangles, no path", self.mesh.triangle_count())
+	};
+	draw_text(&label, 10.0, 20.0, 24.0, WHITE);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+	self
+    }
+}
@@ -0,0 +1,190 @@
+// screens/orca_demo.rs
+
+use std::any::Any;
+
+use macroquad::prelude::*;
+
+use async_trait::async_trait;
+
+use crate::screens::Screen;
+
+use crate::texture_mgr::TextureMgr;
+
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::math::vector::Vector;
+
+use crate::big_dice_games::ai::orca::OrcaAgent;
+use crate::big_dice_games::ai::orca::OrcaObstacle;
+use crate::big_dice_games::ai::orca::OrcaSimulator;
+
+const WALL_X: f32 = 550.0;
+const DOOR_TOP: f32 = 375.0;
+const DOOR_BOTTOM: f32 = 475.0;
+const WALL_POST_RADIUS: f32 = 10.0;
+const WALL_POST_SPACING: f32 = 12.0;
+
+const AGENTS_PER_SIDE: i32 = 24;
+const AGENT_RADIUS: f32 = 10.0;
+const AGENT_MAX_SPEED: f32 = 120.0;
+
+// the cart that trundles up and down, ignoring everyone
+const CART_X: f32 = 820.0;
+const CART_RADIUS: f32 = 25.0;
+const CART_SPEED: f32 = 90.0;
+
+pub struct OrcaDemoScreen
+{
+    time_elapsed: f32,
+    is_complete_flag: bool,
+
+    sim: OrcaSimulator,
+    // index of the moving obstacle in sim.obstacles
+    cart_index: usize,
+    agent_count: usize,
+}
+
+impl OrcaDemoScreen {
+    pub fn new() -> OrcaDemoScreen {
+	let mut screen = OrcaDemoScreen {
+	    is_complete_flag: false,
+	    time_elapsed: 0.0,
+	    sim: OrcaSimulator::new(2.0, 0.5, 80.0),
+	    cart_index: 0,
+	    agent_count: 0,
+	};
+
+	screen.reset();
+	screen
+    }
+
+    pub fn reset(&mut self) {
+	self.time_elapsed = 0.0;
+	self.is_complete_flag = false;
+	self.sim.agents.clear();
+	self.sim.obstacles.clear();
+
+	// the wall is a row of posts, with a gap for the doorway
+	let mut y = 0.0;
+	while y < screen_height() {
+	    if y < DOOR_TOP || y > DOOR_BOTTOM {
+		self.sim.obstacles.push(OrcaObstacle {
+		    position: Vec2f::new(WALL_X, y),
+		    velocity: Vec2f::zero(),
+		    radius: WALL_POST_RADIUS,
+		});
+	    }
+	    y += WALL_POST_SPACING;
+	}
+
+	self.cart_index = self.sim.obstacles.len();
+	self.sim.obstacles.push(OrcaObstacle {
+	    position: Vec2f::new(CART_X, 100.0),
+	    velocity: Vec2f::new(0.0, CART_SPEED),
+	    radius: CART_RADIUS,
+	});
+
+	let door_y = (DOOR_TOP + DOOR_BOTTOM) * 0.5;
+	let door_left = Vec2f::new(WALL_X - 40.0, door_y);
+	let door_right = Vec2f::new(WALL_X + 40.0, door_y);
+
+	// two blocks of agents, each heading for the mirror image of
+	// where it started, through the door
+	for i in 0 .. AGENTS_PER_SIDE {
+	    let col = (i % 4) as f32;
+	    let row = (i / 4) as f32;
+
+	    let left = Vec2f::new(150.0 + col * 50.0, 300.0 + row * 50.0);
+	    let right = Vec2f::new(screen_width() - left.x, left.y);
+
+	    self.sim.agents.push(OrcaAgent::new(
+		left, AGENT_RADIUS, AGENT_MAX_SPEED,
+		vec![door_left, door_right, right]));
+
+	    self.sim.agents.push(OrcaAgent::new(
+		right, AGENT_RADIUS, AGENT_MAX_SPEED,
+		vec![door_right, door_left, left]));
+	}
+
+	self.agent_count = self.sim.agents.len();
+    }
+
+    fn through_count(&self) -> usize {
+	self.sim.agents.iter().filter(|a| a.path_index >= 2).count()
+    }
+}
+
+#[async_trait]
+impl Screen for OrcaDemoScreen {
+    fn is_loaded(&self) -> bool {
+	true
+    }
+
+    async fn load(&mut self, _tex_mgr: &mut TextureMgr) {
+    }
+
+    fn tick(&mut self, dt: f32) {
+	self.time_elapsed += dt;
+
+	if is_key_down(KeyCode::Escape) {
+	    self.is_complete_flag = true;
+	    return;
+	}
+
+	if is_key_pressed(KeyCode::R) {
+	    self.reset();
+	}
+
+	// bounce the cart between the top and bottom
+	let cart = &mut self.sim.obstacles[self.cart_index];
+	if (cart.position.y > screen_height() - 100.0 && cart.velocity.y > 0.0) ||
+	    (cart.position.y < 100.0 && cart.velocity.y < 0.0) {
+	    cart.velocity.y = -cart.velocity.y;
+	}
+
+	// big frame hitches would let agents tunnel
+	self.sim.step(dt.min(1.0 / 30.0), AGENT_RADIUS * 3.0);
+    }
+
+    fn is_complete(&self) -> bool {
+	self.is_complete_flag
+    }
+
+    fn render(&self, _tex_mgr: &TextureMgr) {
+	clear_background(LIGHTGRAY);
+
+	for (i, o) in self.sim.obstacles.iter().enumerate() {
+	    let c = if i == self.cart_index { BROWN } else { DARKGRAY };
+	    draw_circle(o.position.x, o.position.y, o.radius, c);
+	}
+
+	for (i, a) in self.sim.agents.iter().enumerate() {
+	    // agents alternate sides as they were added
+	    let c = if i % 2 == 0 { BLUE } else { RED };
+
+	    if !a.is_finished() {
+		let target = a.path[a.path_index];
+		draw_line(a.position.x, a.position.y,
+			  target.x, target.y,
+			  1.0,
+			  Color::new(c.r, c.g, c.b, 0.25));
+	    }
+
+	    draw_circle(a.position.x, a.position.y, a.radius, c);
+
+	    let v_end = a.position + a.velocity.scale(0.25);
+	    draw_line(a.position.x, a.position.y,
+		      v_end.x, v_end.y,
+		      2.0,
+		      BLACK);
+	}
+
+	let label = format!("ORCA: {} of {} agents through the door",
+			    self.through_count(),
+			    self.agent_count);
+	draw_text(&label, 10.0, 20.0, 24.0, BLACK);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+	self
+    }
+}
@@ -8,6 +8,8 @@ use macroquad::rand::gen_range;
 use async_trait::async_trait;
 
 use crate::screens::Screen;
+use crate::screens::bidir_draw::draw_bidirectional;
+use crate::screens::bidir_draw::bidirectional_labels;
 
 use crate::texture_mgr::TextureMgr;
 
@@ -15,25 +17,123 @@ use crate::big_dice_games::math::vector::Vec2f;
 use crate::big_dice_games::util::map;
 use crate::big_dice_games::math::vector::Vector;
 
+use crate::big_dice_games::ai::graph::NavGraph;
+use crate::big_dice_games::ai::astar::a_star;
+use crate::big_dice_games::ai::astar::dijkstra;
+use crate::big_dice_games::ai::astar::SearchResult;
+use crate::big_dice_games::ai::bidirectional::bidirectional_a_star;
+use crate::big_dice_games::ai::bidirectional::bidirectional_dijkstra;
+use crate::big_dice_games::ai::bidirectional::BidirectionalResult;
+use crate::big_dice_games::ai::anytime::ara_star;
+use crate::big_dice_games::ai::anytime::focal_search;
+use crate::big_dice_games::ai::anytime::weighted_a_star;
+use crate::big_dice_games::ai::anytime::BoundedSolution;
+use crate::big_dice_games::ai::lpastar::LpaStar;
+use crate::big_dice_games::ai::flowfield::flow_direction;
+use crate::big_dice_games::ai::flowfield::flow_field;
+use crate::big_dice_games::ai::flowfield::integration_field;
+use crate::big_dice_games::ai::cbs::conflict_based_search;
+use crate::big_dice_games::ai::cbs::position_at;
+use crate::big_dice_games::ai::cbs::MapfSolution;
+use crate::big_dice_games::ai::cooperative::cooperative_a_star;
+use crate::big_dice_games::ai::cooperative::WindowedPlanner;
+use crate::big_dice_games::ai::steering::SteeringAgent;
+use crate::big_dice_games::ai::cspace::inflate_grid;
+use crate::big_dice_games::ai::cspace::Footprint;
+use crate::big_dice_games::ai::distance::ClearanceCost;
+use crate::big_dice_games::ai::distance::DistanceField;
+use crate::big_dice_games::ai::maze::generate_maze;
+use crate::big_dice_games::ai::maze::MAZE_KINDS;
+use crate::big_dice_games::ai::hpa::Hpa;
+use crate::big_dice_games::ai::hpa::HpaResult;
+use crate::big_dice_games::ai::landmarks::LandmarkSelection;
+use crate::big_dice_games::ai::landmarks::Landmarks;
+use crate::big_dice_games::ai::contraction::ChResult;
+use crate::big_dice_games::ai::contraction::ContractionHierarchy;
+use crate::big_dice_games::ai::heuristic_check::check_heuristic;
+use crate::big_dice_games::ai::heuristic_check::HeuristicReport;
+use crate::big_dice_games::ai::neighborhood::free_distance;
+use crate::big_dice_games::ai::neighborhood::grid_moves;
+use crate::big_dice_games::ai::neighborhood::move_reach;
+use crate::big_dice_games::ai::neighborhood::Connectivity;
+use crate::big_dice_games::ai::neighborhood::CornerCutting;
+use crate::big_dice_games::ai::neighborhood::GridMove;
+use crate::big_dice_games::ai::neighborhood::CONNECTIVITIES;
+use crate::big_dice_games::ai::neighborhood::CORNER_CUTTINGS;
+
 //use priority_queue::PriorityQueue;
 use std::collections::BinaryHeap;
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::collections::HashSet;
 
-const SQRT_2: f32 = 1.41421356237;
+const MAX_EPSILON: f32 = 5.0;
+const EPSILON_STEP: f32 = 0.25;
+
+const FLOW_AGENT_COUNT: usize = 60;
+// in spaces per second
+const FLOW_AGENT_SPEED: f32 = 4.0;
+
+// agents placed when M is pressed with none placed by hand
+const MAPF_RANDOM_AGENTS: usize = 6;
+const MAPF_STEPS_PER_SECOND: f32 = 4.0;
+// windowed cooperative A*: agents, lookahead and how often to replan
+const COOP_RANDOM_AGENTS: usize = 30;
+const COOP_WINDOW: i32 = 8;
+const COOP_REPLAN_INTERVAL: i32 = 4;
+const AGENT_COLORS: [Color; 8] = [RED, GREEN, BLUE, YELLOW, MAGENTA, ORANGE, SKYBLUE, LIME];
+
+const ROBOT_PATH: &str = "Textures/robot.png";
+// steering robots; sizes and speeds are in spaces
+const STEER_ROBOT_COUNT: usize = 5;
+const STEER_ROBOT_SIZE: f32 = 1.5;
+const STEER_MAX_SPEED: f32 = 5.0;
+const STEER_MAX_ACCELERATION: f32 = 15.0;
+// walls further than this are ignored for avoidance
+const STEER_WALL_RANGE: f32 = 3.0;
+// clearance aware search: closer than this to a wall costs extra, up
+// to (1 + weight) times the distance right beside one
+const CLEARANCE_SAFE_DISTANCE: f32 = 3.0;
+const CLEARANCE_WEIGHT: f32 = 4.0;
+
+// HPA* clusters are this many spaces across
+const HPA_CLUSTER_SIZE: i32 = 10;
+
+const LANDMARK_COUNT: usize = 8;
+
+// the order Tab steps through them, and the heuristic check compares
+const HEURISTICS: [Heuristic; 5] = [Heuristic::Euclid, Heuristic::Manhattan,
+				    Heuristic::Exact_8, Heuristic::Neighborhood,
+				    Heuristic::Landmarks];
 
 #[derive(PartialEq)]
 enum SubMode {
     AddPoints,
     Show,
     FindPath,
+    FindBidirectional,
+    FindAnytime,
+}
+
+// what the planners keep clear of walls, cycled with I
+#[derive(PartialEq)]
+enum FootprintMode {
+    Point,
+    // the steering robots' circle
+    Circle,
+    // a cart, wider than it is long
+    Cart,
 }
 
+#[derive(Copy, Clone, PartialEq)]
 enum Heuristic {
     Euclid,
     Manhattan,
     Exact_8,
+    // the better of Euclid and the landmark bound
+    Landmarks,
+    // exact on an empty grid for the current move set
+    Neighborhood,
 }
 
 #[derive(Debug)]
@@ -92,10 +192,16 @@ impl PartialEq for AStarRecord
     }
 }
 
+struct FlowAgent
+{
+    pos: Vec2f,
+}
+
 pub struct SquareNavScreen
 {
     time_elapsed: f32,
     is_complete_flag: bool,
+    is_loaded_flag: bool,
     points: Vec<Vec2f>,
 
     num_x: i32,
@@ -116,11 +222,104 @@ pub struct SquareNavScreen
 
     prev_index: HashMap<i32, i32>,
 
-    is_8_way: bool,
+    connectivity: Connectivity,
+    corner_cutting: CornerCutting,
+    moves: Vec<GridMove>,
 
     heuristic: Heuristic,
 
     wall_nodes: HashSet<i32>,
+
+    footprint_mode: FootprintMode,
+    // spaces where the footprint would overlap a wall, walls included
+    inflated_nodes: HashSet<i32>,
+    // the walls changed under it; reinflated once painting stops
+    inflation_stale: bool,
+
+    bidir_result: Option<BidirectionalResult>,
+    bidir_label: String,
+    // plain A* over the same start/end, to compare expansion counts
+    bidir_baseline: Option<SearchResult>,
+    // plain Dijkstra as well, after bidirectional Dijkstra
+    bidir_dijkstra: Option<SearchResult>,
+    bidir_reveal: usize,
+
+    // heuristic inflation for weighted searches, 1.0 is plain A*
+    epsilon: f32,
+
+    anytime_solutions: Vec<BoundedSolution>,
+    anytime_label: String,
+    anytime_shown: usize,
+    anytime_reveal: usize,
+
+    lpa: Option<LpaStar>,
+    lpa_path: Vec<i32>,
+    // everything LPA* touched since the current wall edit began
+    lpa_repaired: HashSet<i32>,
+
+    // distance to end_index for every space, and where to step next
+    flow_integration: Vec<f32>,
+    flow_next: Vec<i32>,
+    flow_agents: Vec<FlowAgent>,
+
+    // (start, goal) pairs for multi-agent planning
+    mapf_agents: Vec<(i32, i32)>,
+    // start placed, waiting on its goal
+    mapf_pending_start: i32,
+    mapf_solution: Option<MapfSolution>,
+    mapf_failed: bool,
+    mapf_time: f32,
+
+    coop_planner: Option<WindowedPlanner>,
+    coop_prev_positions: Vec<i32>,
+    coop_time: f32,
+    // plain cooperative A* instead: whole plans made once, up front
+    coop_windowed: bool,
+    coop_agents: Vec<(i32, i32)>,
+    coop_paths: Vec<Option<Vec<i32>>>,
+
+    steer_agents: Vec<SteeringAgent>,
+    steer_path: Vec<Vec2f>,
+    // the point on steer_path each robot is heading for
+    steer_targets: Vec<Vec2f>,
+
+    clearance: Option<DistanceField>,
+    show_clearance: bool,
+    // clearance aware path, with the plain shortest one to compare
+    clearance_path: Vec<i32>,
+    clearance_plain_path: Vec<i32>,
+    clearance_label: String,
+    // the walls changed under them; redone once painting stops
+    clearance_stale: bool,
+
+    // index into MAZE_KINDS, and the seed of the last generated map
+    maze_kind: usize,
+    maze_seed: Option<u64>,
+
+    hpa: Option<Hpa>,
+    show_hpa: bool,
+    hpa_result: Option<HpaResult>,
+    hpa_label: String,
+    // spaces changed since the clusters were rebuilt; applied once
+    // painting stops
+    hpa_changed: HashSet<i32>,
+
+    landmarks: Option<Landmarks>,
+    landmark_selection: LandmarkSelection,
+    // the walls changed since they were placed; rebuilt once painting stops
+    landmarks_stale: bool,
+
+    // the hierarchy is only good until the walls change
+    ch: Option<ContractionHierarchy>,
+    ch_result: Option<ChResult>,
+    ch_label: String,
+
+    // the current heuristic against true distances to the end, and a
+    // line on how each of them fares
+    heuristic_check: Option<HeuristicReport>,
+    heuristic_summary: Vec<String>,
+    // the map changed under a check on show; rerun once painting stops
+    heuristic_check_stale: bool,
 }
 
 impl SquareNavScreen {
@@ -157,6 +356,7 @@ impl SquareNavScreen {
 
 	SquareNavScreen {
 	    is_complete_flag: false,
+	    is_loaded_flag: false,
 	    time_elapsed: 0.0,
 	    points: point_list,
 	    num_x: num_x,
@@ -170,9 +370,66 @@ impl SquareNavScreen {
 	    found_distances: HashMap::new(),
 	    open_set: HashSet::new(),
 	    prev_index: HashMap::new(),
-	    is_8_way: true,
+	    connectivity: Connectivity::Eight,
+	    corner_cutting: CornerCutting::Allow,
+	    moves: grid_moves(Connectivity::Eight),
 	    heuristic: Heuristic::Euclid,
 	    wall_nodes: HashSet::new(),
+	    footprint_mode: FootprintMode::Point,
+	    inflated_nodes: HashSet::new(),
+	    inflation_stale: false,
+	    bidir_result: None,
+	    bidir_label: String::new(),
+	    bidir_baseline: None,
+	    bidir_dijkstra: None,
+	    bidir_reveal: 0,
+	    epsilon: 1.0,
+	    anytime_solutions: Vec::new(),
+	    anytime_label: String::new(),
+	    anytime_shown: 0,
+	    anytime_reveal: 0,
+	    lpa: None,
+	    lpa_path: Vec::new(),
+	    lpa_repaired: HashSet::new(),
+	    flow_integration: Vec::new(),
+	    flow_next: Vec::new(),
+	    flow_agents: Vec::new(),
+	    mapf_agents: Vec::new(),
+	    mapf_pending_start: -1,
+	    mapf_solution: None,
+	    mapf_failed: false,
+	    mapf_time: 0.0,
+	    coop_planner: None,
+	    coop_prev_positions: Vec::new(),
+	    coop_time: 0.0,
+	    coop_windowed: true,
+	    coop_agents: Vec::new(),
+	    coop_paths: Vec::new(),
+	    steer_agents: Vec::new(),
+	    steer_path: Vec::new(),
+	    steer_targets: Vec::new(),
+	    clearance: None,
+	    show_clearance: false,
+	    clearance_path: Vec::new(),
+	    clearance_plain_path: Vec::new(),
+	    clearance_label: String::new(),
+	    clearance_stale: false,
+	    maze_kind: 0,
+	    maze_seed: None,
+	    hpa: None,
+	    show_hpa: false,
+	    hpa_result: None,
+	    hpa_label: String::new(),
+	    hpa_changed: HashSet::new(),
+	    landmarks: None,
+	    landmark_selection: LandmarkSelection::Farthest,
+	    landmarks_stale: false,
+	    ch: None,
+	    ch_result: None,
+	    ch_label: String::new(),
+	    heuristic_check: None,
+	    heuristic_summary: Vec::new(),
+	    heuristic_check_stale: false,
 	}
     }
 
@@ -187,6 +444,76 @@ impl SquareNavScreen {
 	self.found_distances.clear();
 	self.a_star_nodes.clear();
 	self.wall_nodes.clear();
+	self.footprint_mode = FootprintMode::Point;
+	self.inflated_nodes.clear();
+	self.inflation_stale = false;
+	self.connectivity = Connectivity::Eight;
+	self.corner_cutting = CornerCutting::Allow;
+	self.moves = grid_moves(self.connectivity);
+	self.bidir_result = None;
+	self.bidir_baseline = None;
+	self.bidir_dijkstra = None;
+	self.bidir_reveal = 0;
+	self.anytime_solutions.clear();
+	self.anytime_shown = 0;
+	self.anytime_reveal = 0;
+	self.lpa = None;
+	self.lpa_path.clear();
+	self.lpa_repaired.clear();
+	self.flow_integration.clear();
+	self.flow_next.clear();
+	self.flow_agents.clear();
+	self.mapf_agents.clear();
+	self.mapf_pending_start = -1;
+	self.mapf_solution = None;
+	self.mapf_failed = false;
+	self.coop_planner = None;
+	self.coop_paths.clear();
+	self.steer_agents.clear();
+	self.steer_path.clear();
+	self.steer_targets.clear();
+	self.clearance = None;
+	self.show_clearance = false;
+	self.clearance_path.clear();
+	self.clearance_plain_path.clear();
+	self.clearance_stale = false;
+	self.maze_seed = None;
+	self.hpa = None;
+	self.show_hpa = false;
+	self.hpa_result = None;
+	self.hpa_changed.clear();
+	self.heuristic = Heuristic::Euclid;
+	self.landmarks = None;
+	self.ch = None;
+	self.heuristic_check = None;
+	self.heuristic_summary.clear();
+	self.heuristic_check_stale = false;
+    }
+
+    fn clear_searches(&mut self) {
+	self.prev_index.clear();
+	self.open_set.clear();
+	self.found_distances.clear();
+	self.a_star_nodes.clear();
+	self.bidir_result = None;
+	self.anytime_solutions.clear();
+	self.lpa = None;
+	self.lpa_path.clear();
+	self.lpa_repaired.clear();
+	self.flow_integration.clear();
+	self.flow_next.clear();
+	self.flow_agents.clear();
+	self.mapf_solution = None;
+	self.mapf_failed = false;
+	self.coop_planner = None;
+	self.coop_paths.clear();
+	self.steer_agents.clear();
+	self.steer_path.clear();
+	self.steer_targets.clear();
+	self.clearance_path.clear();
+	self.clearance_plain_path.clear();
+	self.hpa_result = None;
+	self.ch_result = None;
     }
 
     fn point_in_box(&self, p: &Vec2f) -> bool {
@@ -217,75 +544,119 @@ impl SquareNavScreen {
     fn calc_heuristic_by_indices(&self, a:i32, b:i32, si:i32) -> f32 {
 	//(self.points[a as usize] - self.points[b as usize]).mag()
 
+	self.calc_heuristic_of(self.heuristic, a, b, si)
+    }
 
-	match(self.heuristic) {
+    fn calc_heuristic_of(&self, heuristic: Heuristic, a:i32, b:i32, si:i32) -> f32 {
+	match heuristic {
 	    Heuristic::Euclid => {self.calc_euclid_heuristic_by_indices(a,b, si)},
 	    Heuristic::Manhattan => {self.calc_manhattan_heuristic_by_indices(a,b,si)},
 	    Heuristic::Exact_8 => {self.calc_exact_8_heuristic_by_indices(a,b,si)},
+	    Heuristic::Landmarks => {self.calc_landmark_heuristic_by_indices(a,b,si)},
+	    Heuristic::Neighborhood => {self.calc_neighborhood_heuristic_by_indices(a,b,si)},
 	}
     }
 
-    fn calc_euclid_heuristic_by_indices(&self, a:i32, b:i32, si: i32) -> f32 {
-	let ap = self.points[a as usize];
-	let bp = self.points[b as usize];
-
-	(bp - ap).mag()
+    fn heuristic_name(&self, heuristic: Heuristic) -> String {
+	match heuristic {
+	    Heuristic::Euclid => "euclid".to_string(),
+	    Heuristic::Manhattan => "manhattan".to_string(),
+	    Heuristic::Exact_8 => "exact 8".to_string(),
+	    Heuristic::Landmarks => format!("landmarks ({})", self.landmark_selection.name()),
+	    Heuristic::Neighborhood => format!("neighborhood {}", self.connectivity.count()),
+	}
     }
 
+    // every heuristic against Dijkstra distances to the end, keeping
+    // the full report for the one in use
+    fn run_heuristic_check(&mut self) {
+	self.heuristic_check = None;
+	self.heuristic_summary.clear();
+	self.heuristic_check_stale = false;
 
-    fn calc_manhattan_heuristic_by_indices(&self, a:i32, b:i32, si: i32) -> f32 {
-	let ap = self.points[a as usize];
-	let bp = self.points[b as usize];
-	let start_point = self.points[si as usize];
-
-	let ax = ap.x;
-	let ay = ap.y;
-	let bx = bp.x;
-	let by = bp.y;
-	let sx = start_point.x;
-	let sy = start_point.y;
+	if self.end_index < 0 || self.is_blocked(self.end_index) {
+	    self.heuristic_summary.push("heuristic check: needs an open end".to_string());
+	    return;
+	}
 
-	// Amit wins again
-	// http://theory.stanford.edu/~amitp/GameProgramming/Heuristics.html#breaking-ties
+	// the tie breakers lean on the start, so check from this one
+	let si = if self.start_index >= 0 { self.start_index } else { self.end_index };
 
-	let dx1 = ax - bx;
-	let dy1 = ay - by;
-	let dx2 = sx - bx;
-	let dy2 = sy - by;
-	let cross = (dx1*dy2 - dx2*dy1).abs();
+	for kind in HEURISTICS {
+	    if kind == Heuristic::Landmarks && self.landmarks.is_none() {
+		continue;
+	    }
 
-	let dx = (bx-ax).abs();
-	let dy = (by-ay).abs();
+	    let report = check_heuristic(self, self.end_index,
+					 |a, b| self.calc_heuristic_of(kind, a, b, si));
+
+	    let verdict = match (report.admissible(), report.consistent()) {
+		(true, true) => "ok",
+		(true, false) => "admissible, inconsistent",
+		(false, _) => "OVERESTIMATES",
+	    };
+	    let line = format!("{} [{}]: h/h* mean {:.3} max {:.3}, {} over (worst {:.2}), {} inconsistent (worst {:.2})",
+			       self.heuristic_name(kind),
+			       verdict,
+			       report.mean_ratio,
+			       report.max_ratio(),
+			       report.overestimates.len(),
+			       report.worst_overestimate / self.space_width,
+			       report.inconsistent_edges.len(),
+			       report.worst_inconsistency / self.space_width);
+	    self.heuristic_summary.push(line);
+
+	    if kind == self.heuristic {
+		self.heuristic_check = Some(report);
+	    }
+	}
+    }
 
-	if (!self.is_8_way) {
-	    return dx + dy + cross * 0.01;
+    fn refresh_heuristic_check(&mut self) {
+	if !self.heuristic_summary.is_empty() {
+	    self.heuristic_check_stale = true;
 	}
+    }
 
-	let mut max_dim:f32 = 0.0;
-	let mut min_dim:f32 = 0.0;
+    fn next_heuristic(&mut self) {
+	let i = HEURISTICS.iter().position(|h| *h == self.heuristic).unwrap_or(0);
+	self.heuristic = HEURISTICS[(i + 1) % HEURISTICS.len()];
 
-	if dx < dy {
-	    max_dim = dy;
-	    min_dim = dx;
+	if self.heuristic == Heuristic::Landmarks {
+	    self.build_landmarks();
 	} else {
-	    max_dim = dx;
-	    min_dim = dy;
+	    self.landmarks = None;
+	    self.update_lpa_keys();
 	}
+	println!("heuristic {}", self.heuristic_name(self.heuristic));
+    }
 
-	if (min_dim < 1.0) {
-	    return max_dim;
-	}
+    fn calc_neighborhood_heuristic_by_indices(&self, a:i32, b:i32, _si: i32) -> f32 {
+	let d = self.points[b as usize] - self.points[a as usize];
 
-	let straight_leg = max_dim - min_dim;
-	let diag_leg = min_dim * SQRT_2;
+	free_distance(self.connectivity, d.x / self.space_width, d.y / self.space_width) *
+	    self.space_width
+    }
+
+    fn calc_landmark_heuristic_by_indices(&self, a:i32, b:i32, si: i32) -> f32 {
+	let euclid = self.calc_euclid_heuristic_by_indices(a, b, si);
+
+	// distances through walls since taken away can overestimate
+	match &self.landmarks {
+	    Some(landmarks) if !self.landmarks_stale => euclid.max(landmarks.estimate(a, b)),
+	    _ => euclid,
+	}
+    }
 
-	let flat_cheat = 1.0 + 1.0/1000.0;
+    fn calc_euclid_heuristic_by_indices(&self, a:i32, b:i32, si: i32) -> f32 {
+	let ap = self.points[a as usize];
+	let bp = self.points[b as usize];
 
-	(straight_leg + diag_leg) * flat_cheat //+ cross*0.01
+	(bp - ap).mag()
     }
 
 
-    fn calc_exact_8_heuristic_by_indices(&self, a:i32, b:i32, si: i32) -> f32 {
+    fn calc_manhattan_heuristic_by_indices(&self, a:i32, b:i32, si: i32) -> f32 {
 	let ap = self.points[a as usize];
 	let bp = self.points[b as usize];
 	let start_point = self.points[si as usize];
@@ -309,29 +680,28 @@ impl SquareNavScreen {
 	let dx = (bx-ax).abs();
 	let dy = (by-ay).abs();
 
-	let mut max_dim:f32 = 0.0;
-	let mut min_dim:f32 = 0.0;
-
-	if dx < dy {
-	    max_dim = dy;
-	    min_dim = dx;
-	} else {
-	    max_dim = dx;
-	    min_dim = dy;
+	if (self.connectivity == Connectivity::Four) {
+	    return dx + dy + cross * 0.01;
 	}
 
-	if (min_dim < 1.0) {
-	    return max_dim;
-	}
+	// octile distance would overestimate knight and longer moves
+	self.calc_neighborhood_heuristic_by_indices(a, b, si)
+    }
 
-	let straight_leg = max_dim - min_dim;
-	let diag_leg = min_dim * SQRT_2;
 
-	let flat_cheat = 1.0 + 1.0/1000.0;
+    // octile distance, which is exact for 8 neighbors and still a lower
+    // bound for 4; with more neighbors, exact for the moves in use
+    fn calc_exact_8_heuristic_by_indices(&self, a:i32, b:i32, _si: i32) -> f32 {
+	let connectivity = match self.connectivity {
+	    Connectivity::Four => Connectivity::Eight,
+	    c => c,
+	};
+	let d = self.points[b as usize] - self.points[a as usize];
 
-	(straight_leg + diag_leg) * flat_cheat //+ cross*0.01
+	free_distance(connectivity, d.x / self.space_width, d.y / self.space_width) *
+	    self.space_width
     }
-    
+
 
 
     fn get_neighbor_space_indices(&self, i:i32) -> Vec<i32> {
@@ -340,216 +710,1739 @@ impl SquareNavScreen {
 	let x = i % self.num_x;
 	let y = (i - x) / self.num_x;
 
-	if (x > 0) {
-	    out_vec.push(self.space_coord_to_index(x-1, y));
-	}
-	if (x < self.num_x - 1) {
-	    out_vec.push(self.space_coord_to_index(x+1, y));
+	// off the grid counts as a wall for what a move passes
+	let blocked = |bx: i32, by: i32| {
+	    bx < 0 || by < 0 || bx >= self.num_x || by >= self.num_y ||
+		self.is_blocked(self.space_coord_to_index(bx, by))
+	};
+
+	for m in &self.moves {
+	    let nx = x + m.dx;
+	    let ny = y + m.dy;
+	    if nx < 0 || ny < 0 || nx >= self.num_x || ny >= self.num_y {
+		continue;
+	    }
+
+	    if m.allowed(x, y, self.corner_cutting, blocked) {
+		out_vec.push(self.space_coord_to_index(nx, ny));
+	    }
 	}
 
-	if (y > 0) {
-	    out_vec.push(self.space_coord_to_index(x, y-1));
+	out_vec
+    }
+
+    // the move set changed, so every edge may have
+    fn set_neighborhood(&mut self, connectivity: Connectivity, corner_cutting: CornerCutting) {
+	self.connectivity = connectivity;
+	self.corner_cutting = corner_cutting;
+	self.moves = grid_moves(connectivity);
+	println!("{} neighbors, corner cutting {}", connectivity.count(), corner_cutting.name());
+
+	self.clear_searches();
+	self.sub_mode = SubMode::AddPoints;
+	self.ch = None;
+	if self.hpa.is_some() {
+	    self.build_hpa();
 	}
-	if (y < self.num_y - 1) {
-	    out_vec.push(self.space_coord_to_index(x, y+1));
+	if self.landmarks.is_some() {
+	    self.build_landmarks();
 	}
+	self.refresh_heuristic_check();
+    }
 
-	if (self.is_8_way) {
-	    if (x > 0 && y > 0) {
-		out_vec.push(self.space_coord_to_index(x-1, y-1));
-	    }
-
-	    if (x < self.num_x - 1 && y > 0)  {
-		out_vec.push(self.space_coord_to_index(x+1, y-1));
-	    }
+    // spaces whose moves can pass through or beside the changed ones
+    fn spaces_affected_by(&self, changed: &[i32]) -> Vec<i32> {
+	let reach = move_reach(&self.moves, self.corner_cutting);
+	if reach == 0 {
+	    return changed.to_vec();
+	}
 
-	    if (x > 0 && y < self.num_y - 1) {
-		out_vec.push(self.space_coord_to_index(x-1, y+1));
+	let mut out = HashSet::<i32>::new();
+	for i in changed {
+	    let x = i % self.num_x;
+	    let y = i / self.num_x;
+	    for ny in (y - reach).max(0) ..= (y + reach).min(self.num_y - 1) {
+		for nx in (x - reach).max(0) ..= (x + reach).min(self.num_x - 1) {
+		    out.insert(self.space_coord_to_index(nx, ny));
+		}
 	    }
+	}
+	out.into_iter().collect()
+    }
 
-	    if (x < self.num_x - 1 && y < self.num_y - 1) {
-		out_vec.push(self.space_coord_to_index(x+1, y+1));
-	    }
+    fn footprint(&self) -> Option<Footprint> {
+	match self.footprint_mode {
+	    FootprintMode::Point => None,
+	    FootprintMode::Circle => Some(Footprint::Circle(self.space_width * STEER_ROBOT_SIZE * 0.5)),
+	    FootprintMode::Cart => Some(Footprint::rectangle(self.space_width * 2.5,
+							     self.space_width * 1.0)),
 	}
+    }
 
-	out_vec
+    // walls, or too close to one for the footprint
+    fn is_blocked(&self, i: i32) -> bool {
+	self.wall_nodes.contains(&i) || self.inflated_nodes.contains(&i)
+    }
+
+    // rebuild the configuration space; returns the spaces that changed
+    fn update_inflation(&mut self) -> Vec<i32> {
+	let inflated = match self.footprint() {
+	    None => HashSet::new(),
+	    Some(f) => inflate_grid(self.num_x, self.num_y, self.space_width,
+				    &self.wall_nodes, &f),
+	};
+
+	let changed: Vec<i32> = inflated.symmetric_difference(&self.inflated_nodes)
+	    .cloned()
+	    .collect();
+
+	self.inflated_nodes = inflated;
+	self.inflation_stale = false;
+	changed
     }
 
     fn space_coord_to_index(&self, x: i32, y: i32) -> i32 {
 	x + y * self.num_x
     }
 
-    fn advance_a_star(&mut self) {
-	if self.found_distances.contains_key(&self.end_index) {
+    // which space p falls in, without searching every point
+    fn space_index_at(&self, p: &Vec2f) -> i32 {
+	let x = ((p.x / self.space_width).floor() as i32).clamp(0, self.num_x - 1);
+	let y = ((p.y / self.space_width).floor() as i32).clamp(0, self.num_y - 1);
+	self.space_coord_to_index(x, y)
+    }
+
+    fn start_bidirectional(&mut self, use_heuristic: bool) {
+	self.clear_searches();
+
+	// si == a zeroes the cross product tie breaker, which would
+	// otherwise make the two directions disagree
+	let h = |a: i32, b: i32| self.calc_heuristic_by_indices(a, b, a);
+
+	let result = if use_heuristic {
+	    bidirectional_a_star(self, self.start_index, self.end_index, h)
+	} else {
+	    bidirectional_dijkstra(self, self.start_index, self.end_index)
+	};
+	let baseline = a_star(self, self.start_index, self.end_index, h);
+	let dijkstra_baseline = if use_heuristic {
+	    None
+	} else {
+	    Some(dijkstra(self, self.start_index, self.end_index))
+	};
+
+	self.bidir_label = if use_heuristic {
+	    "bidirectional A*".to_string()
+	} else {
+	    "bidirectional Dijkstra".to_string()
+	};
+	self.bidir_result = Some(result);
+	self.bidir_baseline = Some(baseline);
+	self.bidir_dijkstra = dijkstra_baseline;
+	self.bidir_reveal = 0;
+	self.sub_mode = SubMode::FindBidirectional;
+    }
+
+    fn advance_bidirectional(&mut self) {
+	if let Some(result) = &self.bidir_result {
+	    if self.bidir_reveal < result.expanded.len() {
+		self.bidir_reveal += 1;
+	    } else {
+		self.sub_mode = SubMode::Show;
+	    }
+	}
+    }
+
+    fn start_anytime(&mut self, key: KeyCode) {
+	self.clear_searches();
+
+	let h = |a: i32, b: i32| self.calc_heuristic_by_indices(a, b, a);
+
+	let (label, solutions) = match key {
+	    KeyCode::Q => {
+		("ARA*", ara_star(self, self.start_index, self.end_index, h,
+				  self.epsilon, EPSILON_STEP))
+	    }
+	    KeyCode::O => {
+		("focal", vec![focal_search(self, self.start_index, self.end_index, h,
+					   self.epsilon)])
+	    }
+	    _ => {
+		("weighted A*", vec![weighted_a_star(self, self.start_index, self.end_index, h,
+						     self.epsilon)])
+	    }
+	};
+
+	self.anytime_label = label.to_string();
+	self.anytime_solutions = solutions;
+	self.anytime_shown = 0;
+	self.anytime_reveal = 0;
+	self.sub_mode = SubMode::FindAnytime;
+    }
+
+    fn advance_anytime(&mut self) {
+	if self.anytime_shown >= self.anytime_solutions.len() {
 	    self.sub_mode = SubMode::Show;
 	    return;
 	}
 
-	match self.a_star_nodes.pop() {
-	    None => {
-		// first step; push start on
-		println!("starting search");
+	if self.anytime_reveal < self.anytime_solutions[self.anytime_shown].expanded.len() {
+	    self.anytime_reveal += 1;
+	} else if self.anytime_shown + 1 < self.anytime_solutions.len() {
+	    self.anytime_shown += 1;
+	    self.anytime_reveal = 0;
+	} else {
+	    self.sub_mode = SubMode::Show;
+	}
+    }
 
-		let h = self.calc_heuristic_by_indices(
-			self.start_index, self.end_index, self.start_index);
+    fn start_lpa(&mut self) {
+	self.clear_searches();
 
-		self.a_star_nodes.push(AStarRecord {
-		    combined_distances: h,
-		    distance_travelled: 0.0,
-		    heuristic_remaining: h,
-		    point: self.points[self.start_index as usize],
-		    index: self.start_index,
-		});
+	let h = |a: i32, b: i32| self.calc_heuristic_by_indices(a, b, a);
 
-		self.found_distances.insert(self.start_index, 0.0);
-		self.open_set.insert(self.start_index);
-		self.prev_index.insert(self.start_index, -1);
-	    }
-	    Some(n) => {
-		let neighbor_space_list = self.get_neighbor_space_indices(n.index);
+	let mut lpa = LpaStar::new(self.start_index, self.end_index);
+	lpa.compute_shortest_path(self, &h);
 
-		for neighbor_index in neighbor_space_list {
-		    if self.wall_nodes.contains(&neighbor_index) {
-			continue;
-		    }
-		    let neighbor_point = self.points[neighbor_index as usize];
-		    let this_step_dist = (neighbor_point - n.point).mag();
-		    let new_elapsed_dist = n.distance_travelled + this_step_dist;
+	self.lpa_path = lpa.path(self);
+	self.lpa_repaired = lpa.expanded.iter().cloned().collect();
+	self.lpa = Some(lpa);
+	self.sub_mode = SubMode::Show;
+    }
 
-		    let mut insert_node = true;
+    // incremental repair after the walls in changed were toggled
+    fn repair_lpa(&mut self, changed: &[i32]) {
+	let mut lpa = match self.lpa.take() {
+	    None => { return; }
+	    Some(lpa) => lpa,
+	};
 
-		    let neighbor_index_i32 = neighbor_index as i32;
-		    if self.found_distances.contains_key(&neighbor_index_i32) {
-			insert_node = self.found_distances[&neighbor_index_i32] > new_elapsed_dist;
-		    }
+	let h = |a: i32, b: i32| self.calc_heuristic_by_indices(a, b, a);
 
-		    if insert_node {
-			let new_h = self.calc_heuristic_by_indices(
-			    neighbor_index_i32, self.end_index, self.start_index);
+	for idx in changed {
+	    lpa.node_changed(self, &h, *idx);
+	}
+	lpa.compute_shortest_path(self, &h);
 
-			self.a_star_nodes.push(AStarRecord {
-			    combined_distances: new_h + new_elapsed_dist,
-			    distance_travelled: new_elapsed_dist,
-			    heuristic_remaining: new_h,
-			    point: neighbor_point,
-			    index: neighbor_index_i32,
-			});
-			self.found_distances.insert(neighbor_index_i32,
-						    new_elapsed_dist);
-			self.open_set.insert(neighbor_index_i32);
-			self.prev_index.insert(neighbor_index_i32, n.index);
-		    }
-		}
-	    }
+	self.lpa_path = lpa.path(self);
+	for idx in &lpa.expanded {
+	    self.lpa_repaired.insert(*idx);
 	}
-    }
-}
 
-#[async_trait]
-impl Screen for SquareNavScreen {
-    fn is_loaded(&self) -> bool {
-	true
+	self.lpa = Some(lpa);
     }
 
-    async fn load(&mut self, _tex_mgr: &mut TextureMgr) {
+    fn build_flow_field(&mut self) {
+	self.flow_integration = integration_field(self, self.end_index);
+	self.flow_next = flow_field(self, &self.flow_integration);
     }
 
-    fn tick(&mut self, dt: f32) {
-	self.time_elapsed += dt;
+    fn start_flow_field(&mut self) {
+	self.clear_searches();
+	self.build_flow_field();
 
-	if is_key_down(KeyCode::Escape) {
-	    self.is_complete_flag = true;
+	// scatter the crowd over every space that can reach the goal
+	let reachable: Vec<usize> = (0 .. self.points.len())
+	    .filter(|i| self.flow_next[*i] >= 0)
+	    .collect();
+
+	if reachable.is_empty() {
 	    return;
 	}
 
-	if is_key_down(KeyCode::S) {
-	    let m_pos:Vec2 = mouse_position().into();
-	    let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
-	    let idx = self.find_index(&mouse_pos_vec);
-	    self.start_index = idx;
+	for _ in 0 .. FLOW_AGENT_COUNT {
+	    let i = reachable[gen_range::<usize>(0, reachable.len())];
+	    let jitter = Vec2f::new(gen_range::<f32>(-0.3, 0.3),
+				    gen_range::<f32>(-0.3, 0.3));
+	    self.flow_agents.push(FlowAgent {
+		pos: self.points[i] + jitter.scale(self.space_width),
+	    });
 	}
 
-	if is_key_down(KeyCode::E) {
-	    let m_pos:Vec2 = mouse_position().into();
-	    let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
-	    let idx = self.find_index(&mouse_pos_vec);
-	    self.end_index = idx;
-	}
+	self.sub_mode = SubMode::Show;
+    }
 
-	if is_key_down(KeyCode::R) {
-	    self.reset();
-	}
+    fn advance_flow_agents(&mut self, dt: f32) {
+	let step = FLOW_AGENT_SPEED * self.space_width * dt;
 
-	if is_key_pressed(KeyCode::W) {
-	    println!("W");
-	    let m_pos:Vec2 = mouse_position().into();
-	    let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
-	    let idx = self.find_index(&mouse_pos_vec);
+	let mut remaining = Vec::<FlowAgent>::new();
 
-	    if self.wall_nodes.contains(&idx) {
-		self.wall_nodes.remove(&idx);
-	    } else {
-		self.wall_nodes.insert(idx);
+	for agent in &self.flow_agents {
+	    let here = self.space_index_at(&agent.pos);
+	    let next = self.flow_next[here as usize];
+
+	    if here == self.end_index || next < 0 {
+		// arrived, or stranded by a new wall
+		continue;
 	    }
+
+	    // head for the next space's center so we stay on the grid
+	    let delta = self.points[next as usize] - agent.pos;
+	    let dist = delta.mag();
+	    let pos = if dist <= step {
+		self.points[next as usize]
+	    } else {
+		agent.pos + delta.scale(step / dist)
+	    };
+
+	    remaining.push(FlowAgent {
+		pos: pos,
+	    });
 	}
 
-	if is_mouse_button_down(MouseButton::Left) {
+	self.flow_agents = remaining;
+    }
 
-	    let paint_radius = self.space_width * 1.5;
+    fn place_mapf_endpoint(&mut self, idx: i32) {
+	if self.is_blocked(idx) {
+	    return;
+	}
 
-	    let m_pos:Vec2 = mouse_position().into();
-	    let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
-	    for idx in 0 .. self.points.len() {
-		let p = &self.points[idx];
+	if self.mapf_pending_start < 0 {
+	    self.mapf_pending_start = idx;
+	} else {
+	    self.mapf_agents.push((self.mapf_pending_start, idx));
+	    self.mapf_pending_start = -1;
+	}
+	self.mapf_solution = None;
+	self.mapf_failed = false;
+    }
 
-		let dist = (mouse_pos_vec - *p).mag();
-		if dist < paint_radius {
-		    self.wall_nodes.insert(idx as i32);
-		}
+    // the open space closest to a point
+    fn nearest_open_index(&self, p: Vec2f) -> i32 {
+	let mut best = -1;
+	let mut best_dist = f32::INFINITY;
+	for i in 0 .. self.points.len() as i32 {
+	    if self.is_blocked(i) {
+		continue;
+	    }
+	    let dist = (self.points[i as usize] - p).mag();
+	    if dist < best_dist {
+		best = i;
+		best_dist = dist;
 	    }
 	}
+	best
+    }
 
-	if is_mouse_button_down(MouseButton::Right) {
-	    let m_pos:Vec2 = mouse_position().into();
-	    let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
-	    let idx = self.find_index(&mouse_pos_vec);
+    // replace the walls with a generated maze or cave map
+    fn generate_map(&mut self, seed: u64) {
+	let kind = MAZE_KINDS[self.maze_kind];
+	println!("generating {} with seed {}", kind.name(), seed);
 
-	    self.wall_nodes.remove(&idx);
-	}
+	self.clear_searches();
+	self.clearance_plain_path.clear();
+	self.sub_mode = SubMode::AddPoints;
+	self.wall_nodes = generate_maze(kind, self.num_x, self.num_y, seed);
+	self.maze_seed = Some(seed);
+	self.update_inflation();
 
-	if is_key_down(KeyCode::F) {
-	    self.sub_mode = SubMode::FindPath;
+	if self.clearance.is_some() {
+	    self.build_clearance_field();
 	}
-
-	if self.sub_mode == SubMode::FindPath {
-	    self.advance_a_star();
+	if self.hpa.is_some() {
+	    self.build_hpa();
 	}
 
-	if self.sub_mode == SubMode::AddPoints {
-	    self.start_index = gen_range::<i32>(0, self.points.len() as i32);
-	    self.end_index = gen_range::<i32>(0, self.points.len() as i32);
-	    println!("start {} end {}", self.start_index, self.end_index);
+	// start and end in opposite corners
+	let far = Vec2f::new(self.num_x as f32 * self.space_width,
+			     self.num_y as f32 * self.space_width);
+	self.start_index = self.nearest_open_index(Vec2f::new(0.0, 0.0));
+	self.end_index = self.nearest_open_index(far);
 
-	    self.sub_mode = SubMode::Show;
+	if self.landmarks.is_some() {
+	    self.build_landmarks();
 	}
+	self.ch = None;
+	self.refresh_heuristic_check();
     }
 
-    fn is_complete(&self) -> bool {
-	self.is_complete_flag
+    fn random_open_index(&self) -> i32 {
+	loop {
+	    let i = gen_range::<i32>(0, self.points.len() as i32);
+	    if !self.is_blocked(i) {
+		return i;
+	    }
+	}
     }
 
-    fn render(&self, _tex_mgr: &TextureMgr) {
-	clear_background(GRAY);
+    // distinct starts and goals on open spaces
+    fn random_agent_pairs(&self, count: usize) -> Vec<(i32, i32)> {
+	let mut out_vec = Vec::<(i32, i32)>::new();
+	let mut used = HashSet::<i32>::new();
 
-	let mut dot_size = 2.5;
+	let open_count = (0 .. self.points.len() as i32)
+	    .filter(|i| !self.is_blocked(*i))
+	    .count();
+	let count = count.min(open_count / 2);
 
-	if self.points.len() > 0 {
-	    for i in 0 .. self.points.len() {
-		let p = &self.points[i];
+	while out_vec.len() < count {
+	    let start = self.random_open_index();
+	    let goal = self.random_open_index();
+	    if used.contains(&start) || used.contains(&goal) || start == goal {
+		continue;
+	    }
+	    used.insert(start);
+	    used.insert(goal);
+	    out_vec.push((start, goal));
+	}
 
-		let mut c = BLUE;
+	out_vec
+    }
 
-		if i as i32 == self.start_index {
+    fn start_mapf(&mut self) {
+	self.clear_searches();
+	self.mapf_pending_start = -1;
+
+	if self.mapf_agents.is_empty() {
+	    self.mapf_agents = self.random_agent_pairs(MAPF_RANDOM_AGENTS);
+	}
+
+	self.mapf_solution = conflict_based_search(self, &self.mapf_agents);
+	self.mapf_failed = self.mapf_solution.is_none();
+	self.mapf_time = 0.0;
+	self.sub_mode = SubMode::Show;
+    }
+
+    fn start_cooperative(&mut self) {
+	self.clear_searches();
+	self.mapf_pending_start = -1;
+
+	// hand placed agents if there are any, otherwise a crowd
+	let agents = if self.mapf_agents.is_empty() {
+	    self.random_agent_pairs(COOP_RANDOM_AGENTS)
+	} else {
+	    self.mapf_agents.clone()
+	};
+
+	if self.coop_windowed {
+	    let planner = WindowedPlanner::new(self, &agents, COOP_WINDOW, COOP_REPLAN_INTERVAL);
+	    self.coop_prev_positions = planner.positions.clone();
+	    self.coop_planner = Some(planner);
+	} else {
+	    self.coop_paths = cooperative_a_star(self, &agents);
+	    self.coop_agents = agents;
+	}
+	self.coop_time = 0.0;
+	self.sub_mode = SubMode::Show;
+    }
+
+    fn advance_cooperative(&mut self, dt: f32) {
+	// plain cooperative A* just plays its plans back
+	if !self.coop_paths.is_empty() {
+	    self.coop_time += dt * MAPF_STEPS_PER_SECOND;
+	    return;
+	}
+
+	let mut planner = match self.coop_planner.take() {
+	    None => { return; }
+	    Some(p) => p,
+	};
+
+	self.coop_time += dt * MAPF_STEPS_PER_SECOND;
+	while self.coop_time >= 1.0 {
+	    self.coop_time -= 1.0;
+	    self.coop_prev_positions = planner.positions.clone();
+	    if !planner.is_finished() {
+		planner.step(self);
+	    }
+	}
+
+	self.coop_planner = Some(planner);
+    }
+
+    fn build_clearance_field(&mut self) {
+	self.clearance = Some(DistanceField::from_grid(self.num_x, self.num_y,
+						       self.space_width, &self.wall_nodes));
+    }
+
+    // plain A*, then again with edges near walls costing more
+    fn run_clearance_search(&mut self) {
+	self.clearance_path.clear();
+	self.clearance_plain_path.clear();
+
+	if self.start_index < 0 || self.end_index < 0 {
+	    return;
+	}
+
+	if self.clearance.is_none() {
+	    self.build_clearance_field();
+	}
+
+	let h = |a: i32, b: i32| self.calc_heuristic_by_indices(a, b, a);
+	let plain = a_star(self, self.start_index, self.end_index, &h);
+
+	let field = self.clearance.as_ref().unwrap();
+	let costed = ClearanceCost {
+	    graph: self,
+	    clearance: &field.distances,
+	    safe_distance: CLEARANCE_SAFE_DISTANCE * self.space_width,
+	    weight: CLEARANCE_WEIGHT,
+	};
+	let result = a_star(&costed, self.start_index, self.end_index, &h);
+
+	// in spaces: how long each path is, and how close it gets to a wall
+	let length = |path: &Vec<i32>| path.windows(2)
+	    .map(|w| (self.points[w[1] as usize] - self.points[w[0] as usize]).mag())
+	    .sum::<f32>() / self.space_width;
+	let closest = |path: &Vec<i32>| path.iter()
+	    .map(|i| field.get(*i) / self.space_width)
+	    .fold(f32::INFINITY, f32::min);
+
+	let label = if result.found() {
+	    format!("clearance: length {:.1} vs {:.1}, closest wall {:.1} vs {:.1} spaces",
+		    length(&result.path),
+		    length(&plain.path),
+		    closest(&result.path),
+		    closest(&plain.path))
+	} else {
+	    "clearance: no path".to_string()
+	};
+
+	self.clearance_label = label;
+	self.clearance_path = result.path;
+	self.clearance_plain_path = plain.path;
+    }
+
+    fn start_clearance_search(&mut self) {
+	self.clear_searches();
+	self.build_clearance_field();
+	self.run_clearance_search();
+	self.sub_mode = SubMode::Show;
+    }
+
+    // landmarks go in the start's part of the map, or the first open
+    // space's if there's no start yet
+    fn build_landmarks(&mut self) {
+	let first = if self.start_index >= 0 && !self.is_blocked(self.start_index) {
+	    self.start_index
+	} else {
+	    self.nearest_open_index(Vec2f::new(0.0, 0.0))
+	};
+
+	let seed = gen_range::<u32>(0, u32::MAX) as u64;
+	let landmarks = Landmarks::new(self, LANDMARK_COUNT, self.landmark_selection, first, seed);
+	println!("{} landmarks by {}: {:?}",
+		 landmarks.nodes.len(), landmarks.selection.name(), landmarks.nodes);
+	self.landmarks = Some(landmarks);
+	self.landmarks_stale = false;
+	self.update_lpa_keys();
+    }
+
+    // the heuristic changed under LPA*'s queue
+    fn update_lpa_keys(&mut self) {
+	let mut lpa = match self.lpa.take() {
+	    None => { return; }
+	    Some(lpa) => lpa,
+	};
+
+	let h = |a: i32, b: i32| self.calc_heuristic_by_indices(a, b, a);
+	lpa.update_keys(&h);
+
+	self.lpa = Some(lpa);
+    }
+
+    // Euclid, then landmarks placed farthest, then avoid, then back
+    fn next_landmark_heuristic(&mut self) {
+	match (&self.heuristic, self.landmark_selection) {
+	    (Heuristic::Landmarks, LandmarkSelection::Farthest) => {
+		self.landmark_selection = LandmarkSelection::Avoid;
+	    },
+	    (Heuristic::Landmarks, LandmarkSelection::Avoid) => {
+		self.heuristic = Heuristic::Euclid;
+		self.landmarks = None;
+		self.update_lpa_keys();
+		return;
+	    },
+	    _ => {
+		self.heuristic = Heuristic::Landmarks;
+		self.landmark_selection = LandmarkSelection::Farthest;
+	    },
+	}
+	self.build_landmarks();
+    }
+
+    // contract the grid once, then answer start to end from it
+    fn start_ch_search(&mut self) {
+	self.clear_searches();
+
+	if self.start_index < 0 || self.end_index < 0 {
+	    return;
+	}
+
+	let mut build_ms = 0.0;
+	if self.ch.is_none() {
+	    let t = macroquad::time::get_time();
+	    let ch = ContractionHierarchy::new(self);
+	    build_ms = (macroquad::time::get_time() - t) * 1000.0;
+	    println!("contracted in {:.1} ms: {} edges, {} shortcuts",
+		     build_ms, ch.edge_count(), ch.shortcut_count());
+	    self.ch = Some(ch);
+	}
+
+	let ch = self.ch.as_ref().unwrap();
+	let result = ch.find_path(self.start_index, self.end_index);
+
+	let h = |a: i32, b: i32| self.calc_heuristic_by_indices(a, b, a);
+	let plain = a_star(self, self.start_index, self.end_index, &h);
+
+	let built = if build_ms > 0.0 {
+	    format!(", contracted in {:.0} ms", build_ms)
+	} else {
+	    String::new()
+	};
+
+	self.ch_label = if result.found() {
+	    format!("CH: cost {:.1} vs {:.1}, settled {} vs A* {}, {} shortcuts{}",
+		    result.cost / self.space_width,
+		    plain.cost / self.space_width,
+		    result.settled.len(),
+		    plain.expanded.len(),
+		    ch.shortcut_count(),
+		    built)
+	} else {
+	    "CH: no path".to_string()
+	};
+	self.ch_result = Some(result);
+	self.sub_mode = SubMode::Show;
+    }
+
+    fn build_hpa(&mut self) {
+	let hpa = Hpa::new(self, self.num_x, self.num_y, HPA_CLUSTER_SIZE);
+	println!("HPA*: {} clusters, {} abstract nodes, {} edges",
+		 hpa.cluster_count(), hpa.abstract_nodes().len(), hpa.edge_count());
+	self.hpa = Some(hpa);
+	self.hpa_changed.clear();
+    }
+
+    // walls changed: rebuild only the clusters they touch
+    fn update_hpa(&mut self, changed: &[i32]) {
+	let mut hpa = match self.hpa.take() {
+	    None => { return; }
+	    Some(hpa) => hpa,
+	};
+
+	hpa.update(self, changed);
+	self.hpa = Some(hpa);
+
+	if self.hpa_result.is_some() {
+	    self.run_hpa_search();
+	}
+    }
+
+    fn run_hpa_search(&mut self) {
+	let hpa = match &self.hpa {
+	    None => { return; }
+	    Some(hpa) => hpa,
+	};
+
+	let result = hpa.find_path(self, self.start_index, self.end_index);
+
+	// plain A* for comparison
+	let h = |a: i32, b: i32| self.calc_heuristic_by_indices(a, b, a);
+	let plain = a_star(self, self.start_index, self.end_index, &h);
+
+	self.hpa_label = if result.found() {
+	    format!("HPA*: cost {:.1} vs {:.1} (+{:.1}%), expanded {} abstract + {} local vs {}",
+		    result.cost / self.space_width,
+		    plain.cost / self.space_width,
+		    (result.cost / plain.cost - 1.0) * 100.0,
+		    result.abstract_expanded,
+		    result.local_expanded,
+		    plain.expanded.len())
+	} else {
+	    "HPA*: no path".to_string()
+	};
+	self.hpa_result = Some(result);
+    }
+
+    fn start_hpa_search(&mut self) {
+	self.clear_searches();
+
+	if self.start_index < 0 || self.end_index < 0 {
+	    return;
+	}
+
+	if self.hpa.is_none() {
+	    self.build_hpa();
+	}
+	self.run_hpa_search();
+	self.sub_mode = SubMode::Show;
+    }
+
+    fn start_steering(&mut self) {
+	self.clear_searches();
+
+	if self.start_index < 0 || self.end_index < 0 {
+	    return;
+	}
+
+	let result = a_star(self, self.start_index, self.end_index,
+			    |a, b| self.calc_heuristic_by_indices(a, b, a));
+
+	// no path leaves the path empty and the robots wander instead
+	let path: Vec<Vec2f> = result.path.iter()
+	    .map(|i| self.points[*i as usize])
+	    .collect();
+
+	let start = self.points[self.start_index as usize];
+	for _ in 0 .. STEER_ROBOT_COUNT {
+	    let jitter = Vec2f::new(gen_range::<f32>(-0.5, 0.5),
+				    gen_range::<f32>(-0.5, 0.5));
+	    self.steer_agents.push(SteeringAgent::new(start + jitter.scale(self.space_width),
+						      self.space_width * 0.4,
+						      STEER_MAX_SPEED * self.space_width,
+						      STEER_MAX_ACCELERATION * self.space_width));
+	    self.steer_targets.push(start);
+	}
+
+	self.steer_path = path;
+	self.sub_mode = SubMode::Show;
+    }
+
+    fn advance_steering(&mut self, dt: f32) {
+	if self.steer_agents.is_empty() {
+	    return;
+	}
+
+	let w = self.space_width;
+	let positions: Vec<Vec2f> = self.steer_agents.iter().map(|a| a.position).collect();
+	let center = Vec2f::new(screen_width() * 0.5, screen_height() * 0.5);
+
+	for i in 0 .. self.steer_agents.len() {
+	    let agent = &self.steer_agents[i];
+
+	    let walls: Vec<(Vec2f, f32)> = self.wall_nodes.iter()
+		.map(|n| self.points[*n as usize])
+		.filter(|p| (*p - agent.position).mag() < STEER_WALL_RANGE * w)
+		.map(|p| (p, w * 0.5))
+		.collect();
+
+	    let others: Vec<Vec2f> = positions.iter().enumerate()
+		.filter(|(j, _)| *j != i)
+		.map(|(_, p)| *p)
+		.collect();
+
+	    let separation = agent.separation(&others, w * STEER_ROBOT_SIZE);
+	    let avoidance = agent.avoid_obstacles(&walls, 0.5);
+	    let on_screen = self.point_in_box(&agent.position);
+
+	    let agent = &mut self.steer_agents[i];
+	    let travel = if !self.steer_path.is_empty() {
+		let (accel, target) = agent.follow_path(&self.steer_path, 0.2, w * 1.5, w * 3.0);
+		self.steer_targets[i] = target;
+		accel
+	    } else if on_screen {
+		agent.wander(gen_range::<f32>(-1.0, 1.0), 0.3, w * 2.0, w)
+	    } else {
+		agent.seek(center)
+	    };
+
+	    let accel = travel + separation.scale(1.5) + avoidance.scale(2.0);
+	    agent.integrate(accel, dt);
+	}
+    }
+
+    fn advance_a_star(&mut self) {
+	if self.found_distances.contains_key(&self.end_index) {
+	    self.sub_mode = SubMode::Show;
+	    return;
+	}
+
+	match self.a_star_nodes.pop() {
+	    None => {
+		// first step; push start on
+		println!("starting search");
+
+		let h = self.calc_heuristic_by_indices(
+			self.start_index, self.end_index, self.start_index);
+
+		self.a_star_nodes.push(AStarRecord {
+		    combined_distances: h * self.epsilon,
+		    distance_travelled: 0.0,
+		    heuristic_remaining: h,
+		    point: self.points[self.start_index as usize],
+		    index: self.start_index,
+		});
+
+		self.found_distances.insert(self.start_index, 0.0);
+		self.open_set.insert(self.start_index);
+		self.prev_index.insert(self.start_index, -1);
+	    }
+	    Some(n) => {
+		let neighbor_space_list = self.get_neighbor_space_indices(n.index);
+
+		for neighbor_index in neighbor_space_list {
+		    if self.is_blocked(neighbor_index) {
+			continue;
+		    }
+		    let neighbor_point = self.points[neighbor_index as usize];
+		    let this_step_dist = (neighbor_point - n.point).mag();
+		    let new_elapsed_dist = n.distance_travelled + this_step_dist;
+
+		    let mut insert_node = true;
+
+		    let neighbor_index_i32 = neighbor_index as i32;
+		    if self.found_distances.contains_key(&neighbor_index_i32) {
+			insert_node = self.found_distances[&neighbor_index_i32] > new_elapsed_dist;
+		    }
+
+		    if insert_node {
+			let new_h = self.calc_heuristic_by_indices(
+			    neighbor_index_i32, self.end_index, self.start_index);
+
+			self.a_star_nodes.push(AStarRecord {
+			    combined_distances: new_h * self.epsilon + new_elapsed_dist,
+			    distance_travelled: new_elapsed_dist,
+			    heuristic_remaining: new_h,
+			    point: neighbor_point,
+			    index: neighbor_index_i32,
+			});
+			self.found_distances.insert(neighbor_index_i32,
+						    new_elapsed_dist);
+			self.open_set.insert(neighbor_index_i32);
+			self.prev_index.insert(neighbor_index_i32, n.index);
+		    }
+		}
+	    }
+	}
+    }
+}
+
+impl SquareNavScreen {
+    fn render_bidirectional(&self) {
+	let result = match &self.bidir_result {
+	    None => { return; }
+	    Some(r) => r,
+	};
+
+	draw_bidirectional(self, result, self.bidir_reveal, 5.0, YELLOW);
+	if self.bidir_reveal < result.expanded.len() {
+	    return;
+	}
+
+	let mut baselines = Vec::new();
+	if let Some(baseline) = &self.bidir_baseline {
+	    baselines.push(("A*", baseline));
+	}
+	if let Some(baseline) = &self.bidir_dijkstra {
+	    baselines.push(("Dijkstra", baseline));
+	}
+
+	let lines = bidirectional_labels(&self.bidir_label, result, &baselines);
+	for (i, line) in lines.iter().enumerate() {
+	    draw_text(line, 10.0, 20.0 + 24.0 * i as f32, 24.0, WHITE);
+	}
+    }
+}
+
+impl SquareNavScreen {
+    fn render_anytime(&self) {
+	if self.anytime_solutions.is_empty() {
+	    return;
+	}
+
+	let current = &self.anytime_solutions[self.anytime_shown];
+	let finished = self.anytime_reveal >= current.expanded.len();
+
+	for idx in current.expanded.iter().take(self.anytime_reveal) {
+	    let p = self.points[*idx as usize];
+	    draw_circle(p.x, p.y, 5.0, ORANGE);
+	}
+
+	// earlier solutions stay up, thinner, so the improvement shows
+	for si in 0 ..= self.anytime_shown {
+	    if si == self.anytime_shown && !finished {
+		break;
+	    }
+
+	    let solution = &self.anytime_solutions[si];
+	    let (thickness, c) = if si == self.anytime_shown {
+		(3.0, YELLOW)
+	    } else {
+		(1.5, DARKBROWN)
+	    };
+
+	    for i in 1 .. solution.path.len() {
+		let a = self.points[solution.path[i - 1] as usize];
+		let b = self.points[solution.path[i] as usize];
+		draw_line(a.x, a.y, b.x, b.y, thickness, c);
+	    }
+
+	    let label = if solution.found() {
+		format!("{} eps {:.2}: cost {:.1}, within {:.3} of optimal, {} expanded",
+			self.anytime_label,
+			solution.epsilon,
+			solution.cost,
+			solution.bound,
+			solution.expanded.len())
+	    } else {
+		format!("{} eps {:.2}: no path, {} expanded",
+			self.anytime_label,
+			solution.epsilon,
+			solution.expanded.len())
+	    };
+	    draw_text(&label, 10.0, 20.0 + 24.0 * si as f32, 24.0, WHITE);
+	}
+    }
+}
+
+impl SquareNavScreen {
+    fn render_lpa(&self) {
+	let lpa = match &self.lpa {
+	    None => { return; }
+	    Some(lpa) => lpa,
+	};
+
+	for idx in &self.lpa_repaired {
+	    let p = self.points[*idx as usize];
+	    draw_rectangle(p.x - self.space_width * 0.5,
+			   p.y - self.space_width * 0.5,
+			   self.space_width,
+			   self.space_width,
+			   Color::new(0.0, 1.0, 0.0, 0.3));
+	}
+
+	for i in 1 .. self.lpa_path.len() {
+	    let a = self.points[self.lpa_path[i - 1] as usize];
+	    let b = self.points[self.lpa_path[i] as usize];
+	    draw_line(a.x, a.y, b.x, b.y, 3.0, YELLOW);
+	}
+
+	let label = if self.lpa_path.is_empty() {
+	    format!("LPA*: no path, last repair expanded {}", lpa.expanded.len())
+	} else {
+	    format!("LPA*: cost {:.1}, last repair expanded {}",
+		    lpa.g(lpa.end),
+		    lpa.expanded.len())
+	};
+	draw_text(&label, 10.0, 20.0, 24.0, WHITE);
+    }
+}
+
+impl SquareNavScreen {
+    fn render_flow_field(&self) {
+	if self.flow_next.is_empty() {
+	    return;
+	}
+
+	let arrow_len = self.space_width * 0.35;
+	let head_len = self.space_width * 0.15;
+
+	for i in 0 .. self.points.len() {
+	    let dir = flow_direction(self, &self.flow_next, i as i32);
+	    if dir.mag() <= 0.0 {
+		continue;
+	    }
+
+	    let p = self.points[i];
+	    let tail = p - dir.scale(arrow_len);
+	    let tip = p + dir.scale(arrow_len);
+
+	    // two barbs, 45 degrees either side of the shaft
+	    let back = dir.scale(-head_len);
+	    let barb_a = Vec2f::new(back.x - back.y, back.y + back.x).scale(0.7071);
+	    let barb_b = Vec2f::new(back.x + back.y, back.y - back.x).scale(0.7071);
+
+	    draw_line(tail.x, tail.y, tip.x, tip.y, 1.0, DARKBLUE);
+	    draw_line(tip.x, tip.y, tip.x + barb_a.x, tip.y + barb_a.y, 1.0, DARKBLUE);
+	    draw_line(tip.x, tip.y, tip.x + barb_b.x, tip.y + barb_b.y, 1.0, DARKBLUE);
+	}
+
+	for agent in &self.flow_agents {
+	    draw_circle(agent.pos.x, agent.pos.y, self.space_width * 0.3, ORANGE);
+	}
+
+	let label = format!("flow field: {} agents en route", self.flow_agents.len());
+	draw_text(&label, 10.0, 20.0, 24.0, WHITE);
+    }
+}
+
+impl SquareNavScreen {
+    fn render_clearance_heatmap(&self) {
+	if !self.show_clearance {
+	    return;
+	}
+
+	let field = match &self.clearance {
+	    None => { return; }
+	    Some(f) => f,
+	};
+
+	// red beside walls through to blue well clear of them
+	let far = CLEARANCE_SAFE_DISTANCE * 2.0 * self.space_width;
+
+	for i in 0 .. self.points.len() {
+	    let d = field.get(i as i32);
+	    if d <= 0.0 {
+		continue;
+	    }
+
+	    let t = (d / far).min(1.0);
+	    let p = self.points[i];
+	    draw_rectangle(p.x - self.space_width * 0.5,
+			   p.y - self.space_width * 0.5,
+			   self.space_width,
+			   self.space_width,
+			   Color::new(1.0 - t, 0.4 * t, t, 0.5));
+	}
+    }
+
+    // h/h* per space: blue knows nothing, green is exact, red overestimates
+    fn render_heuristic_heatmap(&self) {
+	let report = match &self.heuristic_check {
+	    None => { return; }
+	    Some(report) => report,
+	};
+
+	for i in 0 .. self.points.len() {
+	    let ratio = report.ratios[i];
+	    if ratio.is_nan() {
+		continue;
+	    }
+
+	    let t = ratio.min(1.0);
+	    let p = self.points[i];
+	    draw_rectangle(p.x - self.space_width * 0.5,
+			   p.y - self.space_width * 0.5,
+			   self.space_width,
+			   self.space_width,
+			   Color::new(0.0, t, 1.0 - t, 0.5));
+	}
+
+	for i in &report.overestimates {
+	    let p = self.points[*i as usize];
+	    draw_rectangle(p.x - self.space_width * 0.5,
+			   p.y - self.space_width * 0.5,
+			   self.space_width,
+			   self.space_width,
+			   Color::new(1.0, 0.0, 0.0, 0.7));
+	}
+    }
+
+    fn render_heuristic_check(&self) {
+	if let Some(report) = &self.heuristic_check {
+	    for (a, b) in &report.inconsistent_edges {
+		let pa = self.points[*a as usize];
+		let pb = self.points[*b as usize];
+		draw_line(pa.x, pa.y, pb.x, pb.y, 2.0, MAGENTA);
+	    }
+	}
+
+	// below the status line the searches use
+	for (k, line) in self.heuristic_summary.iter().enumerate() {
+	    draw_text(line, 10.0, 44.0 + 20.0 * k as f32, 20.0, WHITE);
+	}
+    }
+
+    fn render_clearance_paths(&self) {
+	if self.clearance_path.is_empty() && self.clearance_plain_path.is_empty() {
+	    return;
+	}
+
+	for (path, c) in [(&self.clearance_plain_path, WHITE), (&self.clearance_path, GREEN)] {
+	    for i in 1 .. path.len() {
+		let a = self.points[path[i - 1] as usize];
+		let b = self.points[path[i] as usize];
+		draw_line(a.x, a.y, b.x, b.y, 3.0, c);
+	    }
+	}
+
+	draw_text(&self.clearance_label, 10.0, 20.0, 24.0, WHITE);
+    }
+
+    fn render_ch(&self) {
+	let result = match &self.ch_result {
+	    None => { return; }
+	    Some(result) => result,
+	};
+
+	for idx in &result.settled {
+	    let p = self.points[*idx as usize];
+	    draw_circle(p.x, p.y, 5.0, ORANGE);
+	}
+
+	for i in 1 .. result.path.len() {
+	    let a = self.points[result.path[i - 1] as usize];
+	    let b = self.points[result.path[i] as usize];
+	    draw_line(a.x, a.y, b.x, b.y, 3.0, YELLOW);
+	}
+
+	if result.meeting >= 0 {
+	    let p = self.points[result.meeting as usize];
+	    draw_circle_lines(p.x, p.y, 9.0, 3.0, RED);
+	}
+
+	draw_text(&self.ch_label, 10.0, 20.0, 24.0, WHITE);
+    }
+
+    fn render_landmarks(&self) {
+	let landmarks = match &self.landmarks {
+	    None => { return; }
+	    Some(landmarks) => landmarks,
+	};
+
+	// ring the landmark doing the most for the current start and end
+	let best = if self.start_index >= 0 && self.end_index >= 0 {
+	    landmarks.best_landmark(self.start_index, self.end_index)
+	} else {
+	    None
+	};
+
+	for (k, i) in landmarks.nodes.iter().enumerate() {
+	    let p = self.points[*i as usize];
+	    if best == Some(k) {
+		draw_circle_lines(p.x, p.y, 14.0, 3.0, YELLOW);
+	    }
+	    draw_circle(p.x, p.y, 9.0, PURPLE);
+	    draw_text(&k.to_string(), p.x - 4.0, p.y + 5.0, 18.0, WHITE);
+	}
+    }
+
+    fn render_hpa(&self) {
+	let hpa = match &self.hpa {
+	    None => { return; }
+	    Some(hpa) => hpa,
+	};
+
+	if self.show_hpa {
+	    let w = self.space_width;
+
+	    // the clusters the last wall change rebuilt
+	    if (hpa.rebuilt.len() as i32) < hpa.cluster_count() {
+		for c in &hpa.rebuilt {
+		    let (x0, y0, x1, y1) = hpa.cluster_rect(*c);
+		    draw_rectangle(x0 as f32 * w, y0 as f32 * w,
+				   (x1 - x0) as f32 * w, (y1 - y0) as f32 * w,
+				   Color::new(1.0, 1.0, 0.0, 0.15));
+		}
+	    }
+
+	    for c in 0 .. hpa.cluster_count() {
+		let (x0, y0, x1, y1) = hpa.cluster_rect(c);
+		draw_rectangle_lines(x0 as f32 * w, y0 as f32 * w,
+				     (x1 - x0) as f32 * w, (y1 - y0) as f32 * w,
+				     2.0, DARKBLUE);
+	    }
+
+	    for edges in hpa.intra_edges.values() {
+		for e in edges {
+		    let a = self.points[e.from as usize];
+		    let b = self.points[e.to as usize];
+		    draw_line(a.x, a.y, b.x, b.y, 1.0, SKYBLUE);
+		}
+	    }
+
+	    for list in hpa.transitions.values() {
+		for (i, j, _) in list {
+		    let a = self.points[*i as usize];
+		    let b = self.points[*j as usize];
+		    draw_line(a.x, a.y, b.x, b.y, 3.0, MAGENTA);
+		}
+	    }
+
+	    for i in hpa.abstract_nodes() {
+		let p = self.points[i as usize];
+		draw_circle(p.x, p.y, 4.0, MAGENTA);
+	    }
+	}
+
+	let result = match &self.hpa_result {
+	    None => { return; }
+	    Some(result) => result,
+	};
+
+	for i in 1 .. result.abstract_path.len() {
+	    let a = self.points[result.abstract_path[i - 1] as usize];
+	    let b = self.points[result.abstract_path[i] as usize];
+	    draw_line(a.x, a.y, b.x, b.y, 2.0, ORANGE);
+	}
+
+	for i in 1 .. result.path.len() {
+	    let a = self.points[result.path[i - 1] as usize];
+	    let b = self.points[result.path[i] as usize];
+	    draw_line(a.x, a.y, b.x, b.y, 3.0, YELLOW);
+	}
+
+	for i in &result.abstract_path {
+	    let p = self.points[*i as usize];
+	    draw_circle(p.x, p.y, 5.0, ORANGE);
+	}
+
+	draw_text(&self.hpa_label, 10.0, 20.0, 24.0, WHITE);
+    }
+
+    fn render_steering(&self, tex_mgr: &TextureMgr) {
+	if self.steer_agents.is_empty() {
+	    return;
+	}
+
+	for i in 1 .. self.steer_path.len() {
+	    let a = self.steer_path[i - 1];
+	    let b = self.steer_path[i];
+	    draw_line(a.x, a.y, b.x, b.y, 2.0, DARKGREEN);
+	}
+
+	let robot_texture = tex_mgr.get_texture(&ROBOT_PATH.to_string());
+	let size = self.space_width * STEER_ROBOT_SIZE;
+
+	for (i, agent) in self.steer_agents.iter().enumerate() {
+	    if !self.steer_path.is_empty() {
+		let t = self.steer_targets[i];
+		draw_line(agent.position.x, agent.position.y, t.x, t.y, 1.0, YELLOW);
+		draw_circle(t.x, t.y, 2.5, YELLOW);
+	    }
+
+	    // the robot faces the camera, so just mirror it when heading left
+	    let robot_params = DrawTextureParams {
+		dest_size: Some(Vec2::new(size, size)),
+		flip_x: agent.velocity.x < 0.0,
+		..Default::default()
+	    };
+
+	    draw_texture_ex(
+		*robot_texture,
+		agent.position.x - size * 0.5,
+		agent.position.y - size * 0.5,
+		WHITE,
+		robot_params,
+	    );
+	}
+
+	let label = if self.steer_path.is_empty() {
+	    format!("steering: no path, {} robots wandering", self.steer_agents.len())
+	} else {
+	    format!("steering: {} robots following the path", self.steer_agents.len())
+	};
+	draw_text(&label, 10.0, 20.0, 24.0, WHITE);
+    }
+
+    fn render_mapf(&self) {
+	for (a, (start, goal)) in self.mapf_agents.iter().enumerate() {
+	    let c = AGENT_COLORS[a % AGENT_COLORS.len()];
+	    let s = self.points[*start as usize];
+	    let g = self.points[*goal as usize];
+	    draw_rectangle_lines(s.x - 6.0, s.y - 6.0, 12.0, 12.0, 2.0, c);
+	    draw_circle_lines(g.x, g.y, 7.0, 2.0, c);
+	}
+
+	if self.mapf_pending_start >= 0 {
+	    let s = self.points[self.mapf_pending_start as usize];
+	    draw_rectangle_lines(s.x - 6.0, s.y - 6.0, 12.0, 12.0, 2.0, WHITE);
+	}
+
+	if self.mapf_failed {
+	    draw_text("CBS: no joint plan found", 10.0, 20.0, 24.0, WHITE);
+	}
+
+	let solution = match &self.mapf_solution {
+	    None => { return; }
+	    Some(s) => s,
+	};
+
+	// loop the playback, pausing a couple of steps at the end
+	let horizon = solution.paths.iter().map(|p| p.len()).max().unwrap_or(1) as f32 + 2.0;
+	let t = self.mapf_time % horizon;
+	let step = t.floor() as i32;
+	let frac = t - t.floor();
+
+	for (a, path) in solution.paths.iter().enumerate() {
+	    let c = AGENT_COLORS[a % AGENT_COLORS.len()];
+
+	    for i in 1 .. path.len() {
+		let p0 = self.points[path[i - 1] as usize];
+		let p1 = self.points[path[i] as usize];
+		draw_line(p0.x, p0.y, p1.x, p1.y, 1.0, c);
+	    }
+
+	    let here = self.points[position_at(path, step) as usize];
+	    let next = self.points[position_at(path, step + 1) as usize];
+	    let pos = here + (next - here).scale(frac);
+	    draw_circle(pos.x, pos.y, self.space_width * 0.4, c);
+	}
+
+	let label = format!("CBS: {} agents, sum of costs {}, {} constraint tree nodes",
+			    solution.paths.len(),
+			    solution.cost,
+			    solution.ct_expanded);
+	draw_text(&label, 10.0, 20.0, 24.0, WHITE);
+    }
+}
+
+impl SquareNavScreen {
+    fn render_cooperative(&self) {
+	if !self.coop_paths.is_empty() {
+	    self.render_cooperative_paths();
+	    return;
+	}
+
+	let planner = match &self.coop_planner {
+	    None => { return; }
+	    Some(p) => p,
+	};
+
+	let mut arrived = 0;
+
+	for (a, (_, goal)) in planner.agents.iter().enumerate() {
+	    let c = AGENT_COLORS[a % AGENT_COLORS.len()];
+
+	    let g = self.points[*goal as usize];
+	    draw_circle_lines(g.x, g.y, 6.0, 1.5, c);
+
+	    // what's left of the reserved window
+	    let plan = &planner.plans[a];
+	    for i in 1 .. plan.len() {
+		let p0 = self.points[plan[i - 1] as usize];
+		let p1 = self.points[plan[i] as usize];
+		draw_line(p0.x, p0.y, p1.x, p1.y, 1.0, c);
+	    }
+
+	    let here = self.points[self.coop_prev_positions[a] as usize];
+	    let next = self.points[planner.positions[a] as usize];
+	    let pos = here + (next - here).scale(self.coop_time);
+	    draw_circle(pos.x, pos.y, self.space_width * 0.4, c);
+
+	    if planner.positions[a] == *goal {
+		arrived += 1;
+	    }
+	}
+
+	let label = format!("WHCA* window {}: {} agents, step {}, {} arrived",
+			    COOP_WINDOW,
+			    planner.agents.len(),
+			    planner.time,
+			    arrived);
+	draw_text(&label, 10.0, 20.0, 24.0, WHITE);
+    }
+
+    fn render_cooperative_paths(&self) {
+	// loop the playback, pausing a couple of steps at the end
+	let longest = self.coop_paths.iter().flatten().map(|p| p.len()).max().unwrap_or(1);
+	let t = self.coop_time % (longest as f32 + 2.0);
+	let step = t.floor() as i32;
+	let frac = t - t.floor();
+
+	let mut planned = 0;
+	let mut cost = 0;
+
+	for (a, (start, goal)) in self.coop_agents.iter().enumerate() {
+	    let c = AGENT_COLORS[a % AGENT_COLORS.len()];
+
+	    let g = self.points[*goal as usize];
+	    draw_circle_lines(g.x, g.y, 6.0, 1.5, c);
+
+	    // agents without a plan stay where they started
+	    let stuck = [*start];
+	    let path: &[i32] = match &self.coop_paths[a] {
+		None => &stuck,
+		Some(p) => {
+		    planned += 1;
+		    cost += p.len() as i32 - 1;
+		    p
+		},
+	    };
+
+	    for i in 1 .. path.len() {
+		let p0 = self.points[path[i - 1] as usize];
+		let p1 = self.points[path[i] as usize];
+		draw_line(p0.x, p0.y, p1.x, p1.y, 1.0, c);
+	    }
+
+	    let here = self.points[position_at(path, step) as usize];
+	    let next = self.points[position_at(path, step + 1) as usize];
+	    let pos = here + (next - here).scale(frac);
+	    draw_circle(pos.x, pos.y, self.space_width * 0.4, c);
+	}
+
+	let label = format!("cooperative A*: {} agents, {} planned, sum of costs {}",
+			    self.coop_agents.len(),
+			    planned,
+			    cost);
+	draw_text(&label, 10.0, 20.0, 24.0, WHITE);
+    }
+}
+
+impl NavGraph for SquareNavScreen {
+    fn node_count(&self) -> usize {
+	self.points.len()
+    }
+
+    fn node_point(&self, i: i32) -> Vec2f {
+	self.points[i as usize]
+    }
+
+    fn neighbors(&self, i: i32) -> Vec<(i32, f32)> {
+	let p = self.points[i as usize];
+
+	self.get_neighbor_space_indices(i)
+	    .into_iter()
+	    .filter(|n| !self.is_blocked(*n))
+	    .map(|n| (n, (self.points[n as usize] - p).mag()))
+	    .collect()
+    }
+
+    fn is_open(&self, i: i32) -> bool {
+	!self.is_blocked(i)
+    }
+}
+
+#[async_trait]
+impl Screen for SquareNavScreen {
+    fn is_loaded(&self) -> bool {
+	self.is_loaded_flag
+    }
+
+    async fn load(&mut self, tex_mgr: &mut TextureMgr) {
+	let texture_filenames = vec!(
+	    ROBOT_PATH.to_string(),
+	);
+
+	tex_mgr.load(texture_filenames).await;
+
+	self.is_loaded_flag = true;
+    }
+
+    fn tick(&mut self, dt: f32) {
+	self.time_elapsed += dt;
+
+	if is_key_down(KeyCode::Escape) {
+	    self.is_complete_flag = true;
+	    return;
+	}
+
+	if is_key_down(KeyCode::S) {
+	    let m_pos:Vec2 = mouse_position().into();
+	    let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
+	    let idx = self.find_index(&mouse_pos_vec);
+	    self.start_index = idx;
+	}
+
+	if is_key_down(KeyCode::E) {
+	    let m_pos:Vec2 = mouse_position().into();
+	    let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
+	    let idx = self.find_index(&mouse_pos_vec);
+	    self.end_index = idx;
+	}
+
+	if is_key_down(KeyCode::R) {
+	    self.reset();
+	}
+
+	// walls toggled this frame, for LPA* to repair around
+	let mut changed = Vec::<i32>::new();
+
+	if is_key_pressed(KeyCode::W) ||
+	    is_mouse_button_pressed(MouseButton::Left) ||
+	    is_mouse_button_pressed(MouseButton::Right) {
+	    self.lpa_repaired.clear();
+	}
+
+	if is_key_pressed(KeyCode::W) {
+	    println!("W");
+	    let m_pos:Vec2 = mouse_position().into();
+	    let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
+	    let idx = self.find_index(&mouse_pos_vec);
+
+	    if self.wall_nodes.contains(&idx) {
+		self.wall_nodes.remove(&idx);
+	    } else {
+		self.wall_nodes.insert(idx);
+	    }
+	    changed.push(idx);
+	}
+
+	if is_mouse_button_down(MouseButton::Left) {
+
+	    let paint_radius = self.space_width * 1.5;
+
+	    let m_pos:Vec2 = mouse_position().into();
+	    let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
+	    for idx in 0 .. self.points.len() {
+		let p = &self.points[idx];
+
+		let dist = (mouse_pos_vec - *p).mag();
+		if dist < paint_radius && self.wall_nodes.insert(idx as i32) {
+		    changed.push(idx as i32);
+		}
+	    }
+	}
+
+	if is_mouse_button_down(MouseButton::Right) {
+	    let m_pos:Vec2 = mouse_position().into();
+	    let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
+	    let idx = self.find_index(&mouse_pos_vec);
+
+	    if self.wall_nodes.remove(&idx) {
+		changed.push(idx);
+	    }
+	}
+
+	// the slower rebuilds wait until the walls stop changing
+	let painting = is_mouse_button_down(MouseButton::Left) || is_mouse_button_down(MouseButton::Right);
+
+	if is_key_pressed(KeyCode::I) {
+	    self.footprint_mode = match self.footprint_mode {
+		FootprintMode::Point => FootprintMode::Circle,
+		FootprintMode::Circle => FootprintMode::Cart,
+		FootprintMode::Cart => FootprintMode::Point,
+	    };
+	    self.lpa_repaired.clear();
+	    changed.append(&mut self.update_inflation());
+	} else if !changed.is_empty() && self.footprint().is_some() {
+	    self.inflation_stale = true;
+	}
+
+	if self.inflation_stale && !painting {
+	    changed.append(&mut self.update_inflation());
+	}
+
+	if !changed.is_empty() {
+	    // with long moves or no corner cutting, a wall also changes
+	    // moves that only pass by it
+	    let changed = self.spaces_affected_by(&changed);
+	    self.repair_lpa(&changed);
+
+	    if self.clearance.is_some() ||
+		!self.clearance_path.is_empty() || !self.clearance_plain_path.is_empty() {
+		self.clearance_stale = true;
+	    }
+
+	    if !self.flow_next.is_empty() {
+		self.build_flow_field();
+	    }
+
+	    if self.hpa.is_some() {
+		self.hpa_changed.extend(changed.iter());
+	    }
+
+	    // distances through the old walls could now overestimate
+	    if self.landmarks.is_some() {
+		self.landmarks_stale = true;
+	    }
+
+	    self.ch = None;
+	    self.ch_result = None;
+	    self.refresh_heuristic_check();
+	}
+
+	// landmarks cost a Dijkstra each, too much for every frame of painting
+	if self.landmarks_stale && self.landmarks.is_some() && !painting {
+	    self.build_landmarks();
+	}
+
+	// cluster rebuilds, and a plain A* to compare against
+	if !self.hpa_changed.is_empty() && !painting {
+	    let changed: Vec<i32> = self.hpa_changed.drain().collect();
+	    self.update_hpa(&changed);
+	}
+
+	// a distance transform and two searches
+	if self.clearance_stale && !painting {
+	    self.clearance_stale = false;
+	    if self.clearance.is_some() {
+		self.build_clearance_field();
+	    }
+	    if !self.clearance_path.is_empty() || !self.clearance_plain_path.is_empty() {
+		self.run_clearance_search();
+	    }
+	}
+
+	if is_key_pressed(KeyCode::G) {
+	    self.start_flow_field();
+	}
+
+	if !self.flow_agents.is_empty() {
+	    self.advance_flow_agents(dt);
+	}
+
+	// P places a start, then its goal; M plans them all together
+	if is_key_pressed(KeyCode::P) {
+	    let m_pos:Vec2 = mouse_position().into();
+	    let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
+	    let idx = self.find_index(&mouse_pos_vec);
+	    self.place_mapf_endpoint(idx);
+	}
+
+	if is_key_pressed(KeyCode::M) {
+	    self.start_mapf();
+	}
+
+	if self.mapf_solution.is_some() {
+	    self.mapf_time += dt * MAPF_STEPS_PER_SECOND;
+	}
+
+	// C: cooperative planning, 3: windowed HCA* or plain cooperative A*
+	if is_key_pressed(KeyCode::C) {
+	    self.start_cooperative();
+	}
+
+	if is_key_pressed(KeyCode::Key3) {
+	    self.coop_windowed = !self.coop_windowed;
+	    if self.coop_planner.is_some() || !self.coop_paths.is_empty() {
+		self.start_cooperative();
+	    }
+	}
+
+	self.advance_cooperative(dt);
+
+	// H: clearance heatmap, T: search keeping away from walls
+	if is_key_pressed(KeyCode::H) {
+	    self.show_clearance = !self.show_clearance;
+	    if self.show_clearance {
+		self.build_clearance_field();
+	    }
+	}
+
+	if is_key_pressed(KeyCode::T) {
+	    self.start_clearance_search();
+	}
+
+	// V: robots steer along the start to end path
+	if is_key_pressed(KeyCode::V) {
+	    self.start_steering();
+	}
+
+	self.advance_steering(dt);
+
+	if is_key_pressed(KeyCode::L) {
+	    self.start_lpa();
+	}
+
+	// U: hierarchical search, X: show the clusters and abstract graph
+	if is_key_pressed(KeyCode::U) {
+	    self.start_hpa_search();
+	}
+
+	if is_key_pressed(KeyCode::X) {
+	    self.show_hpa = !self.show_hpa;
+	    if self.show_hpa && self.hpa.is_none() {
+		self.build_hpa();
+	    }
+	}
+
+	// Y: landmark heuristic, placed farthest then avoid, then off
+	if is_key_pressed(KeyCode::Y) {
+	    self.next_landmark_heuristic();
+	    self.refresh_heuristic_check();
+	}
+
+	// 1: 4, 8, 16 or 32 neighbors, 2: corner cutting rule
+	if is_key_pressed(KeyCode::Key1) {
+	    let i = CONNECTIVITIES.iter().position(|c| *c == self.connectivity).unwrap_or(0);
+	    self.set_neighborhood(CONNECTIVITIES[(i + 1) % CONNECTIVITIES.len()], self.corner_cutting);
+	}
+
+	if is_key_pressed(KeyCode::Key2) {
+	    let i = CORNER_CUTTINGS.iter().position(|c| *c == self.corner_cutting).unwrap_or(0);
+	    self.set_neighborhood(self.connectivity, CORNER_CUTTINGS[(i + 1) % CORNER_CUTTINGS.len()]);
+	}
+
+	// Tab: next heuristic, D: check them all against true distances
+	if is_key_pressed(KeyCode::Tab) {
+	    self.next_heuristic();
+	    self.refresh_heuristic_check();
+	}
+
+	if is_key_pressed(KeyCode::D) {
+	    if self.heuristic_summary.is_empty() {
+		self.run_heuristic_check();
+	    } else {
+		self.heuristic_check = None;
+		self.heuristic_summary.clear();
+		self.heuristic_check_stale = false;
+	    }
+	}
+
+	// so is a Dijkstra per heuristic
+	if self.heuristic_check_stale && !painting {
+	    self.run_heuristic_check();
+	}
+
+	// Z: contraction hierarchy query, contracting first if need be
+	if is_key_pressed(KeyCode::Z) {
+	    self.start_ch_search();
+	}
+
+	// K: generate a new map, J: next generator with the same seed
+	if is_key_pressed(KeyCode::K) {
+	    self.generate_map(gen_range::<u32>(0, u32::MAX) as u64);
+	}
+
+	if is_key_pressed(KeyCode::J) {
+	    self.maze_kind = (self.maze_kind + 1) % MAZE_KINDS.len();
+	    let seed = self.maze_seed.unwrap_or(0);
+	    self.generate_map(seed);
+	}
+
+	// once per press, or holding F would keep restarting the search
+	if is_key_pressed(KeyCode::F) {
+	    self.clear_searches();
+	    self.sub_mode = SubMode::FindPath;
+	}
+
+	if is_key_pressed(KeyCode::B) {
+	    self.start_bidirectional(true);
+	}
+
+	if is_key_pressed(KeyCode::N) {
+	    self.start_bidirectional(false);
+	}
+
+	if is_key_pressed(KeyCode::Up) {
+	    self.epsilon = (self.epsilon + EPSILON_STEP).min(MAX_EPSILON);
+	}
+
+	if is_key_pressed(KeyCode::Down) {
+	    self.epsilon = (self.epsilon - EPSILON_STEP).max(1.0);
+	}
+
+	// A: weighted A*, Q: ARA* from epsilon down to 1, O: focal search
+	for key in [KeyCode::A, KeyCode::Q, KeyCode::O] {
+	    if is_key_pressed(key) {
+		self.start_anytime(key);
+	    }
+	}
+
+	if self.sub_mode == SubMode::FindPath {
+	    self.advance_a_star();
+	}
+
+	if self.sub_mode == SubMode::FindBidirectional {
+	    self.advance_bidirectional();
+	}
+
+	if self.sub_mode == SubMode::FindAnytime {
+	    self.advance_anytime();
+	}
+
+	if self.sub_mode == SubMode::AddPoints {
+	    self.start_index = gen_range::<i32>(0, self.points.len() as i32);
+	    self.end_index = gen_range::<i32>(0, self.points.len() as i32);
+	    println!("start {} end {}", self.start_index, self.end_index);
+
+	    self.sub_mode = SubMode::Show;
+	}
+    }
+
+    fn is_complete(&self) -> bool {
+	self.is_complete_flag
+    }
+
+    fn render(&self, tex_mgr: &TextureMgr) {
+	clear_background(GRAY);
+
+	self.render_clearance_heatmap();
+	self.render_heuristic_heatmap();
+
+	let mut dot_size = 2.5;
+
+	if self.points.len() > 0 {
+	    for i in 0 .. self.points.len() {
+		let p = &self.points[i];
+
+		let mut c = BLUE;
+
+		if i as i32 == self.start_index {
 		    dot_size = 7.5;
 		    c = GREEN;
 		} else if i as i32 == self.end_index {
@@ -569,6 +2462,13 @@ impl Screen for SquareNavScreen {
 		if self.wall_nodes.contains(&i_i32) {
 		    dot_size = 15.0;
 		    c = PURPLE;
+		} else if self.inflated_nodes.contains(&i_i32) {
+		    // out of bounds for the footprint, but not a wall
+		    draw_rectangle(p.x - self.space_width * 0.5,
+				   p.y - self.space_width * 0.5,
+				   self.space_width,
+				   self.space_width,
+				   Color::new(0.5, 0.1, 0.6, 0.3));
 		}
 
 		/*
@@ -628,6 +2528,32 @@ impl Screen for SquareNavScreen {
 		      1.5,
 		      BLUE);
 	}
+
+	self.render_bidirectional();
+	self.render_anytime();
+	self.render_lpa();
+	self.render_flow_field();
+	self.render_mapf();
+	self.render_cooperative();
+	self.render_steering(tex_mgr);
+	self.render_clearance_paths();
+	self.render_hpa();
+	self.render_landmarks();
+	self.render_ch();
+	self.render_heuristic_check();
+
+	let footprint_name = match self.footprint_mode {
+	    FootprintMode::Point => "point",
+	    FootprintMode::Circle => "circle",
+	    FootprintMode::Cart => "cart",
+	};
+	let mut label = format!("epsilon {:.2}  footprint {}  moves {}  corners {}  heuristic {}",
+				self.epsilon, footprint_name, self.connectivity.count(),
+				self.corner_cutting.name(), self.heuristic_name(self.heuristic));
+	if let Some(seed) = self.maze_seed {
+	    label += &format!("  map {} seed {}", MAZE_KINDS[self.maze_kind].name(), seed);
+	}
+	draw_text(&label, 10.0, screen_height() - 10.0, 24.0, WHITE);
     }
 
     fn as_any(&self) -> &dyn Any {
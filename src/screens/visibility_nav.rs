@@ -0,0 +1,305 @@
+// screens/visibility_nav.rs
+
+use std::any::Any;
+
+use macroquad::prelude::*;
+
+use async_trait::async_trait;
+
+use crate::screens::Screen;
+
+use crate::texture_mgr::TextureMgr;
+
+use crate::big_dice_games::math::vector::Vec2f;
+use crate::big_dice_games::math::triangulation::segments_cross;
+use crate::big_dice_games::math::triangulation::Triangulation;
+
+use crate::big_dice_games::ai::astar::SearchResult;
+use crate::big_dice_games::ai::navmesh::point_in_polygon;
+use crate::big_dice_games::ai::visibility::VisibilityGraph;
+
+// graph nodes revealed per second
+const REVEAL_RATE: f32 = 20.0;
+
+pub struct VisibilityScreen
+{
+    time_elapsed: f32,
+    is_complete_flag: bool,
+
+    obstacles: Vec<Vec<Vec2f>>,
+    // the obstacles cut into triangles for filling in
+    obstacle_fill: Vec<[Vec2f; 3]>,
+    // corners placed so far for the polygon being drawn
+    drawing: Vec<Vec2f>,
+
+    start: Vec2f,
+    goal: Vec2f,
+
+    graph: VisibilityGraph,
+    result: SearchResult,
+    reveal: f32,
+    show_graph: bool,
+}
+
+// a few shapes so there's something to path round before drawing
+fn default_obstacles() -> Vec<Vec<Vec2f>> {
+    let shape = |points: &[(f32, f32)]| points.iter().map(|(x, y)| Vec2f::new(*x, *y)).collect();
+
+    vec![shape(&[(250.0, 150.0), (400.0, 180.0), (350.0, 420.0), (220.0, 360.0)]),
+	 shape(&[(550.0, 300.0), (800.0, 300.0), (800.0, 360.0), (620.0, 360.0),
+		 (620.0, 600.0), (550.0, 600.0)]),
+	 shape(&[(300.0, 600.0), (420.0, 520.0), (460.0, 700.0)]),
+	 shape(&[(850.0, 120.0), (950.0, 160.0), (930.0, 260.0), (820.0, 230.0)])]
+}
+
+// the polygons can be concave, so split them with a constrained
+// triangulation rather than fanning
+fn fill_triangles(polygon: &[Vec2f]) -> Vec<[Vec2f; 3]> {
+    let mut triangulation = Triangulation::delaunay(polygon);
+    for i in 0 .. polygon.len() {
+	triangulation.insert_constraint(i, (i + 1) % polygon.len());
+    }
+    triangulation.retain_triangles(|tri, t| point_in_polygon(polygon, tri.centroid(t)));
+
+    triangulation.triangles.iter()
+	.map(|t| [triangulation.points[t[0]], triangulation.points[t[1]], triangulation.points[t[2]]])
+	.collect()
+}
+
+impl VisibilityScreen {
+    pub fn new() -> VisibilityScreen {
+	let start = Vec2f::new(60.0, 60.0);
+	let goal = Vec2f::new(1000.0, 780.0);
+	let graph = VisibilityGraph::build(&[], start, goal);
+	let result = graph.find_path();
+
+	let mut screen = VisibilityScreen {
+	    time_elapsed: 0.0,
+	    is_complete_flag: false,
+	    obstacles: default_obstacles(),
+	    obstacle_fill: Vec::<[Vec2f; 3]>::new(),
+	    drawing: Vec::<Vec2f>::new(),
+	    start: start,
+	    goal: goal,
+	    graph: graph,
+	    result: result,
+	    reveal: 0.0,
+	    show_graph: true,
+	};
+
+	screen.rebuild();
+	screen
+    }
+
+    pub fn reset(&mut self) {
+	self.time_elapsed = 0.0;
+	self.is_complete_flag = false;
+	self.obstacles = default_obstacles();
+	self.drawing.clear();
+	self.rebuild();
+    }
+
+    fn rebuild(&mut self) {
+	self.obstacle_fill = self.obstacles.iter().flat_map(|o| fill_triangles(o)).collect();
+	self.replan();
+    }
+
+    // the obstacles are unchanged, only the start or goal moved
+    fn replan(&mut self) {
+	self.graph = VisibilityGraph::build(&self.obstacles, self.start, self.goal);
+	self.result = self.graph.find_path();
+	self.reveal = 0.0;
+    }
+
+    // somewhere new and clear of the obstacles to put the start or goal
+    fn can_move_to(&self, from: Vec2f, to: Vec2f) -> bool {
+	(from.x != to.x || from.y != to.y) && !self.in_obstacle(to)
+    }
+
+    fn in_obstacle(&self, p: Vec2f) -> bool {
+	self.obstacles.iter().any(|o| point_in_polygon(o, p))
+    }
+
+    // would the corners drawn so far make a simple polygon that
+    // stays clear of the others?
+    fn can_close(&self) -> bool {
+	let n = self.drawing.len();
+	if n < 3 {
+	    return false;
+	}
+
+	let edges: Vec<(Vec2f, Vec2f)> = (0 .. n)
+	    .map(|i| (self.drawing[i], self.drawing[(i + 1) % n]))
+	    .collect();
+
+	for i in 0 .. n {
+	    for j in i + 1 .. n {
+		if segments_cross(edges[i].0, edges[i].1, edges[j].0, edges[j].1) {
+		    return false;
+		}
+	    }
+
+	    for o in &self.obstacles {
+		for k in 0 .. o.len() {
+		    if segments_cross(edges[i].0, edges[i].1, o[k], o[(k + 1) % o.len()]) {
+			return false;
+		    }
+		}
+	    }
+	}
+
+	let covers_endpoint = point_in_polygon(&self.drawing, self.start) ||
+	    point_in_polygon(&self.drawing, self.goal);
+	let swallows_obstacle = self.obstacles.iter().any(|o| point_in_polygon(&self.drawing, o[0]));
+
+	!covers_endpoint && !swallows_obstacle
+    }
+}
+
+#[async_trait]
+impl Screen for VisibilityScreen {
+    fn is_loaded(&self) -> bool {
+	true
+    }
+
+    async fn load(&mut self, _tex_mgr: &mut TextureMgr) {
+    }
+
+    fn tick(&mut self, dt: f32) {
+	self.time_elapsed += dt;
+
+	if is_key_down(KeyCode::Escape) {
+	    self.is_complete_flag = true;
+	    return;
+	}
+
+	let m_pos:Vec2 = mouse_position().into();
+	let mouse_pos_vec = Vec2f::new(m_pos.x, m_pos.y);
+
+	// left click adds a corner, right click or enter closes the shape
+	if is_mouse_button_pressed(MouseButton::Left) && !self.in_obstacle(mouse_pos_vec) {
+	    self.drawing.push(mouse_pos_vec);
+	}
+
+	if is_mouse_button_pressed(MouseButton::Right) || is_key_pressed(KeyCode::Enter) {
+	    if self.can_close() {
+		self.obstacles.push(self.drawing.clone());
+		self.rebuild();
+	    } else {
+		println!("can't close that polygon");
+	    }
+	    self.drawing.clear();
+	}
+
+	if is_key_pressed(KeyCode::Backspace) {
+	    if self.drawing.pop().is_none() && self.obstacles.pop().is_some() {
+		self.rebuild();
+	    }
+	}
+
+	if is_key_pressed(KeyCode::C) {
+	    self.obstacles.clear();
+	    self.drawing.clear();
+	    self.rebuild();
+	}
+
+	if is_key_pressed(KeyCode::R) {
+	    self.reset();
+	}
+
+	if is_key_pressed(KeyCode::G) {
+	    self.show_graph = !self.show_graph;
+	}
+
+	if is_key_down(KeyCode::S) && self.can_move_to(self.start, mouse_pos_vec) {
+	    self.start = mouse_pos_vec;
+	    self.replan();
+	}
+
+	if is_key_down(KeyCode::E) && self.can_move_to(self.goal, mouse_pos_vec) {
+	    self.goal = mouse_pos_vec;
+	    self.replan();
+	}
+
+	self.reveal += dt * REVEAL_RATE;
+    }
+
+    fn is_complete(&self) -> bool {
+	self.is_complete_flag
+    }
+
+    fn render(&self, _tex_mgr: &TextureMgr) {
+	clear_background(LIGHTGRAY);
+
+	let to_vec2 = |p: Vec2f| vec2(p.x, p.y);
+
+	for [a, b, c] in &self.obstacle_fill {
+	    draw_triangle(to_vec2(*a), to_vec2(*b), to_vec2(*c), DARKGRAY);
+	}
+
+	if self.show_graph {
+	    for (i, edges) in self.graph.edges.iter().enumerate() {
+		let a = self.graph.points[i];
+		for (j, _) in edges {
+		    if (*j as usize) < i {
+			continue;
+		    }
+		    let b = self.graph.points[*j as usize];
+		    draw_line(a.x, a.y, b.x, b.y, 1.0, Color::new(0.3, 0.3, 0.6, 0.35));
+		}
+	    }
+	}
+
+	for p in &self.graph.points {
+	    draw_circle(p.x, p.y, 4.0, DARKBLUE);
+	}
+
+	let shown = (self.reveal as usize).min(self.result.expanded.len());
+	let search_done = shown == self.result.expanded.len();
+
+	for i in &self.result.expanded[.. shown] {
+	    let p = self.graph.points[*i as usize];
+	    draw_circle(p.x, p.y, 6.0, ORANGE);
+	}
+
+	if search_done {
+	    for w in self.result.path.windows(2) {
+		let a = self.graph.points[w[0] as usize];
+		let b = self.graph.points[w[1] as usize];
+		draw_line(a.x, a.y, b.x, b.y, 3.0, DARKGREEN);
+	    }
+	}
+
+	// the polygon being drawn
+	for w in self.drawing.windows(2) {
+	    draw_line(w[0].x, w[0].y, w[1].x, w[1].y, 2.0, MAROON);
+	}
+	for p in &self.drawing {
+	    draw_circle(p.x, p.y, 4.0, MAROON);
+	}
+	if let Some(last) = self.drawing.last() {
+	    let (mx, my) = mouse_position();
+	    draw_line(last.x, last.y, mx, my, 1.0, MAROON);
+	}
+
+	draw_circle(self.start.x, self.start.y, 7.5, GREEN);
+	draw_circle(self.goal.x, self.goal.y, 7.5, RED);
+
+	let label = if !search_done {
+	    format!("visibility: searching, {} expanded", shown)
+	} else if self.result.found() {
+	    format!("visibility: {} nodes, {} edges, path {:.0} long",
+		    self.graph.points.len(), self.graph.edge_count(), self.result.cost)
+	} else {
+	    format!("visibility: {} nodes, {} edges, no path",
+		    self.graph.points.len(), self.graph.edge_count())
+	};
+	draw_text(&label, 10.0, 20.0, 24.0, BLACK);
+	draw_text("click: corner  right click: close  backspace: undo  C: clear  G: graph",
+		  10.0, screen_height() - 10.0, 20.0, BLACK);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+	self
+    }
+}
@@ -2,16 +2,162 @@
 
 use macroquad::prelude::*;
 
-struct Sprite {
-    x: f32,
-    y: f32,
+// A textured quad placed in the world. (x, y) is where the pivot
+// lands; the pivot is given as a fraction of the sprite, so (0.5, 0.5)
+// spins about the center and (0, 0) about the top left corner.
+pub struct Sprite {
+    pub x: f32,
+    pub y: f32,
 
-    texture: Texture2D,
+    pub texture: Texture2D,
 
-    theta: f32,
+    // radians, clockwise on screen
+    pub theta: f32,
 
-    width: f32,
-    height: f32,
+    // unscaled size, the texture's own by default
+    pub width: f32,
+    pub height: f32,
+
+    pub pivot_x: f32,
+    pub pivot_y: f32,
+
+    pub scale_x: f32,
+    pub scale_y: f32,
+
+    // higher draws on top
+    pub z: i32,
 }
 
+impl Sprite {
+    pub fn new(texture: Texture2D) -> Sprite {
+	Sprite {
+	    x: 0.0,
+	    y: 0.0,
+	    texture: texture,
+	    theta: 0.0,
+	    width: texture.width(),
+	    height: texture.height(),
+	    pivot_x: 0.5,
+	    pivot_y: 0.5,
+	    scale_x: 1.0,
+	    scale_y: 1.0,
+	    z: 0,
+	}
+    }
+
+    pub fn scaled_width(&self) -> f32 {
+	self.width * self.scale_x
+    }
+
+    pub fn scaled_height(&self) -> f32 {
+	self.height * self.scale_y
+    }
+
+    // move so the unrotated sprite's top left corner is at (x, y),
+    // handy for laying things out like draw_texture does
+    pub fn set_top_left(&mut self, x: f32, y: f32) {
+	self.x = x + self.pivot_x * self.scaled_width();
+	self.y = y + self.pivot_y * self.scaled_height();
+    }
+
+    // sprite local point, in unscaled pixels from the top left, to world
+    pub fn local_to_world(&self, local: Vec2) -> Vec2 {
+	let offset = vec2((local.x - self.pivot_x * self.width) * self.scale_x,
+			  (local.y - self.pivot_y * self.height) * self.scale_y);
+	let (s, c) = self.theta.sin_cos();
+
+	vec2(self.x + offset.x * c - offset.y * s,
+	     self.y + offset.x * s + offset.y * c)
+    }
+
+    pub fn world_to_local(&self, world: Vec2) -> Vec2 {
+	let d = world - vec2(self.x, self.y);
+	let (s, c) = self.theta.sin_cos();
+
+	// rotate back by -theta, then undo the scale
+	let unrotated = vec2(d.x * c + d.y * s,
+			     -d.x * s + d.y * c);
+
+	vec2(unrotated.x / self.scale_x + self.pivot_x * self.width,
+	     unrotated.y / self.scale_y + self.pivot_y * self.height)
+    }
+
+    // oriented bounds: top left, top right, bottom right, bottom left
+    pub fn corners(&self) -> [Vec2; 4] {
+	[
+	    self.local_to_world(vec2(0.0, 0.0)),
+	    self.local_to_world(vec2(self.width, 0.0)),
+	    self.local_to_world(vec2(self.width, self.height)),
+	    self.local_to_world(vec2(0.0, self.height)),
+	]
+    }
+
+    // axis aligned box around the oriented bounds
+    pub fn bounds(&self) -> Rect {
+	let corners = self.corners();
 
+	let mut min = corners[0];
+	let mut max = corners[0];
+	for c in &corners[1..] {
+	    min = min.min(*c);
+	    max = max.max(*c);
+	}
+
+	Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+
+    pub fn contains_point(&self, world: Vec2) -> bool {
+	let local = self.world_to_local(world);
+	local.x >= 0.0 && local.x <= self.width &&
+	    local.y >= 0.0 && local.y <= self.height
+    }
+
+    pub fn draw(&self) {
+	let w = self.scaled_width();
+	let h = self.scaled_height();
+
+	// negative scale mirrors; draw_texture_ex wants that as a flip
+	let params = DrawTextureParams {
+	    dest_size: Some(vec2(w.abs(), h.abs())),
+	    source: None,
+	    rotation: self.theta,
+	    flip_x: w < 0.0,
+	    flip_y: h < 0.0,
+	    pivot: Some(vec2(self.x, self.y)),
+	};
+
+	let left = if w < 0.0 { self.x - (1.0 - self.pivot_x) * w.abs() } else { self.x - self.pivot_x * w };
+	let top = if h < 0.0 { self.y - (1.0 - self.pivot_y) * h.abs() } else { self.y - self.pivot_y * h };
+
+	draw_texture_ex(
+	    self.texture,
+	    left,
+	    top,
+	    WHITE,
+	    params,
+	);
+    }
+
+    pub fn draw_bounds(&self, color: Color) {
+	let corners = self.corners();
+	for i in 0 .. 4 {
+	    let a = corners[i];
+	    let b = corners[(i + 1) % 4];
+	    draw_line(a.x, a.y, b.x, b.y, 2.0, color);
+	}
+
+	let r = self.bounds();
+	draw_rectangle_lines(r.x, r.y, r.w, r.h, 1.0, color);
+	draw_circle(self.x, self.y, 3.0, color);
+    }
+}
+
+// draw back to front by z; equal z keeps list order
+pub fn draw_sprites(sprites: &[Sprite]) {
+    let mut order: Vec<&Sprite> = sprites.iter().collect();
+    order.sort_by_key(|s| s.z);
+
+    for s in order {
+	s.draw();
+    }
+}
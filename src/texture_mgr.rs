@@ -38,6 +38,34 @@ impl TextureMgr {
 	}
     }
 
+    // like load, but textures that won't load are counted and reported
+    // instead of panicking
+    pub async fn try_load(&mut self, texture_names: Vec<String>) -> Result<(), String> {
+	self.loaded_count = 0;
+	self.error_count = 0;
+	self.target_count = texture_names.len() as u32;
+
+	let mut errors = Vec::<String>::new();
+	for n in texture_names {
+	    match load_texture(&n).await {
+		Ok(texture) => {
+		    self.texture_map.insert(n, texture);
+		    self.loaded_count += 1;
+		}
+		Err(e) => {
+		    errors.push(format!("can't load texture {}: {:?}", n, e));
+		    self.error_count += 1;
+		}
+	    }
+	}
+
+	if errors.is_empty() {
+	    Ok(())
+	} else {
+	    Err(errors.join(", "))
+	}
+    }
+
     pub fn complete(&self) -> bool {
 	self.loaded_count + self.error_count == self.target_count
     }